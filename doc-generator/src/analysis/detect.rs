@@ -0,0 +1,116 @@
+use std::fs;
+use std::path::Path;
+
+use super::summary::ProjectType;
+
+/// Infer the kind of project from marker files in its root.
+///
+/// Language manifests are checked before a `Dockerfile`, since most
+/// containerised projects also ship one.
+pub fn detect_project_type(project_root: &Path) -> ProjectType {
+    let read = |name: &str| fs::read_to_string(project_root.join(name)).ok();
+
+    if let Some(manifest) = read("Cargo.toml") {
+        return if manifest.contains("[[bin]]") || project_root.join("src/main.rs").is_file() {
+            ProjectType::CliTool
+        } else {
+            ProjectType::Library
+        };
+    }
+
+    let pyproject = read("pyproject.toml");
+    let setup = read("setup.py");
+    if pyproject.is_some() || setup.is_some() {
+        let has_entry_points = pyproject.is_some_and(|manifest| {
+            manifest.contains("[project.scripts]") || manifest.contains("[tool.poetry.scripts]")
+        }) || setup.is_some_and(|script| script.contains("console_scripts"));
+        return if has_entry_points {
+            ProjectType::CliTool
+        } else {
+            ProjectType::Library
+        };
+    }
+
+    if project_root.join("package.json").is_file() {
+        return ProjectType::WebApplication;
+    }
+
+    if project_root.join("Dockerfile").is_file() {
+        return ProjectType::SystemService;
+    }
+
+    ProjectType::Unknown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn project(files: &[(&str, &str)]) -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        for (name, content) in files {
+            let path = temp_dir.path().join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        temp_dir
+    }
+
+    fn detect(files: &[(&str, &str)]) -> ProjectType {
+        detect_project_type(project(files).path())
+    }
+
+    #[test]
+    fn test_detect_rust_projects() {
+        let lib = "[package]\nname = \"demo\"\n";
+        assert_eq!(detect(&[("Cargo.toml", lib)]), ProjectType::Library);
+        assert_eq!(
+            detect(&[("Cargo.toml", "[package]\n[[bin]]\nname = \"demo\"\n")]),
+            ProjectType::CliTool
+        );
+        assert_eq!(
+            detect(&[("Cargo.toml", lib), ("src/main.rs", "fn main() {}")]),
+            ProjectType::CliTool
+        );
+    }
+
+    #[test]
+    fn test_detect_python_projects() {
+        assert_eq!(
+            detect(&[("pyproject.toml", "[project]\nname = \"demo\"\n")]),
+            ProjectType::Library
+        );
+        assert_eq!(
+            detect(&[(
+                "pyproject.toml",
+                "[project.scripts]\ndemo = \"demo:main\"\n"
+            )]),
+            ProjectType::CliTool
+        );
+        assert_eq!(
+            detect(&[(
+                "setup.py",
+                "setup(entry_points={'console_scripts': ['demo=demo:main']})"
+            )]),
+            ProjectType::CliTool
+        );
+    }
+
+    #[test]
+    fn test_detect_other_markers() {
+        assert_eq!(
+            detect(&[("package.json", "{}")]),
+            ProjectType::WebApplication
+        );
+        assert_eq!(
+            detect(&[("Dockerfile", "FROM scratch")]),
+            ProjectType::SystemService
+        );
+        assert_eq!(
+            detect(&[("Dockerfile", "FROM rust"), ("Cargo.toml", "[package]")]),
+            ProjectType::Library
+        );
+        assert_eq!(detect(&[("README.md", "# Demo")]), ProjectType::Unknown);
+    }
+}
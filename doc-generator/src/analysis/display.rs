@@ -1,45 +1,107 @@
 use crate::analysis::summary::{
     DirectoryAnalysis, FileAnalysis, Interface, InterfaceType, ProjectAnalysis,
 };
-use std::fmt::{Display, Formatter, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter, Result, Write};
 
-impl Display for InterfaceType {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        let type_str = match self {
-            InterfaceType::Function => "🔧 Function",
-            InterfaceType::Struct => "📦 Struct",
-            InterfaceType::Trait => "🎯 Trait",
-            InterfaceType::Module => "📁 Module",
-            InterfaceType::Api => "🌐 API",
-            InterfaceType::Configuration => "⚙️ Configuration",
-            InterfaceType::DataModel => "🗄️ Data Model",
+/// How section headers and interface labels are decorated when rendering
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RenderStyle {
+    /// Prefix markers with an emoji (the `Display` output)
+    #[default]
+    Emoji,
+    /// ASCII-only markers for terminals and tools that mangle emoji
+    Plain,
+}
+
+impl RenderStyle {
+    /// Join an emoji and a label, dropping the emoji in plain style
+    pub fn marker(self, emoji: &str, label: &str) -> String {
+        match self {
+            RenderStyle::Emoji => format!("{} {}", emoji, label),
+            RenderStyle::Plain => label.to_string(),
+        }
+    }
+}
+
+impl InterfaceType {
+    /// Render as markdown, decorating markers according to `style`
+    pub fn render(&self, style: RenderStyle) -> String {
+        let (emoji, label) = match self {
+            InterfaceType::Function => ("🔧", "Function"),
+            InterfaceType::Struct => ("📦", "Struct"),
+            InterfaceType::Trait => ("🎯", "Trait"),
+            InterfaceType::Module => ("📁", "Module"),
+            InterfaceType::Api => ("🌐", "API"),
+            InterfaceType::Configuration => ("⚙️", "Configuration"),
+            InterfaceType::DataModel => ("🗄️", "Data Model"),
         };
-        write!(f, "{}", type_str)
+        style.marker(emoji, label)
     }
 }
 
-impl Display for Interface {
+impl Display for InterfaceType {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        write!(
-            f,
+        f.write_str(&self.render(RenderStyle::Emoji))
+    }
+}
+
+impl Interface {
+    /// Render as markdown, decorating markers according to `style`
+    pub fn render(&self, style: RenderStyle) -> String {
+        format!(
             "- **{}** (`{}`)\n  {}",
-            self.name, self.interface_type, self.description
+            self.name,
+            self.interface_type.render(style),
+            self.description
         )
     }
 }
 
-impl Display for FileAnalysis {
+impl Display for Interface {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        writeln!(f, "# 📄 File Analysis: `{}`", self.file_path.display())?;
+        f.write_str(&self.render(RenderStyle::Emoji))
+    }
+}
+
+impl FileAnalysis {
+    /// Render as markdown, decorating markers according to `style`
+    pub fn render(&self, style: RenderStyle) -> String {
+        let mut f = String::new();
+        self.write_to(&mut f, style)
+            .expect("writing to a String cannot fail");
+        f
+    }
+
+    /// Text of the page's top-level heading
+    pub fn title(&self, style: RenderStyle) -> String {
+        format!(
+            "{}: `{}`",
+            style.marker("📄", "File Analysis"),
+            self.file_path.display()
+        )
+    }
+
+    fn write_to(&self, f: &mut String, style: RenderStyle) -> Result {
+        writeln!(f, "# {}", self.title(style))?;
         writeln!(f)?;
         writeln!(f, "**Type:** `{}`", self.file_type)?;
         writeln!(f)?;
+        if let Some(line_count) = self.line_count {
+            writeln!(f, "**Lines:** {}", line_count)?;
+            writeln!(f)?;
+        }
+        if let Some(byte_size) = self.byte_size {
+            writeln!(f, "**Size:** {} bytes", byte_size)?;
+            writeln!(f)?;
+        }
         writeln!(f, "## Summary")?;
         writeln!(f, "{}", self.summary)?;
         writeln!(f)?;
 
         if !self.external_dependencies.is_empty() {
-            writeln!(f, "## 📚 External Dependencies")?;
+            writeln!(f, "## {}", style.marker("📚", "External Dependencies"))?;
             for dep in &self.external_dependencies {
                 writeln!(f, "- `{}`", dep)?;
             }
@@ -47,19 +109,52 @@ impl Display for FileAnalysis {
         }
 
         if !self.public_interfaces.is_empty() {
-            writeln!(f, "## 🔌 Public Interfaces")?;
+            writeln!(f, "## {}", style.marker("🔌", "Public Interfaces"))?;
             for interface in &self.public_interfaces {
-                writeln!(f, "{}", interface)?;
+                writeln!(f, "{}", interface.render(style))?;
             }
         }
 
+        if !self.signatures.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "## {}", style.marker("✍️", "Signatures"))?;
+            writeln!(f, "```{}", self.file_type)?;
+            for signature in &self.signatures {
+                writeln!(f, "{}", signature)?;
+            }
+            writeln!(f, "```")?;
+        }
+
         Ok(())
     }
 }
 
-impl Display for DirectoryAnalysis {
+impl Display for FileAnalysis {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        writeln!(f, "# 📁 Directory: `{}`", self.directory_path.display())?;
+        f.write_str(&self.render(RenderStyle::Emoji))
+    }
+}
+
+impl DirectoryAnalysis {
+    /// Render as markdown, decorating markers according to `style`
+    pub fn render(&self, style: RenderStyle) -> String {
+        let mut f = String::new();
+        self.write_to(&mut f, style)
+            .expect("writing to a String cannot fail");
+        f
+    }
+
+    /// Text of the page's top-level heading
+    pub fn title(&self, style: RenderStyle) -> String {
+        format!(
+            "{}: `{}`",
+            style.marker("📁", "Directory"),
+            self.directory_path.display()
+        )
+    }
+
+    fn write_to(&self, f: &mut String, style: RenderStyle) -> Result {
+        writeln!(f, "# {}", self.title(style))?;
         writeln!(f)?;
         writeln!(f, "**Depth Level:** {}", self.depth_level)?;
         writeln!(f)?;
@@ -68,7 +163,7 @@ impl Display for DirectoryAnalysis {
         writeln!(f)?;
 
         if !self.key_components.is_empty() {
-            writeln!(f, "## 🎯 Key Components")?;
+            writeln!(f, "## {}", style.marker("🎯", "Key Components"))?;
             for component in &self.key_components {
                 writeln!(f, "- **{}**", component)?;
             }
@@ -76,7 +171,7 @@ impl Display for DirectoryAnalysis {
         }
 
         if !self.child_summaries.is_empty() {
-            writeln!(f, "## 📋 Child Summaries")?;
+            writeln!(f, "## {}", style.marker("📋", "Child Summaries"))?;
             for (i, summary) in self.child_summaries.iter().enumerate() {
                 writeln!(f, "{}. {}", i + 1, summary)?;
             }
@@ -84,7 +179,7 @@ impl Display for DirectoryAnalysis {
         }
 
         if !self.external_dependencies.is_empty() {
-            writeln!(f, "## 📚 External Dependencies")?;
+            writeln!(f, "## {}", style.marker("📚", "External Dependencies"))?;
             for dep in &self.external_dependencies {
                 writeln!(f, "- `{}`", dep)?;
             }
@@ -92,9 +187,9 @@ impl Display for DirectoryAnalysis {
         }
 
         if !self.public_interfaces.is_empty() {
-            writeln!(f, "## 🔌 Public Interfaces")?;
+            writeln!(f, "## {}", style.marker("🔌", "Public Interfaces"))?;
             for interface in &self.public_interfaces {
-                let interface_string = interface.to_string();
+                let interface_string = interface.render(style);
                 let interface_lines: Vec<&str> = interface_string.lines().collect();
                 for line in interface_lines {
                     writeln!(f, "{}", line)?;
@@ -106,20 +201,38 @@ impl Display for DirectoryAnalysis {
     }
 }
 
-impl Display for ProjectAnalysis {
+impl Display for DirectoryAnalysis {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        writeln!(f, "# 🚀 Project Analysis")?;
+        f.write_str(&self.render(RenderStyle::Emoji))
+    }
+}
+
+impl ProjectAnalysis {
+    /// Render as markdown, decorating markers according to `style`
+    pub fn render(&self, style: RenderStyle) -> String {
+        let mut f = String::new();
+        self.write_to(&mut f, style)
+            .expect("writing to a String cannot fail");
+        f
+    }
+
+    fn write_to(&self, f: &mut String, style: RenderStyle) -> Result {
+        writeln!(f, "# {}", style.marker("🚀", "Project Analysis"))?;
         writeln!(f)?;
         writeln!(f, "## Overview")?;
         writeln!(f, "{}", self.project_overview)?;
         writeln!(f)?;
+        if let Some(license) = &self.license {
+            writeln!(f, "**License:** `{}`", license)?;
+            writeln!(f)?;
+        }
 
-        writeln!(f, "## 🏗️ Architecture")?;
+        writeln!(f, "## {}", style.marker("🏗️", "Architecture"))?;
         writeln!(f, "{}", self.architecture_summary)?;
         writeln!(f)?;
 
         if !self.core_technologies.is_empty() {
-            writeln!(f, "## 🛠️ Core Technologies")?;
+            writeln!(f, "## {}", style.marker("🛠️", "Core Technologies"))?;
             for tech in &self.core_technologies {
                 writeln!(f, "- **{}**", tech)?;
             }
@@ -127,15 +240,15 @@ impl Display for ProjectAnalysis {
         }
 
         if !self.main_interfaces.is_empty() {
-            writeln!(f, "## 🔌 Main Interfaces")?;
+            writeln!(f, "## {}", style.marker("🔌", "Main Interfaces"))?;
             for interface in &self.main_interfaces {
-                writeln!(f, "{}", interface)?;
+                writeln!(f, "{}", interface.render(style))?;
             }
             writeln!(f)?;
         }
 
         if !self.development_considerations.is_empty() {
-            writeln!(f, "## 🔧 Development Considerations")?;
+            writeln!(f, "## {}", style.marker("🔧", "Development Considerations"))?;
             for consideration in &self.development_considerations {
                 writeln!(f, "- {}", consideration)?;
             }
@@ -143,7 +256,7 @@ impl Display for ProjectAnalysis {
         }
 
         if !self.extension_points.is_empty() {
-            writeln!(f, "## 🔗 Extension Points")?;
+            writeln!(f, "## {}", style.marker("🔗", "Extension Points"))?;
             for point in &self.extension_points {
                 writeln!(f, "- {}", point)?;
             }
@@ -151,9 +264,9 @@ impl Display for ProjectAnalysis {
         }
 
         if !self.risk_factors.is_empty() {
-            writeln!(f, "## ⚠️ Risk Factors")?;
+            writeln!(f, "## {}", style.marker("⚠️", "Risk Factors"))?;
             for risk in &self.risk_factors {
-                writeln!(f, "{}", risk)?;
+                writeln!(f, "{}", risk.render(style))?;
             }
         }
 
@@ -161,6 +274,12 @@ impl Display for ProjectAnalysis {
     }
 }
 
+impl Display for ProjectAnalysis {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.write_str(&self.render(RenderStyle::Emoji))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -192,6 +311,9 @@ mod tests {
                     description: "Main configuration struct".to_string(),
                 }
             ],
+            line_count: None,
+            byte_size: None,
+            signatures: vec![],
         };
 
         let output = analysis.to_string();
@@ -199,5 +321,78 @@ mod tests {
         assert!(output.contains("**Type:** `rs`"));
         assert!(output.contains("## 📚 External Dependencies"));
         assert!(output.contains("- `serde`"));
+        assert!(!output.contains("**Lines:**"));
+
+        let output = analysis.clone().with_metrics("a\nb\nc\n").to_string();
+        assert!(output.contains("**Lines:** 3"));
+        assert!(output.contains("**Size:** 6 bytes"));
+        assert!(!output.contains("Signatures"));
+
+        let output = FileAnalysis {
+            signatures: vec!["pub struct Config".to_string()],
+            ..analysis
+        }
+        .to_string();
+        assert!(output.contains("## ✍️ Signatures\n```rs\npub struct Config\n```\n"));
+    }
+
+    #[test]
+    fn test_plain_render_is_ascii() {
+        let interface = |interface_type| Interface {
+            name: "Config".to_string(),
+            interface_type,
+            description: "Main configuration struct".to_string(),
+        };
+        let all_types = vec![
+            interface(InterfaceType::Function),
+            interface(InterfaceType::Struct),
+            interface(InterfaceType::Trait),
+            interface(InterfaceType::Module),
+            interface(InterfaceType::Api),
+            interface(InterfaceType::Configuration),
+            interface(InterfaceType::DataModel),
+        ];
+        let file = FileAnalysis {
+            file_path: PathBuf::from("src/config.rs"),
+            file_type: "rs".to_string(),
+            summary: "Configuration management module.".to_string(),
+            external_dependencies: vec!["serde".to_string()],
+            public_interfaces: all_types.clone(),
+            line_count: Some(3),
+            byte_size: Some(6),
+            signatures: vec![],
+        };
+        let directory = DirectoryAnalysis {
+            directory_path: PathBuf::from("src"),
+            depth_level: 1,
+            summary: "Source code.".to_string(),
+            child_summaries: vec!["Configuration management module.".to_string()],
+            key_components: vec!["Config".to_string()],
+            external_dependencies: vec!["serde".to_string()],
+            public_interfaces: all_types.clone(),
+        };
+        let project = ProjectAnalysis {
+            project_overview: "A tool.".to_string(),
+            architecture_summary: "One crate.".to_string(),
+            core_technologies: vec!["Rust".to_string()],
+            main_interfaces: all_types.clone(),
+            development_considerations: vec!["Keep it small".to_string()],
+            extension_points: vec!["Backends".to_string()],
+            risk_factors: all_types,
+            license: Some("MIT".to_string()),
+        };
+
+        for output in [
+            file.render(RenderStyle::Plain),
+            directory.render(RenderStyle::Plain),
+            project.render(RenderStyle::Plain),
+        ] {
+            assert!(output.is_ascii(), "non-ASCII output:\n{}", output);
+        }
+        assert!(
+            file.render(RenderStyle::Plain)
+                .contains("# File Analysis: `src/config.rs`")
+        );
+        assert_eq!(file.render(RenderStyle::Emoji), file.to_string());
     }
 }
@@ -0,0 +1,109 @@
+use std::fs;
+use std::path::Path;
+
+/// File names checked (in order) for the project's license text
+const LICENSE_FILES: [&str; 5] = [
+    "LICENSE",
+    "LICENSE.md",
+    "LICENSE.txt",
+    "COPYING",
+    "COPYING.md",
+];
+
+/// Only the start of the file is inspected, the identifying header always appears there
+const HEADER_LEN: usize = 2048;
+
+/// Look for a license file in the project root and identify it by SPDX id
+pub fn detect_license(project_root: &Path) -> Option<String> {
+    LICENSE_FILES.iter().find_map(|name| {
+        let content = fs::read_to_string(project_root.join(name)).ok()?;
+        identify_license(&content)
+    })
+}
+
+/// Match the header of a license text against well known licenses
+pub fn identify_license(text: &str) -> Option<String> {
+    let header: String = text.chars().take(HEADER_LEN).collect();
+
+    // An explicit identifier always wins over heuristics
+    if let Some(line) = header
+        .lines()
+        .find(|line| line.contains("SPDX-License-Identifier:"))
+    {
+        let id = line.split("SPDX-License-Identifier:").nth(1)?.trim();
+        if !id.is_empty() {
+            return Some(id.to_string());
+        }
+    }
+
+    let header = header.to_lowercase();
+    let id = if header.contains("gnu affero general public license") {
+        if header.contains("version 3") {
+            "AGPL-3.0"
+        } else {
+            "AGPL"
+        }
+    } else if header.contains("gnu lesser general public license") {
+        if header.contains("version 3") {
+            "LGPL-3.0"
+        } else if header.contains("version 2.1") {
+            "LGPL-2.1"
+        } else {
+            "LGPL"
+        }
+    } else if header.contains("gnu general public license") {
+        if header.contains("version 3") {
+            "GPL-3.0"
+        } else if header.contains("version 2") {
+            "GPL-2.0"
+        } else {
+            "GPL"
+        }
+    } else if header.contains("apache license") && header.contains("version 2.0") {
+        "Apache-2.0"
+    } else if header.contains("mozilla public license") && header.contains("2.0") {
+        "MPL-2.0"
+    } else if header.contains("mit license")
+        || header.contains("permission is hereby granted, free of charge")
+    {
+        "MIT"
+    } else if header.contains("isc license")
+        || header.contains("permission to use, copy, modify, and/or distribute")
+    {
+        "ISC"
+    } else if header.contains("redistribution and use in source and binary forms") {
+        if header.contains("neither the name") {
+            "BSD-3-Clause"
+        } else {
+            "BSD-2-Clause"
+        }
+    } else if header.contains("this is free and unencumbered software") {
+        "Unlicense"
+    } else {
+        return None;
+    };
+
+    Some(id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identify_license() {
+        assert_eq!(
+            identify_license("Apache License\nVersion 2.0, January 2004"),
+            Some("Apache-2.0".to_string())
+        );
+        assert_eq!(
+            identify_license("GNU GENERAL PUBLIC LICENSE\nVersion 3, 29 June 2007"),
+            Some("GPL-3.0".to_string())
+        );
+        assert_eq!(
+            identify_license("// SPDX-License-Identifier: MIT OR Apache-2.0"),
+            Some("MIT OR Apache-2.0".to_string())
+        );
+        assert_eq!(identify_license("All rights reserved."), None);
+    }
+}
@@ -1,3 +1,7 @@
+pub mod detect;
 pub mod display;
+pub mod license;
 pub mod prompt;
+pub mod signatures;
+pub mod staged;
 pub mod summary;
@@ -7,6 +7,10 @@ pub struct PromptTemplates {
     pub file_analysis_template: String,
     pub directory_synthesis_template: String,
     pub project_analysis_template: String,
+    /// House-style instructions placed before every system prompt
+    pub prompt_prefix: Option<String>,
+    /// House-style instructions placed after every system prompt
+    pub prompt_suffix: Option<String>,
 }
 
 impl Default for PromptTemplates {
@@ -16,24 +20,39 @@ impl Default for PromptTemplates {
             directory_synthesis_template: include_str!("templates/directory_analysis.txt")
                 .to_string(),
             project_analysis_template: include_str!("templates/project_analysis.txt").to_string(),
+            prompt_prefix: None,
+            prompt_suffix: None,
         }
     }
 }
 
 impl PromptTemplates {
+    /// Surround a built prompt with the configured prefix and suffix
+    fn with_house_style(&self, prompt: String) -> String {
+        let mut parts: Vec<&str> = Vec::new();
+        parts.extend(self.prompt_prefix.as_deref());
+        parts.push(&prompt);
+        parts.extend(self.prompt_suffix.as_deref());
+        parts.join("\n\n")
+    }
+
     pub fn build_file_analysis_prompt(
         &self,
         file_path: &Path,
         context: &AnalysisContext,
     ) -> String {
-        self.file_analysis_template
+        let prompt = self
+            .file_analysis_template
             .replace("{FILE_PATH}", &file_path.display().to_string())
+            .replace("{LANGUAGE}", language(file_path))
             .replace("{PROJECT_TYPE}", &format!("{:?}", context.project_type))
             .replace("{ANALYSIS_DEPTH}", &format!("{:?}", context.analysis_depth))
+            .replace("{DEPTH_GUIDANCE}", context.analysis_depth.prompt_guidance())
             .replace(
                 "{TARGET_AUDIENCE}",
                 &format!("{:?}", context.target_audience),
-            )
+            );
+        self.with_house_style(prompt)
     }
 
     pub fn build_directory_synthesis_prompt(
@@ -41,9 +60,15 @@ impl PromptTemplates {
         directory_path: &Path,
         context: &AnalysisContext,
     ) -> String {
-        self.directory_synthesis_template
+        let prompt = self
+            .directory_synthesis_template
             .replace("{DIRECTORY_PATH}", &directory_path.display().to_string())
             .replace("{PROJECT_TYPE}", &format!("{:?}", context.project_type))
+            .replace(
+                "{TARGET_AUDIENCE}",
+                &format!("{:?}", context.target_audience),
+            );
+        self.with_house_style(prompt)
     }
 
     pub fn build_project_analysis_prompt(
@@ -51,8 +76,123 @@ impl PromptTemplates {
         project_root: &Path,
         context: &AnalysisContext,
     ) -> String {
-        self.project_analysis_template
+        let prompt = self
+            .project_analysis_template
             .replace("{PROJECT_TYPE}", &format!("{:?}", context.project_type))
             .replace("{PROJECT_ROOT}", &format!("{:?}", project_root))
+            .replace(
+                "{TARGET_AUDIENCE}",
+                &format!("{:?}", context.target_audience),
+            );
+        self.with_house_style(prompt)
+    }
+}
+
+/// Language of a source file, judged by its extension, or "unknown"
+fn language(file_path: &Path) -> &'static str {
+    let extension = file_path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase());
+    match extension.as_deref() {
+        Some("rs") => "Rust",
+        Some("py") => "Python",
+        Some("js" | "mjs" | "cjs" | "jsx") => "JavaScript",
+        Some("ts" | "tsx") => "TypeScript",
+        Some("go") => "Go",
+        Some("java") => "Java",
+        Some("c" | "h") => "C",
+        Some("cpp" | "cc" | "cxx" | "hpp") => "C++",
+        Some("cs") => "C#",
+        Some("php") => "PHP",
+        Some("rb") => "Ruby",
+        Some("swift") => "Swift",
+        Some("kt" | "kts") => "Kotlin",
+        Some("scala") => "Scala",
+        Some("clj") => "Clojure",
+        Some("ex" | "exs") => "Elixir",
+        Some("elm") => "Elm",
+        Some("hs") => "Haskell",
+        Some("ml") => "OCaml",
+        Some("sh" | "bash") => "Shell",
+        Some("toml") => "TOML",
+        Some("yaml" | "yml") => "YAML",
+        Some("json") => "JSON",
+        Some("xml") => "XML",
+        Some("md") => "Markdown",
+        _ => "unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::summary::{AnalysisAudience, AnalysisDepth, ProjectType};
+
+    fn context() -> AnalysisContext {
+        AnalysisContext {
+            project_type: ProjectType::Library,
+            target_audience: AnalysisAudience::LlmConsumption,
+            analysis_depth: AnalysisDepth::Standard,
+        }
+    }
+
+    #[test]
+    fn test_prefix_and_suffix_wrap_every_prompt() {
+        let templates = PromptTemplates {
+            prompt_prefix: Some("Use British spelling.".to_string()),
+            prompt_suffix: Some("Always mention thread-safety.".to_string()),
+            ..Default::default()
+        };
+        let context = context();
+        let path = Path::new("src/lib.rs");
+
+        for prompt in [
+            templates.build_file_analysis_prompt(path, &context),
+            templates.build_directory_synthesis_prompt(path, &context),
+            templates.build_project_analysis_prompt(path, &context),
+        ] {
+            assert!(prompt.starts_with("Use British spelling.\n\n"));
+            assert!(prompt.ends_with("\n\nAlways mention thread-safety."));
+        }
+    }
+
+    #[test]
+    fn test_no_house_style_leaves_template_untouched() {
+        let templates = PromptTemplates {
+            file_analysis_template: "Analyze {FILE_PATH}".to_string(),
+            ..Default::default()
+        };
+        let prompt = templates.build_file_analysis_prompt(Path::new("a.rs"), &context());
+        assert_eq!(prompt, "Analyze a.rs");
+    }
+
+    #[test]
+    fn test_audience_reaches_every_prompt() {
+        let templates = PromptTemplates::default();
+        let context = AnalysisContext {
+            target_audience: AnalysisAudience::HumanDeveloper,
+            ..context()
+        };
+        let path = Path::new("src/lib.rs");
+
+        for prompt in [
+            templates.build_file_analysis_prompt(path, &context),
+            templates.build_directory_synthesis_prompt(path, &context),
+            templates.build_project_analysis_prompt(path, &context),
+        ] {
+            assert!(prompt.contains("- **Target Audience**: HumanDeveloper\n"));
+            assert!(!prompt.contains("{TARGET_AUDIENCE}"));
+        }
+    }
+
+    #[test]
+    fn test_language_from_extension() {
+        let templates = PromptTemplates::default();
+        let prompt = templates.build_file_analysis_prompt(Path::new("app/Main.kt"), &context());
+        assert!(prompt.contains("- **Language**: Kotlin\n"));
+
+        let prompt = templates.build_file_analysis_prompt(Path::new("data.xyz"), &context());
+        assert!(prompt.contains("- **Language**: unknown\n"));
+        assert_eq!(language(Path::new("Makefile")), "unknown");
     }
 }
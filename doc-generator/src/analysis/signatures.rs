@@ -0,0 +1,118 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::path::Path;
+
+// Top-level declarations only: each pattern is anchored at the start of an unindented line
+static RUST_ITEM: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(?m)^pub(?:\([^)]*\))?\s+(?:(?:async|const|unsafe|extern\s+"[^"]*")\s+)*(?:fn|struct|enum|trait|type)\s+[^{;]*"#,
+    )
+    .unwrap()
+});
+
+static PYTHON_ITEM: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?m)^(?:(?:async\s+)?def\s+[A-Za-z]\w*\s*\([^)]*\)(?:\s*->\s*[^:]+)?|class\s+[A-Za-z]\w*(?:\([^)]*\))?)",
+    )
+    .unwrap()
+});
+
+static TYPESCRIPT_ITEM: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?m)^export\s+(?:(?:default|declare|abstract|async)\s+)*(?:function\*?|class|interface|type|enum|const)\s+[^{=;]*",
+    )
+    .unwrap()
+});
+
+/// Top-level public signatures declared in `content`, found locally rather than by the LLM
+///
+/// Rust `pub` items, Python functions and classes without a leading underscore, and
+/// TypeScript exports are recognised; other file types yield nothing. Each signature
+/// is trimmed at its body and has its whitespace collapsed onto one line.
+pub fn extract_signatures(path: &Path, content: &str) -> Vec<String> {
+    let pattern = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("rs") => &RUST_ITEM,
+        Some("py") => &PYTHON_ITEM,
+        Some("ts" | "tsx") => &TYPESCRIPT_ITEM,
+        _ => return vec![],
+    };
+
+    pattern
+        .find_iter(content)
+        .map(|item| {
+            item.as_str()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rust_public_items() {
+        let content = r#"
+use std::fmt;
+
+pub struct Config {
+    pub name: String,
+}
+
+struct Private;
+
+pub(crate) enum Mode { Fast, Slow }
+
+impl Config {
+    pub fn new() -> Self {
+        todo!()
+    }
+}
+
+pub async fn load<P: AsRef<Path>>(
+    path: P,
+) -> Result<Config, Error>
+where
+    P: Send,
+{
+    todo!()
+}
+
+fn helper() {}
+
+pub trait Source: Send;
+"#;
+        assert_eq!(
+            extract_signatures(Path::new("src/config.rs"), content),
+            vec![
+                "pub struct Config",
+                "pub(crate) enum Mode",
+                "pub async fn load<P: AsRef<Path>>( path: P, ) -> Result<Config, Error> where P: Send,",
+                "pub trait Source: Send",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_python_and_typescript_items() {
+        let python = "class Parser(Base):\n    def parse(self):\n        pass\n\ndef load(path: str) -> Parser:\n    pass\n\ndef _private():\n    pass\n";
+        assert_eq!(
+            extract_signatures(Path::new("parser.py"), python),
+            vec!["class Parser(Base)", "def load(path: str) -> Parser"]
+        );
+
+        let typescript = "export interface Options {\n  depth: number;\n}\nexport const DEFAULT_DEPTH = 2;\nfunction internal() {}\nexport async function crawl(root: string): Promise<Node> {\n}\n";
+        assert_eq!(
+            extract_signatures(Path::new("crawl.ts"), typescript),
+            vec![
+                "export interface Options",
+                "export const DEFAULT_DEPTH",
+                "export async function crawl(root: string): Promise<Node>",
+            ]
+        );
+
+        assert!(extract_signatures(Path::new("notes.md"), "pub fn nope()").is_empty());
+    }
+}
@@ -0,0 +1,107 @@
+use std::path::Path;
+
+use async_trait::async_trait;
+
+use super::summary::{
+    AnalysisContext, AnalysisError, ChildAnalysis, DirectoryAnalysis, FileAnalysis, LlmAnalyser,
+    ProjectAnalysis,
+};
+
+/// Routes each analysis stage to its own analyser, falling back to a shared default
+#[derive(Clone)]
+pub struct StagedAnalyser<A: LlmAnalyser> {
+    default: A,
+    file: Option<A>,
+    directory: Option<A>,
+    project: Option<A>,
+}
+
+impl<A: LlmAnalyser> StagedAnalyser<A> {
+    pub fn new(default: A) -> Self {
+        Self {
+            default,
+            file: None,
+            directory: None,
+            project: None,
+        }
+    }
+
+    /// Use a dedicated analyser for per-file analysis
+    pub fn file(mut self, analyser: Option<A>) -> Self {
+        self.file = analyser;
+        self
+    }
+
+    /// Use a dedicated analyser for directory synthesis
+    pub fn directory(mut self, analyser: Option<A>) -> Self {
+        self.directory = analyser;
+        self
+    }
+
+    /// Use a dedicated analyser for project synthesis
+    pub fn project(mut self, analyser: Option<A>) -> Self {
+        self.project = analyser;
+        self
+    }
+}
+
+#[async_trait]
+impl<A: LlmAnalyser> LlmAnalyser for StagedAnalyser<A> {
+    async fn analyze_file(
+        &self,
+        file_path: &Path,
+        file_content: &str,
+        context: &AnalysisContext,
+    ) -> Result<FileAnalysis, AnalysisError> {
+        self.file
+            .as_ref()
+            .unwrap_or(&self.default)
+            .analyze_file(file_path, file_content, context)
+            .await
+    }
+
+    async fn analyze_directory(
+        &self,
+        directory_path: &Path,
+        child_analyses: &[ChildAnalysis],
+        context: &AnalysisContext,
+    ) -> Result<DirectoryAnalysis, AnalysisError> {
+        self.directory
+            .as_ref()
+            .unwrap_or(&self.default)
+            .analyze_directory(directory_path, child_analyses, context)
+            .await
+    }
+
+    async fn analyze_project(
+        &self,
+        project_root: &Path,
+        child_analyses: &[ChildAnalysis],
+        context: &AnalysisContext,
+    ) -> Result<ProjectAnalysis, AnalysisError> {
+        self.project
+            .as_ref()
+            .unwrap_or(&self.default)
+            .analyze_project(project_root, child_analyses, context)
+            .await
+    }
+
+    fn tokens_used(&self) -> u64 {
+        // Stage analysers are built separately from the default, so their usage never overlaps
+        [&self.file, &self.directory, &self.project]
+            .into_iter()
+            .flatten()
+            .map(|analyser| analyser.tokens_used())
+            .sum::<u64>()
+            + self.default.tokens_used()
+    }
+
+    fn retries(&self) -> u64 {
+        [&self.file, &self.directory, &self.project]
+            .into_iter()
+            .flatten()
+            .map(|analyser| analyser.retries())
+            .sum::<u64>()
+            + self.default.retries()
+    }
+}
@@ -5,7 +5,7 @@ use std::fmt::{Display, Formatter};
 use std::path::{Path, PathBuf};
 
 // Core data structures
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct FileAnalysis {
     #[schemars(description = "Path to the file being analyzed")]
     pub file_path: PathBuf,
@@ -23,9 +23,119 @@ pub struct FileAnalysis {
         description = "Public functions, structs, traits, or modules that other components can use"
     )]
     pub public_interfaces: Vec<Interface>,
+    /// Number of lines in the file, measured locally rather than by the LLM
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(skip)]
+    pub line_count: Option<usize>,
+    /// Size of the file in bytes, measured locally rather than by the LLM
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(skip)]
+    pub byte_size: Option<u64>,
+    /// Top-level signatures extracted from the source, when enabled
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(skip)]
+    pub signatures: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+impl FileAnalysis {
+    /// Record line count and size measured from the analyzed content
+    pub fn with_metrics(mut self, content: &str) -> Self {
+        self.line_count = Some(content.lines().count());
+        self.byte_size = Some(content.len() as u64);
+        self
+    }
+}
+
+/// Drop case-insensitive duplicates, keeping the first-seen spelling of each entry
+pub fn dedup_preserve_order(items: Vec<String>) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    items
+        .into_iter()
+        .filter(|item| seen.insert(item.trim().to_lowercase()))
+        .collect()
+}
+
+impl DirectoryAnalysis {
+    /// Dedup the lists the model tends to repeat once per child file
+    pub fn normalized(mut self) -> Self {
+        self.external_dependencies = dedup_preserve_order(self.external_dependencies);
+        self.key_components = dedup_preserve_order(self.key_components);
+        self
+    }
+}
+
+impl ProjectAnalysis {
+    /// Dedup the lists the model tends to repeat once per child
+    pub fn normalized(mut self) -> Self {
+        self.core_technologies = dedup_preserve_order(self.core_technologies);
+        self
+    }
+
+    /// A minimal overview assembled locally from `children`, for when project synthesis fails.
+    /// Summaries are concatenated and dependencies unioned into `core_technologies`.
+    pub fn from_children(children: &[ChildAnalysis]) -> Self {
+        let (summaries, dependencies): (Vec<String>, Vec<Vec<String>>) = children
+            .iter()
+            .map(|child| match child {
+                ChildAnalysis::File(file) => (
+                    format!("**{}**: {}", file.file_path.display(), file.summary),
+                    file.external_dependencies.clone(),
+                ),
+                ChildAnalysis::Directory(dir) => (
+                    format!("**{}**: {}", dir.directory_path.display(), dir.summary),
+                    dir.external_dependencies.clone(),
+                ),
+            })
+            .unzip();
+
+        ProjectAnalysis {
+            project_overview: format!(
+                "_Auto-generated from the directory and file analyses because project synthesis failed._\n\n{}",
+                summaries.join("\n\n")
+            ),
+            architecture_summary: String::new(),
+            core_technologies: dependencies.into_iter().flatten().collect(),
+            main_interfaces: vec![],
+            development_considerations: vec![],
+            extension_points: vec![],
+            risk_factors: vec![],
+            license: None,
+        }
+        .normalized()
+    }
+}
+
+/// Combine the analyses of a file's chunks into one: summaries are concatenated in order,
+/// dependencies and interfaces (by name) are unioned. Path and type come from the first part.
+///
+/// # Panics
+/// If `parts` is empty
+pub fn merge_chunk_analyses(parts: Vec<FileAnalysis>) -> FileAnalysis {
+    let mut parts = parts.into_iter();
+    let mut merged = parts.next().expect("at least one file analysis to merge");
+
+    for part in parts {
+        merged.summary = format!("{}\n\n{}", merged.summary, part.summary);
+        for dependency in part.external_dependencies {
+            if !merged.external_dependencies.contains(&dependency) {
+                merged.external_dependencies.push(dependency);
+            }
+        }
+        for interface in part.public_interfaces {
+            if !merged
+                .public_interfaces
+                .iter()
+                .any(|existing| existing.name == interface.name)
+            {
+                merged.public_interfaces.push(interface);
+            }
+        }
+    }
+
+    merged
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct DirectoryAnalysis {
     #[schemars(description = "Path to the directory being analyzed")]
     pub directory_path: PathBuf,
@@ -67,9 +177,13 @@ pub struct ProjectAnalysis {
     pub extension_points: Vec<String>,
     #[schemars(description = "Potential technical risks or dependencies that could cause issues")]
     pub risk_factors: Vec<Interface>,
+    /// SPDX identifier of the project license, detected locally rather than by the LLM
+    #[serde(default)]
+    #[schemars(skip)]
+    pub license: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct Interface {
     #[schemars(
         description = "Name of the interface (function name, struct name, API endpoint, etc.)"
@@ -81,7 +195,7 @@ pub struct Interface {
     pub description: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum InterfaceType {
     #[schemars(description = "A callable function or method")]
     Function,
@@ -134,6 +248,16 @@ pub trait LlmAnalyser: Send + Sync + Clone {
         child_analyses: &[ChildAnalysis],
         context: &AnalysisContext,
     ) -> Result<ProjectAnalysis, AnalysisError>;
+
+    /// Estimated tokens consumed so far, used to enforce token budgets
+    fn tokens_used(&self) -> u64 {
+        0
+    }
+
+    /// Requests retried so far after a retryable error
+    fn retries(&self) -> u64 {
+        0
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -143,7 +267,7 @@ pub struct AnalysisContext {
     pub analysis_depth: AnalysisDepth,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[allow(dead_code)]
 pub enum ProjectType {
     WebApplication,
@@ -154,15 +278,15 @@ pub enum ProjectType {
     Unknown,
 }
 
-#[derive(Debug, Clone)]
-#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum AnalysisAudience {
     LlmConsumption,
     HumanDeveloper,
     TechnicalDocumentation,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[allow(dead_code)]
 pub enum AnalysisDepth {
     Surface,  // Just interfaces and dependencies
@@ -170,7 +294,24 @@ pub enum AnalysisDepth {
     Deep,     // Include implementation details
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+impl AnalysisDepth {
+    /// Extra file prompt instructions for this depth
+    pub fn prompt_guidance(&self) -> &'static str {
+        match self {
+            AnalysisDepth::Surface => {
+                "Only document public interfaces and external dependencies. Keep the summary to one or two sentences and omit implementation details."
+            }
+            AnalysisDepth::Standard => {
+                "Document the file's purpose, public interfaces and external dependencies."
+            }
+            AnalysisDepth::Deep => {
+                "Document the file's purpose, public interfaces and external dependencies, and describe notable implementation details such as algorithms, error handling and invariants."
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[schemars(tag = "type")]
 pub enum ChildAnalysis {
     File(FileAnalysis),
@@ -185,3 +326,19 @@ impl Display for ChildAnalysis {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dedup_preserve_order() {
+        let items = ["serde", "Tokio", "SERDE", "tokio", "anyhow", " serde "]
+            .map(String::from)
+            .to_vec();
+        assert_eq!(
+            dedup_preserve_order(items),
+            vec!["serde", "Tokio", "anyhow"]
+        );
+    }
+}
@@ -1,19 +1,37 @@
-use crate::crawler::file::{CrawlOptions, crawl_directory};
-use crate::generate::{AnalysisCrawlOptions, AnalysisCrawler};
+use crate::analysis::detect::detect_project_type;
+use crate::analysis::staged::StagedAnalyser;
+use crate::analysis::summary::{AnalysisAudience, AnalysisDepth};
+use crate::crawler::file::{CrawlOptions, FileNode, PathFilter, crawl_directory};
+use crate::crawler::git::changed_files;
+use crate::generate::{
+    AnalysisCrawlError, AnalysisCrawlOptions, AnalysisCrawler, AnalysisEvent, RESUME_STATE_FILE,
+    load_prior_analysis,
+};
 use crate::llm_interface::client::LlmClient;
-use crate::llm_interface::models::ModelId;
-use crate::llm_interface::pool::{LlmPool, PoolMember};
-use crate::output::file_system::{MarkdownConfig, MarkdownGenerator};
-use crate::settings::{FileType, Settings};
+use crate::llm_interface::models::{ModelId, Provider};
+use crate::llm_interface::pool::builder::LlmPoolBuilderError;
+use crate::llm_interface::pool::{Behaviour, LlmPool};
+use crate::output::DocBackend;
+use crate::output::diff::{AnalysisDiff, load_analyses, merge_file_analyses};
+use crate::output::file_system::{MarkdownConfig, MarkdownGenerator, output_collisions};
+use crate::output::ndjson::NdjsonGenerator;
+use crate::output::sink::StdoutSink;
+use crate::settings::{FileType, LlmModel, LlmSettings, MarkdownSettings, Settings};
 use clap::CommandFactory;
 use clap::{Command, Parser, Subcommand, ValueEnum};
 use clap_complete::{Generator, Shell, generate};
+use config::ConfigError;
 use dotenv::dotenv;
 use indicatif::{ProgressBar, ProgressStyle};
-use std::io;
-use std::path::PathBuf;
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
 use strum::IntoEnumIterator;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
 use tracing::{Level, error};
 use tracing_subscriber::{EnvFilter, FmtSubscriber};
 
@@ -29,8 +47,12 @@ struct Cli {
     /// Enable JSON logging format
     #[arg(long, global = true, default_value_t = false)]
     json_logs: bool,
+    /// Config file to load; repeat to layer files, later ones taking precedence
     #[arg(short, long)]
-    config: Option<PathBuf>,
+    config: Vec<PathBuf>,
+    /// Only print errors and the output that was asked for, not progress or summaries
+    #[arg(short, long, global = true, default_value_t = false)]
+    quiet: bool,
     #[arg(long = "completion", value_enum)]
     completions: Option<Shell>,
 }
@@ -88,17 +110,138 @@ fn init_tracing(log_level: LogLevel, json_format: bool) -> Result<(), Box<dyn st
     Ok(())
 }
 
+#[derive(Debug, Clone, ValueEnum)]
+enum OutputFormat {
+    /// A markdown file per analyzed file and directory
+    Markdown,
+    /// One JSON analysis per line
+    Ndjson,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum DepthArg {
+    /// Just interfaces and dependencies
+    Surface,
+    /// Full analysis
+    Standard,
+    /// Include implementation details
+    Deep,
+}
+
+impl From<DepthArg> for AnalysisDepth {
+    fn from(depth: DepthArg) -> Self {
+        match depth {
+            DepthArg::Surface => AnalysisDepth::Surface,
+            DepthArg::Standard => AnalysisDepth::Standard,
+            DepthArg::Deep => AnalysisDepth::Deep,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum AudienceArg {
+    /// Docs other LLMs will read to understand the codebase
+    Llm,
+    /// Docs for developers working on the code
+    Developer,
+    /// Reference documentation
+    Technical,
+}
+
+impl From<AudienceArg> for AnalysisAudience {
+    fn from(audience: AudienceArg) -> Self {
+        match audience {
+            AudienceArg::Llm => AnalysisAudience::LlmConsumption,
+            AudienceArg::Developer => AnalysisAudience::HumanDeveloper,
+            AudienceArg::Technical => AnalysisAudience::TechnicalDocumentation,
+        }
+    }
+}
+
 #[derive(Subcommand, Debug)]
 enum Commands {
     /// Uses the files options in the config to show what files are targeted (useful for testing globs/excludes)
-    Crawl,
+    Crawl {
+        #[arg(default_value = ".")]
+        dir: PathBuf,
+        /// Print the crawled tree as JSON instead of a tree
+        #[arg(long, default_value_t = false, conflicts_with = "stats")]
+        json: bool,
+        /// Print file and directory counts and a per-extension histogram instead of the tree
+        #[arg(long, default_value_t = false)]
+        stats: bool,
+    },
     /// Generates the docs
     Generate {
         #[arg(short, long, default_value_t = false)]
         preview: bool,
+        /// Project directory to document, or a single file to document on its own
         dir: PathBuf,
         #[arg(short, long)]
         directory_output: Option<PathBuf>,
+        /// Output format for the generated docs
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Markdown)]
+        format: OutputFormat,
+        /// File to write NDJSON output to (defaults to stdout)
+        #[arg(long)]
+        output_file: Option<PathBuf>,
+        /// Stop analysis once this many estimated tokens have been used
+        #[arg(long)]
+        max_tokens: Option<u64>,
+        /// Only analyze files modified within this window (e.g. 7d, 12h, 30m)
+        #[arg(long, value_parser = parse_age)]
+        max_age: Option<Duration>,
+        /// Only analyze files last modified at least this long ago (e.g. 1d)
+        #[arg(long, value_parser = parse_age)]
+        min_age: Option<Duration>,
+        /// How much detail to ask the LLM for in each file analysis
+        #[arg(long, value_enum, default_value_t = DepthArg::Standard)]
+        depth: DepthArg,
+        /// Reuse analyses saved by an interrupted run in the output directory for files
+        /// that haven't changed since (markdown written to a directory only)
+        #[arg(long, default_value_t = false)]
+        resume: bool,
+        /// Crawl build, dependency and VCS directories (target/, node_modules/, .git/, ...)
+        /// that are skipped by default
+        #[arg(long, default_value_t = false)]
+        no_default_excludes: bool,
+        /// Print the markdown pages to stdout, separated by their paths, instead of writing them
+        #[arg(long, default_value_t = false)]
+        stdout: bool,
+        /// Who the docs are written for, overriding files.audience in the config
+        #[arg(long, value_enum)]
+        audience: Option<AudienceArg>,
+        /// Use only this model (e.g. gpt-4o-mini), taking precedence over every model in the config
+        #[arg(long, value_parser = parse_model)]
+        model: Option<ModelId>,
+        /// Only document files matching this pattern, on top of the configured includes;
+        /// repeat to target several (e.g. --only 'src/crawler/**'). Directories and the
+        /// project with files outside the patterns keep their existing pages.
+        #[arg(long)]
+        only: Vec<String>,
+        /// Exit with an error after writing the docs if any file or directory failed to
+        /// analyze, e.g. to fail a CI job
+        #[arg(long, default_value_t = false)]
+        fail_on_error: bool,
+    },
+    /// Re-analyze only the files changed since a git base ref, merging them into a saved analysis
+    GenerateDiff {
+        /// Git ref the current branch is compared against (e.g. origin/main)
+        base: String,
+        dir: PathBuf,
+        /// Saved analysis (NDJSON or JSON) to merge the changed files into
+        #[arg(long)]
+        existing: Option<PathBuf>,
+        /// File to write the merged NDJSON to (defaults to --existing, then stdout)
+        #[arg(short, long)]
+        output_file: Option<PathBuf>,
+        /// How much detail to ask the LLM for in each file analysis
+        #[arg(long, value_enum, default_value_t = DepthArg::Standard)]
+        depth: DepthArg,
+        /// Exit with an error after writing the merged analysis if any changed file failed
+        /// to analyze, e.g. to fail a CI job
+        #[arg(long, default_value_t = false)]
+        fail_on_error: bool,
     },
     /// Generate an example config
     Config {
@@ -108,7 +251,188 @@ enum Commands {
         format: FileType,
     },
     /// Print supported models to std out
-    Models,
+    Models {
+        /// Only list models from this provider
+        #[arg(long, value_enum)]
+        provider: Option<Provider>,
+        /// Only list models that support structured output
+        #[arg(long)]
+        structured_only: bool,
+        /// Only list reasoning models
+        #[arg(long)]
+        reasoning_only: bool,
+        /// Only list multimodal models
+        #[arg(long)]
+        multimodal_only: bool,
+    },
+    /// Generate the docs, then watch the directory and regenerate them as files change
+    Watch {
+        dir: PathBuf,
+        #[arg(short, long)]
+        directory_output: Option<PathBuf>,
+    },
+    /// Check the config, API keys and (optionally) provider connectivity, printing a checklist
+    Doctor {
+        /// Send each configured model a tiny request to confirm it responds
+        #[arg(long, default_value_t = false)]
+        ping: bool,
+    },
+    /// Compare two saved analysis runs (NDJSON or JSON) and report what changed
+    Diff {
+        old: PathBuf,
+        new: PathBuf,
+        /// File to write the markdown report to (defaults to stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+/// Parse an age such as "90s", "30m", "12h" or "7d"
+fn parse_age(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("Invalid age {:?}: expected e.g. 30m, 12h or 7d", value))?;
+
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" | "" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => {
+            return Err(format!(
+                "Invalid age unit {:?}: expected s, m, h, d or w",
+                unit
+            ));
+        }
+    };
+
+    number
+        .checked_mul(seconds)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("Invalid age {:?}: too large", value))
+}
+
+/// Parse a model by the name used in config files, e.g. "claude-3-5-haiku-latest"
+fn parse_model(value: &str) -> Result<ModelId, String> {
+    value.parse().map_err(|_| {
+        format!(
+            "Unknown model {:?}: run `auto-doc models` to list them",
+            value
+        )
+    })
+}
+
+/// Load settings from the given config files, or from defaults and the environment if there are none
+fn load_settings(config: &[PathBuf]) -> Result<Settings, ConfigError> {
+    if config.is_empty() {
+        return Settings::from_env();
+    }
+    let config_paths: Vec<String> = config
+        .iter()
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect();
+    let config_paths: Vec<&str> = config_paths.iter().map(String::as_str).collect();
+    Settings::from_files(&config_paths)
+}
+
+const CHECK_PASSED: &str = "\x1b[32m✓\x1b[0m";
+const CHECK_FAILED: &str = "\x1b[31m✗\x1b[0m";
+
+/// Write a checklist of everything a run needs to `out`: the settings load and validate,
+/// every configured model has a key, and with `ping` each model answers a tiny request.
+/// Returns whether every check passed.
+async fn doctor(
+    settings: Result<Settings, ConfigError>,
+    ping: bool,
+    out: &mut impl Write,
+) -> io::Result<bool> {
+    let settings = match settings {
+        Ok(settings) => {
+            writeln!(out, "{} Settings loaded", CHECK_PASSED)?;
+            settings
+        }
+        Err(e) => {
+            writeln!(out, "{} Settings failed to load: {}", CHECK_FAILED, e)?;
+            return Ok(false);
+        }
+    };
+
+    let mut passed = true;
+    match settings.validate() {
+        Ok(()) => writeln!(out, "{} Settings are valid", CHECK_PASSED)?,
+        Err(problems) => {
+            passed = false;
+            for problem in problems {
+                writeln!(out, "{} {}", CHECK_FAILED, problem)?;
+            }
+        }
+    }
+
+    let llm = &settings.llm_settings;
+    let stage_models = [&llm.file_model, &llm.directory_model, &llm.project_model];
+    let models = llm.models.iter().chain(stage_models.into_iter().flatten());
+    for model in models {
+        let client = match model
+            .resolved_api_key()
+            .map_err(|e| e.to_string())
+            .and_then(|api_key| {
+                LlmClient::try_new(
+                    model.model.clone(),
+                    api_key,
+                    model.max_tokens,
+                    model.temperature,
+                )
+                .map_err(|e| e.to_string())
+            }) {
+            Ok(client) => {
+                writeln!(out, "{} {}: API key found", CHECK_PASSED, model.model)?;
+                client.with_base_url(model.base_url.clone())
+            }
+            Err(e) => {
+                passed = false;
+                writeln!(out, "{} {}: {}", CHECK_FAILED, model.model, e)?;
+                continue;
+            }
+        };
+        if !ping {
+            continue;
+        }
+
+        let response = tokio::time::timeout(
+            DOCTOR_PING_TIMEOUT,
+            client.get_simple_response("Reply with the single word OK.", "ping"),
+        )
+        .await;
+        match response {
+            Ok(Ok(_)) => writeln!(out, "{} {}: responded", CHECK_PASSED, model.model)?,
+            Ok(Err(e)) => {
+                passed = false;
+                writeln!(
+                    out,
+                    "{} {}: request failed: {}",
+                    CHECK_FAILED, model.model, e
+                )?;
+            }
+            Err(_) => {
+                passed = false;
+                writeln!(
+                    out,
+                    "{} {}: no response within {}s",
+                    CHECK_FAILED,
+                    model.model,
+                    DOCTOR_PING_TIMEOUT.as_secs()
+                )?;
+            }
+        }
+    }
+
+    Ok(passed)
 }
 
 fn print_completions<G: Generator>(generator: G, cmd: &mut Command) {
@@ -120,36 +444,463 @@ fn print_completions<G: Generator>(generator: G, cmd: &mut Command) {
     );
 }
 
-fn crawl() -> Result<(), Box<dyn std::error::Error>> {
-    let options: CrawlOptions = CrawlOptions {
-        max_depth: Some(3),
-        include_hidden: false,
-        glob_patterns: vec!["*.rs".to_string(), "*.toml".to_string()], // Only include these
-        exclude_patterns: vec![
-            "target/**".to_string(),
-            "node_modules/**".to_string(),
-            ".git/**".to_string(),
-            "*.tmp".to_string(),
-        ],
+/// Crawl `dir` with the configured file options and write its tree, JSON or stats to `out`
+fn crawl(
+    settings: &Settings,
+    dir: &Path,
+    json: bool,
+    stats: bool,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let options = build_analysis_options(settings, DepthArg::Standard, None, None, None)?;
+    let tree = crawl_directory(dir, options.crawl_options)?;
+
+    if json {
+        serde_json::to_writer_pretty(&mut *out, &tree)?;
+        writeln!(out)?;
+    } else if stats {
+        write_crawl_stats(&tree, out)?;
+    } else {
+        writeln!(out, "File tree:")?;
+        write!(out, "{}", tree.tree_string())?;
+        writeln!(out, "\nTotal files: {}", tree.total_files())?;
+        let rust_files = tree.find_files_by_extension("rs");
+        writeln!(out, "Found {} Rust files", rust_files.len())?;
+    }
+    Ok(())
+}
+
+/// Aggregate counts for a crawled tree, without any LLM involvement
+fn write_crawl_stats(tree: &FileNode, out: &mut impl Write) -> io::Result<()> {
+    let mut file_types: BTreeMap<&str, usize> = BTreeMap::new();
+    for file in tree.collect_files() {
+        if let FileNode::File { extension, .. } = file {
+            *file_types
+                .entry(extension.as_deref().unwrap_or("(none)"))
+                .or_insert(0) += 1;
+        }
+    }
+
+    writeln!(out, "Crawl Stats:")?;
+    writeln!(out, "  Total files: {}", tree.total_files())?;
+    writeln!(
+        out,
+        "  Total directories: {}",
+        tree.collect_directories().len()
+    )?;
+    writeln!(out, "  Total size: {} bytes", tree.size())?;
+    if !file_types.is_empty() {
+        writeln!(out, "  File types:")?;
+        let mut types: Vec<_> = file_types.into_iter().collect();
+        types.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        for (ext, count) in types {
+            writeln!(out, "    .{}: {} files", ext, count)?;
+        }
+    }
+    Ok(())
+}
+
+fn build_pool(
+    models: &[LlmModel],
+    behaviour: Behaviour,
+    llm_settings: &LlmSettings,
+) -> Result<LlmPool, LlmPoolBuilderError> {
+    let retry_config = llm_settings.retry_config();
+    models
+        .iter()
+        .try_fold(
+            LlmPool::builder()
+                .behaviour(behaviour)
+                .error_cooldown(Duration::from_secs(llm_settings.error_cooldown_s.into())),
+            |builder, m| {
+                let api_key = m
+                    .resolved_api_key()
+                    .map_err(|e| LlmPoolBuilderError::Client(e.to_string()))?;
+                let client =
+                    LlmClient::try_new(m.model.clone(), api_key, m.max_tokens, m.temperature)
+                        .map_err(|e| LlmPoolBuilderError::Client(e.to_string()))?;
+                Ok(builder.add_client_with_priority(
+                    client
+                        .with_base_url(m.base_url.clone())
+                        .with_requests_per_minute(m.requests_per_minute)
+                        .with_max_concurrent_requests(llm_settings.max_concurrent_per_client)
+                        .with_retry_config(retry_config.clone())
+                        .with_prompt_templates(llm_settings.prompt_templates(m)),
+                    m.priority,
+                ))
+            },
+        )?
+        .build()
+}
+
+/// A single model pool for a stage specific model override
+fn build_stage_pool(
+    model: &LlmModel,
+    llm_settings: &LlmSettings,
+) -> Result<LlmPool, LlmPoolBuilderError> {
+    build_pool(
+        std::slice::from_ref(model),
+        Behaviour::Failover,
+        llm_settings,
+    )
+}
+
+/// The staged analyser described by the llm settings
+/// A crawler over the configured analyser, bounded by `max_concurrent_requests` when set
+fn build_crawler(
+    settings: &Settings,
+) -> Result<AnalysisCrawler<StagedAnalyser<LlmPool>>, LlmPoolBuilderError> {
+    let crawler = AnalysisCrawler::new(build_analyser(settings)?);
+    Ok(match settings.llm_settings.max_concurrent_requests {
+        Some(max_concurrency) => crawler.with_max_concurrency(max_concurrency),
+        None => crawler,
+    })
+}
+
+fn build_analyser(settings: &Settings) -> Result<StagedAnalyser<LlmPool>, LlmPoolBuilderError> {
+    let llm_settings = &settings.llm_settings;
+    let stage_pool = |model: &Option<LlmModel>| {
+        model
+            .as_ref()
+            .map(|model| build_stage_pool(model, llm_settings))
+            .transpose()
+    };
+    Ok(StagedAnalyser::new(build_pool(
+        &llm_settings.models,
+        llm_settings.behaviour.clone(),
+        llm_settings,
+    )?)
+    .file(stage_pool(&llm_settings.file_model)?)
+    .directory(stage_pool(&llm_settings.directory_model)?)
+    .project(stage_pool(&llm_settings.project_model)?))
+}
+
+/// Analysis options from the files settings and generate flags
+fn build_analysis_options(
+    settings: &Settings,
+    depth: DepthArg,
+    max_tokens: Option<u64>,
+    max_age: Option<Duration>,
+    min_age: Option<Duration>,
+) -> Result<AnalysisCrawlOptions, Box<dyn std::error::Error>> {
+    let files = &settings.files;
+    let mut options = AnalysisCrawlOptions {
+        crawl_options: CrawlOptions {
+            exclude_patterns: files.exclude_patterns.clone(),
+            glob_patterns: files.include_patterns.clone(),
+            include_hidden: files.include_hidden,
+            max_depth: files.max_depth,
+            ..Default::default()
+        },
+        include_license: files.include_license,
+        lossy_decode: files.lossy_decode,
+        chunk_large_files: files.chunk_large_files,
+        context_window: settings
+            .llm_settings
+            .file_model
+            .as_ref()
+            .or(settings.llm_settings.models.first())
+            .and_then(|model| model.model.context_window()),
+        include_signatures: files.include_signatures,
+        min_file_size: files.min_file_size.unwrap_or_default(),
+        max_files: files.max_files,
+        max_total_tokens: max_tokens,
+        max_age,
+        min_age,
         ..Default::default()
     };
+    options.analysis_context.analysis_depth = depth.into();
+    if let Some(audience) = &files.audience {
+        options.analysis_context.target_audience = audience.clone();
+    }
+    if let Some(max_file_size) = files.max_file_size_bytes()? {
+        options.max_file_size = max_file_size;
+    }
+    if let Some(chunk_overlap) = files.chunk_overlap {
+        options.chunk_overlap = chunk_overlap;
+    }
+    if let Some(max_chunks) = files.max_chunks {
+        options.max_chunks = max_chunks;
+    }
+    if let Some(extensions) = &files.analyzable_extensions {
+        let extensions = extensions
+            .iter()
+            .map(|extension| extension.trim_start_matches('.').to_string());
+        if files.replace_default_extensions {
+            options.analyzable_extensions = extensions.collect();
+        } else {
+            for extension in extensions {
+                if !options.analyzable_extensions.contains(&extension) {
+                    options.analyzable_extensions.push(extension);
+                }
+            }
+        }
+    }
+    Ok(options)
+}
 
-    match crawl_directory("./src", options) {
-        Ok(tree) => {
-            println!("File tree:");
-            tree.print_tree();
+fn markdown_generator(
+    markdown: &MarkdownSettings,
+    dir: &Path,
+    directory_output: Option<PathBuf>,
+    quiet: bool,
+) -> MarkdownGenerator {
+    // A single file's page goes at the top of the output rather than under its own name
+    let root = match dir.is_file() {
+        true => dir.parent().unwrap_or(dir),
+        false => dir,
+    };
+    let mut config_builder = MarkdownConfig::builder()
+        .project_root(root)
+        .front_matter(markdown.front_matter)
+        .render_style(markdown.render_style)
+        .collision_strategy(markdown.collision_strategy)
+        .merge_existing(markdown.merge_existing)
+        .quiet(quiet);
+
+    if let Some(output_dir) = directory_output {
+        config_builder = config_builder.output_dir(output_dir);
+    }
+
+    MarkdownGenerator::new(config_builder.build())
+}
+
+/// Print the pages a markdown run would write, flagging any that more than one page maps to
+fn print_output_plan(planned: &[PathBuf]) {
+    let mut sorted: Vec<&PathBuf> = planned.iter().collect();
+    sorted.sort();
+    println!("  Planned output ({} pages):", sorted.len());
+    for path in sorted {
+        println!("    {}", path.display());
+    }
+
+    let collisions = output_collisions(planned);
+    if !collisions.is_empty() {
+        println!("  Output collisions ({}):", collisions.len());
+        for path in collisions {
+            println!("    {}", path.display());
+        }
+    }
+}
+
+/// Exit status of a generate run stopped by Ctrl-C, as shells report for SIGINT
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// How long `doctor --ping` waits for each model to answer
+const DOCTOR_PING_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long the tree must be quiet before a rebuild starts
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Generate docs for `dir`, then regenerate them whenever a crawled file changes.
+/// Unchanged files are served from the crawler's content-hash cache.
+async fn watch(
+    settings: &Settings,
+    dir: PathBuf,
+    directory_output: Option<PathBuf>,
+    quiet: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let crawler = build_crawler(settings)?.with_cache();
+    let options = build_analysis_options(settings, DepthArg::Standard, None, None, None)?;
+    let output_dir = directory_output
+        .clone()
+        .unwrap_or_else(|| MarkdownConfig::default().output_dir);
+    let generator = markdown_generator(&settings.markdown, &dir, directory_output, quiet);
 
-            println!("\nTotal files: {}", tree.total_files());
+    regenerate(&crawler, &dir, &options, &generator, quiet).await;
 
-            let rust_files = tree.find_files_by_extension("rs");
-            println!("Found {} Rust files", rust_files.len());
+    // Compare against canonical paths, as reported by the watcher
+    let root = dir.canonicalize()?;
+    std::fs::create_dir_all(&output_dir)?;
+    let output_dir = output_dir.canonicalize()?;
+    let filter = PathFilter::new(&root, &options.crawl_options)?;
+    let is_relevant = |path: &PathBuf| !path.starts_with(&output_dir) && filter.allows_file(path);
+
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if let Ok(event) = event {
+            let _ = sender.send(event);
+        }
+    })?;
+    watcher.watch(&root, RecursiveMode::Recursive)?;
+    if !quiet {
+        println!("👀 Watching {} for changes...", dir.display());
+    }
+
+    while let Some(event) = receiver.recv().await {
+        let mut changed = BTreeSet::new();
+        let mut pending = Some(event);
+        while let Some(event) = pending {
+            if !matches!(event.kind, EventKind::Access(_)) {
+                changed.extend(event.paths.into_iter().filter(&is_relevant));
+            }
+            pending = tokio::time::timeout(WATCH_DEBOUNCE, receiver.recv())
+                .await
+                .ok()
+                .flatten();
+        }
+
+        if !changed.is_empty() {
+            regenerate(&crawler, &dir, &options, &generator, quiet).await;
         }
-        Err(e) => eprintln!("Error crawling directory: {}", e),
     }
 
     Ok(())
 }
 
+/// One watch rebuild, reporting which files the LLM re-analyzed
+async fn regenerate(
+    crawler: &AnalysisCrawler<StagedAnalyser<LlmPool>>,
+    dir: &Path,
+    options: &AnalysisCrawlOptions,
+    generator: &MarkdownGenerator,
+    quiet: bool,
+) {
+    let run = match crawler.analyze_project(dir, options.clone(), None).await {
+        Ok(run) => run,
+        Err(e) => {
+            error!("Analysis failed: {}", e);
+            return;
+        }
+    };
+
+    let refreshed = crawler.take_refreshed();
+    if !quiet {
+        if refreshed.is_empty() {
+            println!("No content changes, docs are up to date");
+        } else {
+            println!("🔄 Re-analyzed {} file(s):", refreshed.len());
+            for path in refreshed {
+                println!("  {}", path.display());
+            }
+        }
+    }
+
+    if !quiet && !run.failures.is_empty() {
+        print!("{}", failures_summary(&run.failures));
+    }
+
+    if let Err(e) = generator
+        .generate_documentation(&run.project, &run.children)
+        .await
+    {
+        error!("Failed to write documentation: {}", e);
+    }
+}
+
+/// The files and directories that failed to analyze, one per line with the error
+fn failures_summary(failures: &[(PathBuf, AnalysisCrawlError)]) -> String {
+    let mut summary = format!("Failed files ({}):\n", failures.len());
+    for (path, error) in failures {
+        summary.push_str(&format!("  ❌ {}: {}\n", path.display(), error));
+    }
+    summary
+}
+
+/// Supported models matching the `models` subcommand filters
+fn filter_models(
+    provider: Option<&Provider>,
+    structured_only: bool,
+    reasoning_only: bool,
+    multimodal_only: bool,
+) -> Vec<ModelId> {
+    ModelId::iter()
+        .filter(|model| provider.is_none_or(|provider| model.provider() == provider.backend()))
+        .filter(|model| !structured_only || model.supports_structured_output())
+        .filter(|model| !reasoning_only || model.has_reasoning())
+        .filter(|model| !multimodal_only || model.is_multimodal())
+        .collect()
+}
+
+/// Render models as a table with aligned columns
+fn models_table(models: &[ModelId]) -> String {
+    let yes_no = |flag: bool| if flag { "yes" } else { "no" };
+    let rows: Vec<[String; 5]> = models
+        .iter()
+        .map(|model| {
+            [
+                model.to_string(),
+                model.provider_enum().to_string(),
+                yes_no(model.supports_structured_output()).to_string(),
+                yes_no(model.has_reasoning()).to_string(),
+                yes_no(model.is_multimodal()).to_string(),
+            ]
+        })
+        .collect();
+    let header = ["MODEL", "PROVIDER", "STRUCTURED", "REASONING", "MULTIMODAL"].map(String::from);
+
+    let mut widths = header.clone().map(|cell| cell.len());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let mut table = String::new();
+    for row in std::iter::once(&header).chain(&rows) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell))
+            .collect();
+        table.push_str(cells.join("  ").trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+/// Analyze the files changed since `base` and merge them into the saved analysis, if any.
+/// The report goes to stderr so NDJSON written to stdout stays clean. Files that failed to
+/// analyze keep their saved analysis and are returned.
+async fn generate_diff(
+    settings: &Settings,
+    base: &str,
+    dir: &Path,
+    existing: Option<PathBuf>,
+    output_file: Option<PathBuf>,
+    depth: DepthArg,
+    quiet: bool,
+) -> Result<Vec<(PathBuf, AnalysisCrawlError)>, Box<dyn std::error::Error>> {
+    let changed = changed_files(dir, base)?;
+    let crawler = build_crawler(settings)?;
+    let mut options = build_analysis_options(settings, depth, None, None, None)?;
+    options.analysis_context.project_type = detect_project_type(dir);
+    let updated = crawler
+        .analyze_files(dir, &changed.changed, &options)
+        .await?;
+    let failures = crawler.take_failures();
+    let updated_paths: Vec<PathBuf> = updated
+        .iter()
+        .map(|analysis| analysis.file_path.clone())
+        .collect();
+
+    let mut analyses = match &existing {
+        Some(path) if path.exists() => load_analyses(path)?,
+        _ => Vec::new(),
+    };
+    let reused = merge_file_analyses(&mut analyses, updated, &changed.deleted);
+
+    if !quiet {
+        eprintln!("🔄 Updated {} file(s):", updated_paths.len());
+        for path in &updated_paths {
+            eprintln!("  {}", path.display());
+        }
+        eprintln!("🗑️ Removed {} deleted file(s):", changed.deleted.len());
+        for path in &changed.deleted {
+            eprintln!("  {}", path.display());
+        }
+        eprintln!("♻️ Reused {} file(s) from the existing analysis", reused);
+        if !failures.is_empty() {
+            eprint!("{}", failures_summary(&failures));
+        }
+    }
+
+    NdjsonGenerator::new(output_file.or(existing))
+        .generate_documentation(&None, &analyses)
+        .await?;
+    Ok(failures)
+}
+
 pub async fn run_application() -> Result<(), Box<dyn std::error::Error>> {
     let cli: Cli = Cli::parse();
     if let Some(generator) = cli.completions {
@@ -157,13 +908,42 @@ pub async fn run_application() -> Result<(), Box<dyn std::error::Error>> {
         print_completions(generator, &mut cmd);
     };
     init_tracing(cli.log_level.clone(), cli.json_logs)?;
-    let settings: Settings = match cli.config {
-        Some(config_path) => Settings::from_file(&config_path.to_string_lossy())?,
-        None => Settings::from_env()?,
-    };
+    // Keys kept in .env must be in the environment before the settings are loaded and checked
+    dotenv().ok();
+    // Doctor reports a config that fails to load instead of bailing out on it
+    if let Some(Commands::Doctor { ping }) = cli.command {
+        if !doctor(load_settings(&cli.config), ping, &mut io::stdout().lock()).await? {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    let mut settings = load_settings(&cli.config)?;
+
+    // A --model flag replaces the configured models before they are checked
+    if let Some(Commands::Generate {
+        model: Some(model), ..
+    }) = &cli.command
+    {
+        settings.llm_settings.use_only_model(model.clone());
+    }
+
+    // Only commands that call a model need usable LLM settings
+    if matches!(
+        cli.command,
+        Some(Commands::Generate { .. } | Commands::GenerateDiff { .. } | Commands::Watch { .. })
+    ) && let Err(problems) = settings.validate()
+    {
+        error!("Invalid settings:");
+        for problem in &problems {
+            error!("  - {}", problem);
+        }
+        std::process::exit(1);
+    }
 
     match cli.command {
-        Some(Commands::Crawl) => crawl(),
+        Some(Commands::Crawl { dir, json, stats }) => {
+            crawl(&settings, &dir, json, stats, &mut io::stdout().lock())
+        }
         Some(Commands::Config { output, format }) => {
             if let Err(e) = Settings::write_default_config(output, format) {
                 error!("Error generating config: {}", e);
@@ -175,83 +955,654 @@ pub async fn run_application() -> Result<(), Box<dyn std::error::Error>> {
             preview,
             dir,
             directory_output,
+            format,
+            output_file,
+            max_tokens,
+            max_age,
+            min_age,
+            depth,
+            resume,
+            no_default_excludes,
+            stdout,
+            audience,
+            only,
+            model: _,
+            fail_on_error,
         }) => {
-            dotenv().ok();
-            let clients = settings
-                .llm_settings
-                .models
-                .iter()
-                .map(|m| {
-                    PoolMember::new(
-                        m.priority,
-                        LlmClient::new(
-                            m.model.clone(),
-                            m.api_key.clone(),
-                            m.max_tokens,
-                            m.temperature,
-                        ),
-                    )
-                })
-                .collect::<Vec<PoolMember>>();
-
-            let analyser: LlmPool = LlmPool::new(clients, settings.llm_settings.behaviour);
-            let crawler = AnalysisCrawler::new(analyser);
-
-            let options = AnalysisCrawlOptions {
-                crawl_options: CrawlOptions {
-                    exclude_patterns: settings.files.exclude_patterns,
-                    glob_patterns: settings.files.include_patterns,
-                    include_hidden: settings.files.include_hidden,
-                    max_depth: settings.files.max_depth,
-                    ..Default::default()
-                },
-                ..Default::default()
-            };
+            let crawler = build_crawler(&settings)?;
+            let mut options =
+                build_analysis_options(&settings, depth, max_tokens, max_age, min_age)?;
+            options.crawl_options.no_default_excludes = no_default_excludes;
+            options.crawl_options.only_patterns = only;
+            if let Some(audience) = audience {
+                options.analysis_context.target_audience = audience.into();
+            }
             match preview {
                 true => {
                     let preview = crawler.preview_analysis(dir.clone(), &options)?;
-                    preview.print_summary()
+                    let file_model = settings
+                        .llm_settings
+                        .file_model
+                        .as_ref()
+                        .or(settings.llm_settings.models.first());
+                    preview.print_summary(file_model.map(|model| &model.model));
+                    if let OutputFormat::Markdown = format {
+                        let generator = markdown_generator(
+                            &settings.markdown,
+                            &dir,
+                            directory_output,
+                            cli.quiet,
+                        );
+                        print_output_plan(&generator.plan_paths(
+                            true,
+                            &preview.analyzable_paths,
+                            &preview.synthesized_directories,
+                        ));
+                    }
                 }
                 false => {
-                    let crawl_spinner = ProgressBar::new_spinner();
-                    crawl_spinner.set_style(
-                        ProgressStyle::default_spinner()
-                            .tick_chars("⠁⠂⠄⡀⢀⠠⠐⠈ ")
-                            .template("{spinner:.blue} {msg}")
-                            .unwrap(),
+                    // Only pages written to the output directory keep a resume state next to
+                    // them, and an --only run leaves the full run's state as it is
+                    let partial_run = !options.crawl_options.only_patterns.is_empty();
+                    let state_dir = match format {
+                        OutputFormat::Markdown if stdout || partial_run => None,
+                        OutputFormat::Markdown => Some(
+                            directory_output
+                                .clone()
+                                .unwrap_or_else(|| MarkdownConfig::default().output_dir),
+                        ),
+                        OutputFormat::Ndjson => None,
+                    };
+                    let crawler = match &state_dir {
+                        Some(state_dir) => {
+                            if resume && !cli.quiet {
+                                println!(
+                                    "Found {} saved analyses in {}, reusing those still up to date",
+                                    load_prior_analysis(state_dir).len(),
+                                    state_dir.display()
+                                );
+                            }
+                            crawler.with_resume_state(state_dir, resume)?
+                        }
+                        None if resume => {
+                            return Err("--resume needs markdown written to an output directory, \
+                                 without --only"
+                                .into());
+                        }
+                        None => crawler,
+                    };
+                    let cancel = CancellationToken::new();
+                    let crawler = crawler.with_cancellation(cancel.clone());
+                    // Ctrl-C stops the run; finished analyses are already in the resume state
+                    tokio::spawn({
+                        let cancel = cancel.clone();
+                        async move {
+                            if tokio::signal::ctrl_c().await.is_ok() {
+                                cancel.cancel();
+                            }
+                        }
+                    });
+                    let preview = crawler.preview_analysis(dir.clone(), &options)?;
+                    let progress = match cli.quiet {
+                        true => ProgressBar::hidden(),
+                        false => ProgressBar::new(preview.analyzable_files as u64),
+                    };
+                    progress.set_style(
+                        ProgressStyle::default_bar()
+                            .template("{spinner:.blue} [{bar:40.cyan/blue}] {pos}/{len} {msg}")
+                            .unwrap()
+                            .progress_chars("=> "),
                     );
-                    crawl_spinner.set_message("Crawling directory structure...");
-                    crawl_spinner.enable_steady_tick(Duration::from_millis(100));
-                    let (analysis, children) =
-                        crawler.analyze_project(dir.clone(), options).await?;
-                    crawl_spinner.finish_with_message("✅ Directory crawling complete");
+                    progress.enable_steady_tick(Duration::from_millis(100));
 
-                    let mut config_builder = MarkdownConfig::builder().project_root(dir.clone());
+                    // NDJSON records are written as each analysis completes, not after the run
+                    let ndjson = match format {
+                        OutputFormat::Ndjson => {
+                            Some(Arc::new(NdjsonGenerator::new(output_file.clone())))
+                        }
+                        OutputFormat::Markdown => None,
+                    };
+                    let (sender, mut receiver) = mpsc::channel::<AnalysisEvent>(64);
+                    let progress_handle = {
+                        let progress = progress.clone();
+                        let ndjson = ndjson.clone();
+                        tokio::spawn(async move {
+                            let mut write_result = Ok(());
+                            while let Some(event) = receiver.recv().await {
+                                match event {
+                                    AnalysisEvent::FileStarted(path) => {
+                                        progress.set_message(format!("{}", path.display()));
+                                    }
+                                    AnalysisEvent::FileCompleted(_) => progress.inc(1),
+                                    AnalysisEvent::FileFailed(path) => {
+                                        progress.inc(1);
+                                        progress.println(format!(
+                                            "❌ Failed to analyze {}",
+                                            path.display()
+                                        ));
+                                    }
+                                    AnalysisEvent::DirectorySynthesized(path) => {
+                                        progress
+                                            .set_message(format!("Synthesized {}", path.display()));
+                                    }
+                                    AnalysisEvent::Analyzed(analysis) => {
+                                        // Keep draining after a failed write so the run isn't blocked
+                                        if let (Some(ndjson), Ok(())) = (&ndjson, &write_result) {
+                                            write_result = ndjson.write_child(&analysis);
+                                        }
+                                    }
+                                }
+                            }
+                            write_result
+                        })
+                    };
 
-                    if let Some(output_dir) = directory_output {
-                        config_builder = config_builder.output_dir(output_dir);
+                    let result = crawler
+                        .analyze_project(dir.clone(), options, Some(sender))
+                        .await;
+                    let write_result = progress_handle.await?;
+                    if cancel.is_cancelled() {
+                        progress.abandon_with_message("Interrupted");
+                        let stats = crawler.stats();
+                        let finished = stats.files_analyzed + stats.directories_synthesized;
+                        match &state_dir {
+                            Some(state_dir) => eprintln!(
+                                "Interrupted: {} analyses saved in {}, run again with --resume to continue",
+                                finished,
+                                state_dir.join(RESUME_STATE_FILE).display()
+                            ),
+                            None => eprintln!("Interrupted after {} analyses", finished),
+                        }
+                        std::process::exit(INTERRUPTED_EXIT_CODE);
                     }
+                    let run = result?;
+                    write_result?;
+                    progress.finish_with_message("✅ Analysis complete");
 
-                    let config = config_builder.build();
-                    let md_generator = MarkdownGenerator::new(config);
-                    md_generator
-                        .generate_documentation(&analysis, &children)
-                        .await?;
+                    match &ndjson {
+                        Some(ndjson) => {
+                            if let Some(project) = &run.project {
+                                ndjson.write_project(project)?;
+                            }
+                        }
+                        None => {
+                            let backend = match stdout {
+                                true => {
+                                    markdown_generator(&settings.markdown, &dir, None, cli.quiet)
+                                        .with_sink(StdoutSink)
+                                }
+                                false => markdown_generator(
+                                    &settings.markdown,
+                                    &dir,
+                                    directory_output,
+                                    cli.quiet,
+                                ),
+                            };
+                            backend
+                                .generate_documentation(&run.project, &run.children)
+                                .await?;
+                        }
+                    }
+
+                    let file_model = settings
+                        .llm_settings
+                        .file_model
+                        .as_ref()
+                        .or(settings.llm_settings.models.first());
+                    let mut summary = crawler
+                        .stats()
+                        .summary(file_model.map(|model| &model.model));
+                    if !run.failures.is_empty() {
+                        summary.push_str(&failures_summary(&run.failures));
+                    }
+                    // Keep stdout clean for the pages or records when they are printed there
+                    let to_stdout = stdout || (ndjson.is_some() && output_file.is_none());
+                    match (cli.quiet, to_stdout) {
+                        (true, _) => {}
+                        (false, true) => eprint!("{}", summary),
+                        (false, false) => print!("{}", summary),
+                    }
+                    if fail_on_error && !run.failures.is_empty() {
+                        return Err(format!(
+                            "{} file(s) or directories failed to analyze",
+                            run.failures.len()
+                        )
+                        .into());
+                    }
                 }
             }
             Ok(())
         }
-        Some(Commands::Models) => {
-            for model in ModelId::iter() {
-                println!(
-                    "model-id: {:?}, provider: {:?}",
-                    model.to_string(),
-                    model.provider()
-                );
+        Some(Commands::GenerateDiff {
+            base,
+            dir,
+            existing,
+            output_file,
+            depth,
+            fail_on_error,
+        }) => {
+            let failures = generate_diff(
+                &settings,
+                &base,
+                &dir,
+                existing,
+                output_file,
+                depth,
+                cli.quiet,
+            )
+            .await?;
+            if fail_on_error && !failures.is_empty() {
+                return Err(format!("{} file(s) failed to analyze", failures.len()).into());
+            }
+            Ok(())
+        }
+        Some(Commands::Models {
+            provider,
+            structured_only,
+            reasoning_only,
+            multimodal_only,
+        }) => {
+            let models = filter_models(
+                provider.as_ref(),
+                structured_only,
+                reasoning_only,
+                multimodal_only,
+            );
+            print!("{}", models_table(&models));
+            Ok(())
+        }
+        Some(Commands::Watch {
+            dir,
+            directory_output,
+        }) => watch(&settings, dir, directory_output, cli.quiet).await,
+        Some(Commands::Doctor { .. }) => unreachable!("doctor runs before the settings are loaded"),
+        Some(Commands::Diff { old, new, output }) => {
+            let diff = AnalysisDiff::between(&load_analyses(&old)?, &load_analyses(&new)?);
+            match output {
+                Some(path) => std::fs::write(path, diff.to_string())?,
+                None => print!("{}", diff),
             }
-
             Ok(())
         }
         None => Ok(()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    #[test]
+    fn test_depth_flag_reaches_analysis_context() {
+        let cli = Cli::try_parse_from(["auto-doc", "generate", "--depth", "surface", "."]).unwrap();
+        let Some(Commands::Generate { depth, .. }) = cli.command else {
+            panic!("Expected generate command");
+        };
+
+        let options =
+            build_analysis_options(&Settings::default(), depth, None, None, None).unwrap();
+        assert_eq!(
+            options.analysis_context.analysis_depth,
+            AnalysisDepth::Surface
+        );
+    }
+
+    #[test]
+    fn test_audience_from_config_and_flag() {
+        let mut settings = Settings::default();
+        settings.files.audience = Some(AnalysisAudience::TechnicalDocumentation);
+        let options =
+            build_analysis_options(&settings, DepthArg::Standard, None, None, None).unwrap();
+        assert_eq!(
+            options.analysis_context.target_audience,
+            AnalysisAudience::TechnicalDocumentation
+        );
+
+        let cli =
+            Cli::try_parse_from(["auto-doc", "generate", "--audience", "developer", "."]).unwrap();
+        let Some(Commands::Generate { audience, .. }) = cli.command else {
+            panic!("Expected generate command");
+        };
+        assert_eq!(
+            audience.map(AnalysisAudience::from),
+            Some(AnalysisAudience::HumanDeveloper)
+        );
+    }
+
+    #[test]
+    fn test_only_flag_is_repeatable() {
+        let cli = Cli::try_parse_from([
+            "auto-doc",
+            "generate",
+            "--only",
+            "src/crawler/**",
+            "--only",
+            "src/cli.rs",
+            ".",
+        ])
+        .unwrap();
+        let Some(Commands::Generate { only, .. }) = cli.command else {
+            panic!("Expected generate command");
+        };
+        assert_eq!(only, vec!["src/crawler/**", "src/cli.rs"]);
+    }
+
+    #[test]
+    fn test_configured_extensions_extend_defaults() {
+        let mut settings = Settings::default();
+        settings.files.analyzable_extensions = Some(vec![".vue".to_string(), "rs".to_string()]);
+        let options =
+            build_analysis_options(&settings, DepthArg::Standard, None, None, None).unwrap();
+
+        let defaults = AnalysisCrawlOptions::default().analyzable_extensions;
+        assert_eq!(options.analyzable_extensions.len(), defaults.len() + 1);
+        assert!(options.analyzable_extensions.starts_with(&defaults));
+        assert_eq!(options.analyzable_extensions.last().unwrap(), "vue");
+    }
+
+    #[test]
+    fn test_configured_extensions_can_replace_defaults() {
+        let mut settings = Settings::default();
+        settings.files.analyzable_extensions = Some(vec!["vue".to_string(), "sql".to_string()]);
+        settings.files.replace_default_extensions = true;
+        let options =
+            build_analysis_options(&settings, DepthArg::Standard, None, None, None).unwrap();
+        assert_eq!(options.analyzable_extensions, vec!["vue", "sql"]);
+
+        // Without a list there is nothing to replace the defaults with
+        settings.files.analyzable_extensions = None;
+        let options =
+            build_analysis_options(&settings, DepthArg::Standard, None, None, None).unwrap();
+        assert_eq!(
+            options.analyzable_extensions,
+            AnalysisCrawlOptions::default().analyzable_extensions
+        );
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_doctor_checks_api_keys() {
+        let env_var = ModelId::Llama33.api_key_env_var();
+        unsafe { std::env::remove_var(env_var) };
+        let mut settings = Settings::default();
+        settings.llm_settings.models = vec![LlmModel {
+            model: ModelId::Llama33,
+            ..Default::default()
+        }];
+
+        let mut out = Vec::new();
+        assert!(!doctor(Ok(settings), false, &mut out).await.unwrap());
+        let report = String::from_utf8(out).unwrap();
+        assert!(report.contains("Settings loaded"));
+        assert!(report.contains(CHECK_FAILED));
+        assert!(report.contains(env_var));
+
+        let mut settings = Settings::default();
+        settings.llm_settings.models = vec![LlmModel {
+            model: ModelId::Llama33,
+            api_key: Some("key".to_string()),
+            ..Default::default()
+        }];
+
+        let mut out = Vec::new();
+        assert!(doctor(Ok(settings), false, &mut out).await.unwrap());
+        let report = String::from_utf8(out).unwrap();
+        assert!(report.contains("Settings are valid"));
+        assert!(report.contains("API key found"));
+        assert!(!report.contains(CHECK_FAILED));
+    }
+
+    #[tokio::test]
+    async fn test_doctor_reports_unloadable_settings() {
+        let settings = Err(ConfigError::Message("bad config".to_string()));
+
+        let mut out = Vec::new();
+        assert!(!doctor(settings, false, &mut out).await.unwrap());
+        assert!(String::from_utf8(out).unwrap().contains("bad config"));
+
+        let cli = Cli::try_parse_from(["auto-doc", "doctor", "--ping"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::Doctor { ping: true })));
+    }
+
+    #[test]
+    fn test_crawl_json_round_trips() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+        std::fs::write(temp_dir.path().join("src/lib.rs"), "pub fn lib() {}").unwrap();
+        std::fs::write(temp_dir.path().join("README.md"), "# Readme").unwrap();
+
+        let cli = Cli::try_parse_from([
+            "auto-doc",
+            "crawl",
+            "--json",
+            temp_dir.path().to_str().unwrap(),
+        ])
+        .unwrap();
+        let Some(Commands::Crawl { dir, json, stats }) = cli.command else {
+            panic!("Expected crawl command");
+        };
+
+        let mut out = Vec::new();
+        crawl(&Settings::default(), &dir, json, stats, &mut out).unwrap();
+        let tree: FileNode = serde_json::from_slice(&out).unwrap();
+        assert_eq!(tree.total_files(), 2);
+        assert_eq!(tree.find_files_by_extension("rs").len(), 1);
+
+        let mut out = Vec::new();
+        crawl(&Settings::default(), &dir, false, true, &mut out).unwrap();
+        let stats = String::from_utf8(out).unwrap();
+        assert!(stats.contains("Total files: 2"));
+        assert!(stats.contains(".rs: 1 files"));
+        assert!(stats.contains(".md: 1 files"));
+
+        assert!(Cli::try_parse_from(["auto-doc", "crawl", "--json", "--stats"]).is_err());
+    }
+
+    #[test]
+    fn test_quiet_flag_is_global() {
+        let cli = Cli::try_parse_from(["auto-doc", "generate", ".", "--quiet"]).unwrap();
+        assert!(cli.quiet);
+        let cli = Cli::try_parse_from(["auto-doc", "-q", "watch", "."]).unwrap();
+        assert!(cli.quiet);
+        assert!(!Cli::try_parse_from(["auto-doc", "crawl"]).unwrap().quiet);
+    }
+
+    #[test]
+    fn test_parse_age_rejects_overflow() {
+        assert_eq!(parse_age("2h"), Ok(Duration::from_secs(7200)));
+        assert!(
+            parse_age("99999999999999999w")
+                .unwrap_err()
+                .contains("too large")
+        );
+    }
+
+    #[test]
+    fn test_single_file_is_documented_beside_the_output_root() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let file = temp_dir.path().join("main.rs");
+        std::fs::write(&file, "fn main() {}").unwrap();
+
+        let generator = markdown_generator(
+            &MarkdownSettings::default(),
+            &file,
+            Some(PathBuf::from("docs")),
+            true,
+        );
+        assert_eq!(
+            generator.plan_paths(false, std::slice::from_ref(&file), &[]),
+            vec![PathBuf::from("docs/main.md")]
+        );
+    }
+
+    #[test]
+    fn test_model_flag_uses_config_names() {
+        let cli = Cli::try_parse_from([
+            "auto-doc",
+            "generate",
+            "--model",
+            "claude-3-5-haiku-latest",
+            ".",
+        ])
+        .unwrap();
+        let Some(Commands::Generate { model, .. }) = cli.command else {
+            panic!("Expected generate command");
+        };
+        assert_eq!(model, Some(ModelId::Claude35Haiku));
+
+        assert!(Cli::try_parse_from(["auto-doc", "generate", "--model", "gpt-9", "."]).is_err());
+    }
+
+    #[test]
+    fn test_config_flag_is_repeatable() {
+        let cli = Cli::try_parse_from([
+            "auto-doc",
+            "--config",
+            "team.toml",
+            "--config",
+            "mine.toml",
+            "crawl",
+        ])
+        .unwrap();
+        assert_eq!(
+            cli.config,
+            vec![PathBuf::from("team.toml"), PathBuf::from("mine.toml")]
+        );
+    }
+
+    #[test]
+    fn test_generate_diff_args() {
+        let cli = Cli::try_parse_from([
+            "auto-doc",
+            "generate-diff",
+            "origin/main",
+            ".",
+            "--existing",
+            "docs.ndjson",
+            "--fail-on-error",
+        ])
+        .unwrap();
+        let Some(Commands::GenerateDiff {
+            base,
+            existing,
+            output_file,
+            fail_on_error,
+            ..
+        }) = cli.command
+        else {
+            panic!("Expected generate-diff command");
+        };
+        assert_eq!(base, "origin/main");
+        assert_eq!(existing, Some(PathBuf::from("docs.ndjson")));
+        assert_eq!(output_file, None);
+        assert!(fail_on_error);
+    }
+
+    #[test]
+    fn test_every_configured_model_joins_the_pool() {
+        let model = |model, priority| LlmModel {
+            model,
+            priority,
+            api_key: Some("test".to_string()),
+            ..Default::default()
+        };
+        let llm_settings = LlmSettings {
+            behaviour: Behaviour::Failover,
+            models: vec![
+                model(ModelId::Claude4Sonnet, 2),
+                model(ModelId::Gpt4o, 1),
+                model(ModelId::Claude35Haiku, 3),
+            ],
+            ..Default::default()
+        };
+
+        let pool = build_pool(
+            &llm_settings.models,
+            llm_settings.behaviour.clone(),
+            &llm_settings,
+        )
+        .unwrap();
+        assert_eq!(pool.len(), 3);
+        // Failover serves the highest priority (lowest number) model first
+        assert_eq!(pool.get_client().model, ModelId::Gpt4o);
+
+        let no_models = LlmSettings {
+            models: vec![],
+            ..Default::default()
+        };
+        assert!(matches!(
+            build_pool(&no_models.models, Behaviour::Failover, &no_models),
+            Err(LlmPoolBuilderError::NoClients)
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn test_build_pool_reports_missing_key() {
+        let env_var = ModelId::Llama33.api_key_env_var();
+        unsafe { std::env::remove_var(env_var) };
+        let llm_settings = LlmSettings {
+            models: vec![LlmModel {
+                model: ModelId::Llama33,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let Err(LlmPoolBuilderError::Client(message)) =
+            build_pool(&llm_settings.models, Behaviour::Failover, &llm_settings)
+        else {
+            panic!("Expected a client error");
+        };
+        assert!(message.contains(env_var));
+    }
+
+    #[test]
+    fn test_prompt_override_reaches_file_prompt() {
+        let settings = Settings::default();
+        let model = LlmModel {
+            api_key: Some("test".to_string()),
+            prompt_override: Some("Summarise {FILE_PATH} for the wiki".to_string()),
+            ..Default::default()
+        };
+        let options =
+            build_analysis_options(&settings, DepthArg::Standard, None, None, None).unwrap();
+
+        let pool = build_stage_pool(&model, &settings.llm_settings).unwrap();
+        let prompt = pool
+            .get_client()
+            .prompt_templates()
+            .build_file_analysis_prompt(Path::new("src/lib.rs"), &options.analysis_context);
+        assert_eq!(prompt, "Summarise src/lib.rs for the wiki");
+    }
+
+    #[test]
+    fn test_models_provider_filter() {
+        let cli = Cli::try_parse_from(["auto-doc", "models", "--provider", "anthropic"]).unwrap();
+        let Some(Commands::Models { provider, .. }) = cli.command else {
+            panic!("Expected models command");
+        };
+
+        let models = filter_models(provider.as_ref(), false, false, false);
+        assert!(!models.is_empty());
+        assert!(
+            models
+                .iter()
+                .all(|model| model.to_string().starts_with("claude"))
+        );
+
+        let reasoning = filter_models(None, false, true, false);
+        assert!(reasoning.iter().all(ModelId::has_reasoning));
+    }
+
+    #[test]
+    fn test_models_table_is_aligned() {
+        let table = models_table(&[ModelId::Gpt4o, ModelId::Claude35Haiku]);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 3);
+        let provider_column = lines[0].find("PROVIDER").unwrap();
+        assert_eq!(lines[1].find("openai"), Some(provider_column));
+        assert_eq!(lines[2].find("anthropic"), Some(provider_column));
+    }
+}
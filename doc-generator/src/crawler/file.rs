@@ -1,7 +1,10 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, VecDeque};
 use std::fs;
+use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,11 +14,14 @@ pub enum FileNode {
         path: PathBuf,
         size: u64,
         extension: Option<String>,
+        /// Last modification time, if the platform reports one
+        modified: Option<SystemTime>,
     },
     Directory {
         name: String,
         path: PathBuf,
-        children: HashMap<String, FileNode>,
+        /// Keyed by name, so traversals and serialization are stable across runs
+        children: BTreeMap<String, FileNode>,
         total_size: u64,
     },
 }
@@ -36,6 +42,14 @@ impl FileNode {
         }
     }
 
+    /// Size of a file, or the total size of every file under a directory
+    pub fn size(&self) -> u64 {
+        match self {
+            FileNode::File { size, .. } => *size,
+            FileNode::Directory { total_size, .. } => *total_size,
+        }
+    }
+
     pub fn is_file(&self) -> bool {
         matches!(self, FileNode::File { .. })
     }
@@ -46,6 +60,22 @@ impl FileNode {
     }
 }
 
+/// Build output, dependency and VCS directories skipped by every crawl unless
+/// `CrawlOptions::no_default_excludes` is set: `.git`, `.hg`, `.svn`, `target`,
+/// `node_modules`, `__pycache__`, `.venv`, `venv`, `dist` and `build`, at any depth.
+pub const DEFAULT_EXCLUDE_PATTERNS: &[&str] = &[
+    ".git/",
+    ".hg/",
+    ".svn/",
+    "target/",
+    "node_modules/",
+    "__pycache__/",
+    ".venv/",
+    "venv/",
+    "dist/",
+    "build/",
+];
+
 #[derive(Debug, Clone, Default)]
 pub struct CrawlOptions {
     pub max_depth: Option<usize>,
@@ -53,6 +83,25 @@ pub struct CrawlOptions {
     pub include_hidden: bool,
     pub glob_patterns: Vec<String>,
     pub exclude_patterns: Vec<String>,
+    /// Only apply `exclude_patterns`, not `DEFAULT_EXCLUDE_PATTERNS`
+    pub no_default_excludes: bool,
+    /// Further narrow the crawl: files must match one of these as well as `glob_patterns`
+    pub only_patterns: Vec<String>,
+}
+
+impl CrawlOptions {
+    /// The user's exclude patterns, preceded by the defaults unless they are turned off
+    fn all_exclude_patterns(&self) -> Vec<String> {
+        let defaults = match self.no_default_excludes {
+            true => &[][..],
+            false => DEFAULT_EXCLUDE_PATTERNS,
+        };
+        defaults
+            .iter()
+            .map(|pattern| pattern.to_string())
+            .chain(self.exclude_patterns.iter().cloned())
+            .collect()
+    }
 }
 
 pub type CrawlResult = Result<FileNode, CrawlError>;
@@ -62,13 +111,139 @@ pub enum CrawlError {
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
     #[error("Glob pattern error: {0}")]
-    GlobPattern(#[from] glob::PatternError),
-    #[error("Glob error: {0}")]
-    Glob(#[from] glob::GlobError),
+    GlobPattern(#[from] globset::Error),
     #[error("Path does not exist: {0}")]
     PathNotFound(PathBuf),
     #[error("Maximum depth exceeded")]
     MaxDepthExceeded,
+    #[error("Git error: {0}")]
+    Git(String),
+}
+
+/// Include or exclude patterns compiled once and matched against paths relative
+/// to the crawl root, following gitignore conventions:
+///
+/// - A pattern without a `/` (`*.rs`, `target`) matches at any depth.
+/// - A pattern containing a `/` (`src/**/*.rs`) is anchored to the crawl root.
+/// - A trailing `/` is ignored, and `dir/**` also matches `dir` itself so an
+///   excluded directory is pruned rather than walked.
+/// - A leading `!` negates a pattern: anything it matches is dropped from the
+///   set even if another pattern matched it. Excluding `["*.log", "!keep.log"]`
+///   skips every log except `keep.log`; including `["**/*.rs", "!tests/**"]`
+///   takes every Rust file outside `tests`. A negation cannot re-include files
+///   inside an excluded directory, as excluded directories are never descended.
+struct PatternSet {
+    matches: GlobSet,
+    negated: GlobSet,
+}
+
+impl PatternSet {
+    /// Compile the patterns, or `None` if there are none to match
+    fn new(patterns: &[String]) -> Result<Option<Self>, globset::Error> {
+        if patterns.is_empty() {
+            return Ok(None);
+        }
+
+        let mut matches = GlobSetBuilder::new();
+        let mut negated = GlobSetBuilder::new();
+        for pattern in patterns {
+            let (builder, pattern) = match pattern.strip_prefix('!') {
+                Some(pattern) => (&mut negated, pattern),
+                None => (&mut matches, pattern.as_str()),
+            };
+            for glob in Self::expand(pattern) {
+                builder.add(GlobBuilder::new(&glob).literal_separator(true).build()?);
+            }
+        }
+
+        Ok(Some(Self {
+            matches: matches.build()?,
+            negated: negated.build()?,
+        }))
+    }
+
+    /// Rewrite a gitignore-style pattern into the globs that implement it
+    fn expand(pattern: &str) -> Vec<String> {
+        let pattern = pattern.trim_end_matches('/');
+        let glob = match pattern.strip_prefix('/') {
+            Some(anchored) => anchored.to_string(),
+            None if pattern.contains('/') => pattern.to_string(),
+            None => format!("**/{}", pattern),
+        };
+
+        match glob.strip_suffix("/**") {
+            Some(directory) => vec![directory.to_string(), glob.clone()],
+            None => vec![glob],
+        }
+    }
+
+    fn is_match(&self, relative_path: &Path) -> bool {
+        self.matches.is_match(relative_path) && !self.negated.is_match(relative_path)
+    }
+}
+
+/// Decides whether paths under a crawl root pass the crawl's hidden-file and
+/// include/exclude rules, without walking the file system
+pub struct PathFilter {
+    root: PathBuf,
+    include_hidden: bool,
+    include: Option<PatternSet>,
+    only: Option<PatternSet>,
+    exclude: Option<PatternSet>,
+}
+
+impl PathFilter {
+    pub fn new(root: &Path, options: &CrawlOptions) -> Result<Self, CrawlError> {
+        Ok(Self {
+            root: root.to_path_buf(),
+            include_hidden: options.include_hidden,
+            include: PatternSet::new(&options.glob_patterns)?,
+            only: PatternSet::new(&options.only_patterns)?,
+            exclude: PatternSet::new(&options.all_exclude_patterns())?,
+        })
+    }
+
+    fn relative<'p>(&self, path: &'p Path) -> &'p Path {
+        path.strip_prefix(&self.root).unwrap_or(path)
+    }
+
+    fn is_excluded(&self, path: &Path) -> bool {
+        self.exclude
+            .as_ref()
+            .is_some_and(|exclude| exclude.is_match(self.relative(path)))
+    }
+
+    fn is_included(&self, path: &Path) -> bool {
+        [&self.include, &self.only]
+            .into_iter()
+            .flatten()
+            .all(|include| include.is_match(self.relative(path)))
+    }
+
+    /// Whether a crawl would reach the file at `path`: it must be included and
+    /// neither it nor any directory above it may be hidden or excluded
+    pub fn allows_file(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+
+        let mut current = self.root.clone();
+        for component in relative.components() {
+            current.push(component);
+            let hidden = component.as_os_str().to_string_lossy().starts_with('.');
+            if (!self.include_hidden && hidden) || self.is_excluded(&current) {
+                return false;
+            }
+        }
+
+        self.is_included(path)
+    }
+}
+
+/// Options and path filter shared across a single crawl
+struct CrawlContext<'a> {
+    options: &'a CrawlOptions,
+    filter: PathFilter,
 }
 
 /// Crawl a directory structure with optional glob pattern filtering
@@ -79,10 +254,15 @@ pub fn crawl_directory<P: AsRef<Path>>(root_path: P, options: CrawlOptions) -> C
         return Err(CrawlError::PathNotFound(root_path.to_path_buf()));
     }
 
-    crawl_recursive(root_path, &options, 0)
+    let context = CrawlContext {
+        options: &options,
+        filter: PathFilter::new(root_path, &options)?,
+    };
+    crawl_recursive(root_path, &context, 0)
 }
 
-fn crawl_recursive(path: &Path, options: &CrawlOptions, current_depth: usize) -> CrawlResult {
+fn crawl_recursive(path: &Path, context: &CrawlContext, current_depth: usize) -> CrawlResult {
+    let options = context.options;
     if let Some(max_depth) = options.max_depth {
         if current_depth > max_depth {
             return Err(CrawlError::MaxDepthExceeded);
@@ -97,7 +277,7 @@ fn crawl_recursive(path: &Path, options: &CrawlOptions, current_depth: usize) ->
     }
 
     if metadata.is_dir() {
-        let mut children = HashMap::new();
+        let mut children = BTreeMap::new();
         let mut total_size = 0u64;
 
         let entries = fs::read_dir(path)?;
@@ -122,24 +302,17 @@ fn crawl_recursive(path: &Path, options: &CrawlOptions, current_depth: usize) ->
             }
 
             // Skip if path matches any exclude patterns (applies to both files and directories)
-            if !options.exclude_patterns.is_empty()
-                && matches_any_pattern(&entry_path, &options.exclude_patterns)
-            {
+            if context.filter.is_excluded(&entry_path) {
                 continue;
             }
 
             // For files, check if they match the include glob patterns
             // For directories, we always recurse (unless excluded above)
-            if entry_path.is_file() {
-                // If we have include patterns, file must match at least one
-                if !options.glob_patterns.is_empty()
-                    && !matches_any_pattern(&entry_path, &options.glob_patterns)
-                {
-                    continue;
-                }
+            if entry_path.is_file() && !context.filter.is_included(&entry_path) {
+                continue;
             }
 
-            match crawl_recursive(&entry_path, options, current_depth + 1) {
+            match crawl_recursive(&entry_path, context, current_depth + 1) {
                 Ok(child_node) => {
                     match &child_node {
                         FileNode::File { size, .. } => total_size += size,
@@ -173,7 +346,8 @@ fn crawl_recursive(path: &Path, options: &CrawlOptions, current_depth: usize) ->
     create_file_node(path)
 }
 
-fn create_file_node(path: &Path) -> Result<FileNode, CrawlError> {
+/// A node for a single path, without descending into directories
+pub fn create_file_node(path: &Path) -> Result<FileNode, CrawlError> {
     let metadata = fs::metadata(path)?;
     let name = path
         .file_name()
@@ -192,60 +366,51 @@ fn create_file_node(path: &Path) -> Result<FileNode, CrawlError> {
             path: path.to_path_buf(),
             size: metadata.len(),
             extension,
+            modified: metadata.modified().ok(),
         })
     } else {
         Ok(FileNode::Directory {
             name,
             path: path.to_path_buf(),
-            children: HashMap::new(),
+            children: BTreeMap::new(),
             total_size: 0,
         })
     }
 }
 
-fn matches_any_pattern(path: &Path, patterns: &[String]) -> bool {
-    if patterns.is_empty() {
-        return false; // Empty patterns should match nothing, not everything
+/// How much of a file is sniffed when deciding whether it's binary
+const BINARY_SNIFF_LEN: u64 = 8 * 1024;
+
+/// Sniff the start of a file for binary content: any NUL byte, or more than 30%
+/// of characters being invalid UTF-8 or non-whitespace control characters.
+/// Files starting with a UTF-16 byte order mark are treated as text.
+/// Unreadable files are treated as binary.
+pub fn is_binary_file(path: &Path) -> bool {
+    let mut buffer = Vec::new();
+    let read =
+        fs::File::open(path).and_then(|file| file.take(BINARY_SNIFF_LEN).read_to_end(&mut buffer));
+    if read.is_err() {
+        return true;
+    }
+    // UTF-16 text is full of NULs but announces itself with a byte order mark
+    if buffer.starts_with(&[0xFF, 0xFE]) || buffer.starts_with(&[0xFE, 0xFF]) {
+        return false;
+    }
+    if buffer.contains(&0) {
+        return true;
     }
 
-    let path_str = path.to_string_lossy();
-
-    for pattern in patterns {
-        if let Ok(glob_matcher) = glob::Pattern::new(pattern) {
-            // Check full path
-            if glob_matcher.matches(&path_str) {
-                return true;
-            }
-            // Also check just the filename
-            if let Some(filename) = path.file_name() {
-                let filename_str = filename.to_string_lossy();
-                if glob_matcher.matches(&filename_str) {
-                    return true;
-                }
-            }
-            // For relative paths, try without leading components
-            if let Ok(relative_path) =
-                path.strip_prefix(std::env::current_dir().unwrap_or_default())
-            {
-                if glob_matcher.matches(&relative_path.to_string_lossy()) {
-                    return true;
-                }
-            }
-
-            // Special handling for directory patterns like "target/**"
-            // If pattern ends with "/**", also match the directory name itself
-            if pattern.ends_with("/**") {
-                let dir_pattern = pattern.trim_end_matches("/**");
-                if let Some(filename) = path.file_name() {
-                    if filename.to_string_lossy() == dir_pattern {
-                        return true;
-                    }
-                }
-            }
+    let text = String::from_utf8_lossy(&buffer);
+    let mut total = 0usize;
+    let mut suspicious = 0usize;
+    for c in text.chars() {
+        total += 1;
+        if c == char::REPLACEMENT_CHARACTER || (c.is_control() && !c.is_whitespace()) {
+            suspicious += 1;
         }
     }
 
-    false
+    suspicious * 10 > total * 3
 }
 
 // Utility functions for working with the file tree
@@ -282,6 +447,7 @@ impl FileNode {
         }
     }
 
+    /// The tree as indented lines, two spaces per level, with sizes
     pub fn tree_string(&self) -> String {
         let mut result = String::new();
         self.tree_string_recursive(0, &mut result);
@@ -320,6 +486,7 @@ impl FileNode {
         }
     }
 
+    #[allow(dead_code)]
     /// Keep the original print method for compatibility
     pub fn print_tree(&self) {
         print!("{}", self.tree_string());
@@ -336,6 +503,12 @@ impl FileNode {
         FileNodeDepthIterator::new(self)
     }
 
+    #[allow(dead_code)]
+    /// Returns an iterator over all nodes with their depth level, shallowest first
+    pub fn iter_bfs(&self) -> FileNodeBfsIterator<'_> {
+        FileNodeBfsIterator::new(self)
+    }
+
     /// Collects all nodes into a Vec (convenience method)
     #[allow(dead_code)]
     pub fn collect_all_nodes(&self) -> Vec<&FileNode> {
@@ -422,6 +595,33 @@ impl<'a> Iterator for FileNodeDepthIterator<'a> {
     }
 }
 
+/// Iterator that traverses all nodes with their depth level (breadth-first)
+pub struct FileNodeBfsIterator<'a> {
+    queue: VecDeque<(&'a FileNode, usize)>,
+}
+
+impl<'a> FileNodeBfsIterator<'a> {
+    fn new(root: &'a FileNode) -> Self {
+        Self {
+            queue: VecDeque::from([(root, 0)]),
+        }
+    }
+}
+
+impl<'a> Iterator for FileNodeBfsIterator<'a> {
+    type Item = (&'a FileNode, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (current, depth) = self.queue.pop_front()?;
+        // If it's a directory, queue its children behind the rest of this level
+        if let FileNode::Directory { children, .. } = current {
+            self.queue
+                .extend(children.values().map(|child| (child, depth + 1)));
+        }
+        Some((current, depth))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -532,4 +732,255 @@ mod tests {
         assert!(with_depth.iter().any(|(_, depth)| *depth == 1)); // files/subdirs at depth 1
         assert!(with_depth.iter().any(|(_, depth)| *depth == 2)); // file2.rs at depth 2
     }
+
+    #[test]
+    fn test_bfs_iterator_yields_shallow_nodes_first() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        fs::create_dir(temp_path.join("a")).unwrap();
+        fs::write(temp_path.join("a/deep.rs"), "content").unwrap();
+        fs::write(temp_path.join("z.txt"), "content").unwrap();
+
+        let result = crawl_directory(temp_path, CrawlOptions::default()).unwrap();
+        let depths: Vec<usize> = result.iter_bfs().map(|(_, depth)| depth).collect();
+
+        assert_eq!(depths, vec![0, 1, 1, 2]);
+        let (deepest, _) = result.iter_bfs().last().unwrap();
+        assert_eq!(deepest.name(), "deep.rs");
+    }
+
+    fn write_tree(root: &Path, files: &[&str]) {
+        for file in files {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "content").unwrap();
+        }
+    }
+
+    fn relative_files(root: &Path, tree: &FileNode) -> Vec<String> {
+        let mut files: Vec<String> = tree
+            .collect_files()
+            .iter()
+            .map(|node| {
+                node.path()
+                    .strip_prefix(root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn test_recursive_include_pattern() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write_tree(
+            root,
+            &["src/main.rs", "src/a/b/deep.rs", "src/notes.md", "build.rs"],
+        );
+
+        let options = CrawlOptions {
+            glob_patterns: vec!["src/**/*.rs".to_string()],
+            ..Default::default()
+        };
+        let tree = crawl_directory(root, options).unwrap();
+
+        assert_eq!(
+            relative_files(root, &tree),
+            vec!["src/a/b/deep.rs", "src/main.rs"]
+        );
+    }
+
+    #[test]
+    fn test_recursive_exclude_patterns() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write_tree(
+            root,
+            &[
+                "node_modules/left-pad/index.js",
+                "web/node_modules/react/index.js",
+                "web/app.js",
+                "target/debug/foo.rs",
+                "src/target/keep.rs",
+            ],
+        );
+
+        let options = CrawlOptions {
+            exclude_patterns: vec!["**/node_modules/**".to_string(), "target/**".to_string()],
+            no_default_excludes: true,
+            ..Default::default()
+        };
+        let tree = crawl_directory(root, options).unwrap();
+
+        // `target/**` is anchored to the root, so only the top-level target is pruned
+        assert_eq!(
+            relative_files(root, &tree),
+            vec!["src/target/keep.rs", "web/app.js"]
+        );
+        let FileNode::Directory { children, .. } = &tree else {
+            panic!("Expected directory node");
+        };
+        assert!(!children.contains_key("node_modules"));
+        assert!(!children.contains_key("target"));
+    }
+
+    #[test]
+    fn test_default_excludes() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write_tree(
+            root,
+            &[
+                "src/main.rs",
+                "target/debug/build.rs",
+                "web/node_modules/a.js",
+            ],
+        );
+
+        let tree = crawl_directory(root, CrawlOptions::default()).unwrap();
+        assert_eq!(relative_files(root, &tree), vec!["src/main.rs"]);
+
+        let options = CrawlOptions {
+            no_default_excludes: true,
+            ..Default::default()
+        };
+        let tree = crawl_directory(root, options).unwrap();
+        assert_eq!(
+            relative_files(root, &tree),
+            vec![
+                "src/main.rs",
+                "target/debug/build.rs",
+                "web/node_modules/a.js"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_negated_patterns() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write_tree(
+            root,
+            &["app.log", "logs/keep.log", "src/lib.rs", "tests/it.rs"],
+        );
+
+        // Negated excludes re-include what another exclude matched
+        let options = CrawlOptions {
+            exclude_patterns: vec!["*.log".to_string(), "!keep.log".to_string()],
+            ..Default::default()
+        };
+        let tree = crawl_directory(root, options).unwrap();
+        assert_eq!(
+            relative_files(root, &tree),
+            vec!["logs/keep.log", "src/lib.rs", "tests/it.rs"]
+        );
+
+        // Negated includes drop matches from the include set
+        let options = CrawlOptions {
+            glob_patterns: vec!["**/*.rs".to_string(), "!tests/**".to_string()],
+            ..Default::default()
+        };
+        let tree = crawl_directory(root, options).unwrap();
+        assert_eq!(relative_files(root, &tree), vec!["src/lib.rs"]);
+    }
+
+    #[test]
+    fn test_only_patterns_narrow_includes() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write_tree(
+            root,
+            &["src/crawler/file.rs", "src/crawler/notes.md", "src/cli.rs"],
+        );
+
+        let options = CrawlOptions {
+            glob_patterns: vec!["**/*.rs".to_string()],
+            only_patterns: vec!["src/crawler/**".to_string()],
+            ..Default::default()
+        };
+        let tree = crawl_directory(root, options).unwrap();
+        assert_eq!(relative_files(root, &tree), vec!["src/crawler/file.rs"]);
+    }
+
+    #[test]
+    fn test_tree_string_indents_children() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write_tree(root, &["src/lib.rs", "README.md"]);
+
+        let tree = crawl_directory(root, CrawlOptions::default()).unwrap();
+        let root_name = root.file_name().unwrap().to_string_lossy();
+        let tree_string = tree.tree_string();
+        let lines: Vec<&str> = tree_string.lines().collect();
+
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with(&format!("📁 {} (", root_name)));
+        assert!(lines[1].starts_with("  📄 README.md ("));
+        assert!(lines[2].starts_with("  📁 src ("));
+        assert!(lines[3].starts_with("    📄 lib.rs ("));
+    }
+
+    #[test]
+    fn test_crawl_is_deterministic() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write_tree(
+            root,
+            &[
+                "zeta.rs",
+                "alpha.rs",
+                "src/mid.rs",
+                "src/b/inner.rs",
+                "src/a/inner.rs",
+                "docs/readme.md",
+            ],
+        );
+
+        let first = crawl_directory(root, CrawlOptions::default()).unwrap();
+        let second = crawl_directory(root, CrawlOptions::default()).unwrap();
+        assert_eq!(
+            serde_json::to_string(&first).unwrap(),
+            serde_json::to_string(&second).unwrap()
+        );
+        assert_eq!(first.tree_string(), second.tree_string());
+
+        let names: Vec<&str> = first.iter().map(|node| node.name()).skip(1).collect();
+        assert_eq!(
+            names,
+            vec![
+                "alpha.rs",
+                "docs",
+                "readme.md",
+                "src",
+                "a",
+                "inner.rs",
+                "b",
+                "inner.rs",
+                "mid.rs",
+                "zeta.rs"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_path_filter_matches_crawl_rules() {
+        let root = Path::new("/project");
+        let options = CrawlOptions {
+            glob_patterns: vec!["**/*.rs".to_string()],
+            exclude_patterns: vec!["target/**".to_string()],
+            ..Default::default()
+        };
+        let filter = PathFilter::new(root, &options).unwrap();
+
+        assert!(filter.allows_file(&root.join("src/lib.rs")));
+        assert!(!filter.allows_file(&root.join("src/notes.md")));
+        assert!(!filter.allows_file(&root.join("target/debug/build.rs")));
+        assert!(!filter.allows_file(&root.join(".git/hooks/pre-commit.rs")));
+        assert!(!filter.allows_file(Path::new("/elsewhere/lib.rs")));
+    }
 }
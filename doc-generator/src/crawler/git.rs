@@ -0,0 +1,102 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::file::CrawlError;
+
+/// Files under `dir` changed between two revisions, joined onto `dir`
+#[derive(Debug, Default, PartialEq)]
+pub struct ChangedFiles {
+    /// Files added or modified, which need analyzing
+    pub changed: Vec<PathBuf>,
+    /// Files that no longer exist
+    pub deleted: Vec<PathBuf>,
+}
+
+/// Files under `dir` changed between `base` and `HEAD`
+///
+/// Uses the three-dot form (`base...HEAD`) so only changes made since the branch
+/// diverged from `base` are listed. A renamed file is listed as deleted under its old
+/// name and changed under its new one.
+pub fn changed_files(dir: &Path, base: &str) -> Result<ChangedFiles, CrawlError> {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(["diff", "--name-status", "--relative", "--no-renames"])
+        .arg(format!("{}...HEAD", base))
+        .output()?;
+
+    if !output.status.success() {
+        return Err(CrawlError::Git(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    let mut files = ChangedFiles::default();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let Some((status, path)) = line.split_once('\t') else {
+            continue;
+        };
+        match status {
+            "D" => files.deleted.push(dir.join(path)),
+            _ => files.changed.push(dir.join(path)),
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .current_dir(dir)
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn test_changed_files_since_base() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        git(root, &["init", "-q", "-b", "main"]);
+        fs::create_dir(root.join("src")).unwrap();
+        fs::write(root.join("src/lib.rs"), "pub fn a() {}").unwrap();
+        fs::write(root.join("src/old.rs"), "pub fn old() {}").unwrap();
+        fs::write(root.join("README.md"), "# Test").unwrap();
+        git(root, &["add", "."]);
+        git(root, &["commit", "-q", "-m", "base"]);
+
+        git(root, &["checkout", "-q", "-b", "feature"]);
+        fs::write(root.join("src/lib.rs"), "pub fn a() {}\npub fn b() {}").unwrap();
+        fs::write(root.join("src/new.rs"), "pub fn new() {}").unwrap();
+        fs::remove_file(root.join("src/old.rs")).unwrap();
+        git(root, &["add", "-A"]);
+        git(root, &["commit", "-q", "-m", "change"]);
+
+        let mut files = changed_files(root, "main").unwrap();
+        files.changed.sort();
+        assert_eq!(
+            files,
+            ChangedFiles {
+                changed: vec![root.join("src/lib.rs"), root.join("src/new.rs")],
+                deleted: vec![root.join("src/old.rs")],
+            }
+        );
+
+        // Paths are relative to the directory asked about, not the repo root
+        let src = root.join("src");
+        let files = changed_files(&src, "main").unwrap();
+        assert_eq!(files.changed.len(), 2);
+        assert_eq!(files.deleted, vec![src.join("old.rs")]);
+
+        assert!(matches!(
+            changed_files(root, "no-such-branch"),
+            Err(CrawlError::Git(_))
+        ));
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use crate::analysis::summary::{ChildAnalysis, DirectoryAnalysis, FileAnalysis, ProjectAnalysis};
+
+/// In-memory analysis cache keyed by content hash
+///
+/// Files are keyed by a hash of their contents. Directory and project syntheses
+/// are keyed by a fingerprint of every file analysis beneath them, so they are
+/// only re-synthesized when a file they summarize has changed.
+#[derive(Debug, Default)]
+pub struct AnalysisCache {
+    files: HashMap<PathBuf, (u64, FileAnalysis)>,
+    directories: HashMap<PathBuf, (u64, DirectoryAnalysis)>,
+    project: Option<(u64, ProjectAnalysis)>,
+    refreshed: Vec<PathBuf>,
+}
+
+impl AnalysisCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cached analysis for `path` if its contents still hash to `hash`
+    pub fn file(&self, path: &Path, hash: u64) -> Option<FileAnalysis> {
+        self.files
+            .get(path)
+            .filter(|(cached, _)| *cached == hash)
+            .map(|(_, analysis)| analysis.clone())
+    }
+
+    /// Store a fresh file analysis, recording the path as re-analyzed
+    pub fn insert_file(&mut self, path: &Path, hash: u64, analysis: FileAnalysis) {
+        self.refreshed.push(path.to_path_buf());
+        self.files.insert(path.to_path_buf(), (hash, analysis));
+    }
+
+    /// Store an analysis carried over from an earlier run, without marking it as refreshed
+    pub fn restore_file(&mut self, path: &Path, hash: u64, analysis: FileAnalysis) {
+        self.files.insert(path.to_path_buf(), (hash, analysis));
+    }
+
+    pub fn directory(&self, path: &Path, children: &[ChildAnalysis]) -> Option<DirectoryAnalysis> {
+        let fingerprint = self.fingerprint(children)?;
+        self.directories
+            .get(path)
+            .filter(|(cached, _)| *cached == fingerprint)
+            .map(|(_, analysis)| analysis.clone())
+    }
+
+    pub fn insert_directory(
+        &mut self,
+        path: &Path,
+        children: &[ChildAnalysis],
+        analysis: DirectoryAnalysis,
+    ) {
+        if let Some(fingerprint) = self.fingerprint(children) {
+            self.directories
+                .insert(path.to_path_buf(), (fingerprint, analysis));
+        }
+    }
+
+    pub fn project(&self, children: &[ChildAnalysis]) -> Option<ProjectAnalysis> {
+        let fingerprint = self.fingerprint(children)?;
+        self.project
+            .as_ref()
+            .filter(|(cached, _)| *cached == fingerprint)
+            .map(|(_, analysis)| analysis.clone())
+    }
+
+    pub fn insert_project(&mut self, children: &[ChildAnalysis], analysis: ProjectAnalysis) {
+        if let Some(fingerprint) = self.fingerprint(children) {
+            self.project = Some((fingerprint, analysis));
+        }
+    }
+
+    /// Files analyzed (rather than served from the cache) since the last call
+    pub fn take_refreshed(&mut self) -> Vec<PathBuf> {
+        std::mem::take(&mut self.refreshed)
+    }
+
+    /// Hash of the sorted (path, content hash) pairs of every file analysis in
+    /// `children`, independent of crawl order. `None` if any file is uncached.
+    fn fingerprint(&self, children: &[ChildAnalysis]) -> Option<u64> {
+        let mut files: Vec<(&PathBuf, u64)> = children
+            .iter()
+            .filter_map(|child| match child {
+                ChildAnalysis::File(file) => Some(&file.file_path),
+                ChildAnalysis::Directory(_) => None,
+            })
+            .map(|path| self.files.get(path).map(|(hash, _)| (path, *hash)))
+            .collect::<Option<_>>()?;
+        files.sort();
+
+        let mut hasher = DefaultHasher::new();
+        files.hash(&mut hasher);
+        Some(hasher.finish())
+    }
+}
+
+pub fn content_hash(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_analysis(path: &str) -> FileAnalysis {
+        FileAnalysis {
+            file_path: PathBuf::from(path),
+            file_type: "rs".to_string(),
+            summary: "summary".to_string(),
+            external_dependencies: vec![],
+            public_interfaces: vec![],
+            line_count: None,
+            byte_size: None,
+            signatures: vec![],
+        }
+    }
+
+    #[test]
+    fn test_file_cache_keyed_by_content() {
+        let mut cache = AnalysisCache::new();
+        let path = Path::new("src/lib.rs");
+        cache.insert_file(path, content_hash("fn a() {}"), file_analysis("src/lib.rs"));
+
+        assert!(cache.file(path, content_hash("fn a() {}")).is_some());
+        assert!(cache.file(path, content_hash("fn b() {}")).is_none());
+        assert_eq!(cache.take_refreshed(), vec![PathBuf::from("src/lib.rs")]);
+        assert!(cache.take_refreshed().is_empty());
+    }
+
+    #[test]
+    fn test_directory_fingerprint_ignores_order_and_tracks_content() {
+        let mut cache = AnalysisCache::new();
+        cache.insert_file(Path::new("a.rs"), 1, file_analysis("a.rs"));
+        cache.insert_file(Path::new("b.rs"), 2, file_analysis("b.rs"));
+
+        let children = vec![
+            ChildAnalysis::File(file_analysis("a.rs")),
+            ChildAnalysis::File(file_analysis("b.rs")),
+        ];
+        let reversed: Vec<_> = children.iter().rev().cloned().collect();
+        let directory = DirectoryAnalysis {
+            directory_path: PathBuf::from("."),
+            depth_level: 0,
+            summary: "dir".to_string(),
+            child_summaries: vec![],
+            key_components: vec![],
+            external_dependencies: vec![],
+            public_interfaces: vec![],
+        };
+        cache.insert_directory(Path::new("."), &children, directory);
+
+        assert!(cache.directory(Path::new("."), &reversed).is_some());
+
+        cache.insert_file(Path::new("b.rs"), 3, file_analysis("b.rs"));
+        assert!(cache.directory(Path::new("."), &children).is_none());
+    }
+}
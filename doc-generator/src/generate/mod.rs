@@ -1,13 +1,34 @@
-use std::collections::HashMap;
+pub mod cache;
+
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
+use std::io::{self, Write};
 use std::path::{Path, PathBuf};
-use tracing::{debug, error, warn};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::mpsc::Sender;
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tokio_util::sync::CancellationToken;
+use tracing::field::Empty;
+use tracing::{Span, debug, error, instrument, warn};
 
+use crate::analysis::detect::detect_project_type;
+use crate::analysis::license::detect_license;
+use crate::analysis::signatures::extract_signatures;
 use crate::analysis::summary::{
-    AnalysisAudience, AnalysisContext, AnalysisDepth, AnalysisError, ChildAnalysis, FileAnalysis,
-    LlmAnalyser, ProjectAnalysis, ProjectType,
+    AnalysisAudience, AnalysisContext, AnalysisDepth, AnalysisError, ChildAnalysis,
+    DirectoryAnalysis, FileAnalysis, LlmAnalyser, ProjectAnalysis, ProjectType,
+    merge_chunk_analyses,
+};
+use crate::crawler::file::{
+    CrawlError, CrawlOptions, FileNode, PathFilter, crawl_directory, create_file_node,
+    is_binary_file,
 };
-use crate::crawler::file::{CrawlError, CrawlOptions, FileNode, crawl_directory};
+use crate::llm_interface::models::ModelId;
+use cache::{AnalysisCache, content_hash};
 
 #[derive(Debug, Clone)]
 pub struct AnalysisCrawlOptions {
@@ -17,8 +38,38 @@ pub struct AnalysisCrawlOptions {
     pub analysis_context: AnalysisContext,
     /// File extensions to analyze (empty means analyze all text files)
     pub analyzable_extensions: Vec<String>,
+    /// Extensionless file names to analyze (e.g. Makefile, Dockerfile)
+    pub analyzable_filenames: Vec<String>,
+    /// Sniff file contents and skip anything that looks binary
+    pub detect_binary: bool,
     /// Maximum file size to analyze (in bytes)
     pub max_file_size: u64,
+    /// Minimum file size to analyze (in bytes), to skip tiny stub files
+    pub min_file_size: u64,
+    /// Stop dispatching file analyses once this many files have been started
+    pub max_files: Option<usize>,
+    /// Analyze files over `max_file_size` in chunks instead of skipping them
+    pub chunk_large_files: bool,
+    /// Lines repeated at the start of each chunk from the end of the previous one
+    pub chunk_overlap: usize,
+    /// Most chunks a file is split into; only the first ones are analyzed for larger files
+    pub max_chunks: usize,
+    /// Context window of the model analyzing files, in tokens, which sizes the chunks.
+    /// Chunks are at most `max_file_size` when it isn't known.
+    pub context_window: Option<u32>,
+    /// Analyze files that aren't valid UTF-8 by decoding UTF-16 (with a BOM) or replacing
+    /// invalid bytes, instead of skipping them
+    pub lossy_decode: bool,
+    /// Detect the project license from its license file and include it in the overview
+    pub include_license: bool,
+    /// Extract top-level signatures from Rust, Python and TypeScript files into each analysis
+    pub include_signatures: bool,
+    /// Stop dispatching LLM work once this many estimated tokens have been used
+    pub max_total_tokens: Option<u64>,
+    /// Only analyze files modified within this long ago
+    pub max_age: Option<Duration>,
+    /// Only analyze files last modified at least this long ago
+    pub min_age: Option<Duration>,
 }
 
 impl Default for AnalysisCrawlOptions {
@@ -62,9 +113,189 @@ impl Default for AnalysisCrawlOptions {
                 "config".to_string(),
                 "conf".to_string(),
             ],
+            analyzable_filenames: vec![
+                "Makefile".to_string(),
+                "Dockerfile".to_string(),
+                "Containerfile".to_string(),
+                "Justfile".to_string(),
+                "Jenkinsfile".to_string(),
+                "Vagrantfile".to_string(),
+                "Rakefile".to_string(),
+                "Gemfile".to_string(),
+                "Procfile".to_string(),
+            ],
+            detect_binary: true,
             max_file_size: 1024 * 1024, // 1MB
+            min_file_size: 0,
+            max_files: None,
+            chunk_large_files: false,
+            chunk_overlap: 20,
+            max_chunks: 16,
+            context_window: None,
+            lossy_decode: false,
+            include_license: true,
+            include_signatures: false,
+            max_total_tokens: None,
+            max_age: None,
+            min_age: None,
+        }
+    }
+}
+
+/// Resume state kept in the output directory by `AnalysisCrawler::with_resume_state`
+///
+/// One `ChildAnalysis` JSON object per line, appended as each file is analyzed and each
+/// directory synthesized, so an interrupted run keeps everything it finished. When a path
+/// appears more than once the last line wins.
+pub const RESUME_STATE_FILE: &str = ".auto-doc-state.ndjson";
+
+/// One line of the resume state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StateRecord {
+    #[serde(flatten)]
+    analysis: ChildAnalysis,
+    /// The files a directory summary was synthesized from, its direct children
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    files: Vec<PathBuf>,
+}
+
+/// Analyses recorded in `dir`'s resume state, one per path
+///
+/// A missing state file yields nothing. Lines that don't parse, such as one cut short
+/// when the previous run was killed, are skipped.
+fn load_state(dir: &Path) -> Vec<StateRecord> {
+    let Ok(content) = fs::read_to_string(dir.join(RESUME_STATE_FILE)) else {
+        return vec![];
+    };
+
+    let mut latest: HashMap<PathBuf, usize> = HashMap::new();
+    let mut records: Vec<Option<StateRecord>> = Vec::new();
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        let record: StateRecord = match serde_json::from_str(line) {
+            Ok(record) => record,
+            Err(e) => {
+                warn!("Skipping unreadable resume state entry: {}", e);
+                continue;
+            }
+        };
+        let path = match &record.analysis {
+            ChildAnalysis::File(file) => file.file_path.clone(),
+            ChildAnalysis::Directory(dir) => dir.directory_path.clone(),
+        };
+        if let Some(previous) = latest.insert(path, records.len()) {
+            records[previous] = None;
+        }
+        records.push(Some(record));
+    }
+    records.into_iter().flatten().collect()
+}
+
+/// Analyses saved in `dir`'s resume state by an earlier run, one per path
+///
+/// As with the resume state itself, a missing file yields nothing and unreadable lines
+/// are skipped.
+pub fn load_prior_analysis(dir: &Path) -> Vec<ChildAnalysis> {
+    load_state(dir)
+        .into_iter()
+        .map(|record| record.analysis)
+        .collect()
+}
+
+/// Whether a saved file analysis still describes the file on disk
+///
+/// The file must not have been modified since the state was last written, and its size
+/// must match the one recorded with the analysis.
+fn is_fresh(file: &FileAnalysis, content: &str, saved_at: SystemTime) -> bool {
+    let unmodified = fs::metadata(&file.file_path)
+        .and_then(|metadata| metadata.modified())
+        .is_ok_and(|modified| modified <= saved_at);
+    unmodified && file.byte_size == Some(content.len() as u64)
+}
+
+/// Read a file as text, or `None` if it is unreadable or can't be decoded
+///
+/// Strict UTF-8 is tried first. With `lossy` set, UTF-16 files with a byte order mark are
+/// decoded as such and anything else has its invalid sequences replaced.
+fn read_text(path: &Path, lossy: bool) -> Option<String> {
+    let bytes = match String::from_utf8(fs::read(path).ok()?) {
+        Ok(text) => return Some(text),
+        Err(e) if lossy => e.into_bytes(),
+        Err(_) => return None,
+    };
+
+    let text = match bytes.as_slice() {
+        [0xFF, 0xFE, rest @ ..] => decode_utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => decode_utf16(rest, u16::from_be_bytes),
+        _ => String::from_utf8_lossy(&bytes).into_owned(),
+    };
+    Some(text)
+}
+
+fn decode_utf16(bytes: &[u8], from_bytes: fn([u8; 2]) -> u16) -> String {
+    let units = bytes
+        .chunks_exact(2)
+        .map(|pair| from_bytes([pair[0], pair[1]]));
+    char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+/// A chunk fills at most 1/n of the model's context window, leaving room for the prompt
+/// and the analysis written back
+const CHUNK_CONTEXT_DIVISOR: usize = 2;
+
+/// Rough bytes per token, matching `estimate_tokens`
+const BYTES_PER_TOKEN: usize = 4;
+
+/// Largest chunk that fits `context_window` tokens alongside the prompt and response, or
+/// `max_file_size` when the window isn't known
+fn chunk_size(options: &AnalysisCrawlOptions) -> usize {
+    match options.context_window {
+        Some(window) => window as usize / CHUNK_CONTEXT_DIVISOR * BYTES_PER_TOKEN,
+        None => options.max_file_size as usize,
+    }
+}
+
+/// Split `content` on line boundaries into chunks of at most `max_bytes`, each starting
+/// with the last `overlap` lines of the previous chunk. A single line longer than
+/// `max_bytes` becomes a chunk of its own.
+fn chunk_content(content: &str, max_bytes: usize, overlap: usize) -> Vec<&str> {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .filter(|&start| start < content.len())
+        .collect();
+    let line_end = |line: usize| line_starts.get(line).copied().unwrap_or(content.len());
+
+    let mut chunks = Vec::new();
+    let mut first = 0;
+    while first < line_starts.len() {
+        let start = line_starts[first];
+        let mut next = first + 1;
+        while next < line_starts.len() && line_end(next + 1) - start <= max_bytes {
+            next += 1;
         }
+        chunks.push(&content[start..line_end(next)]);
+        if next == line_starts.len() {
+            break;
+        }
+        first = next.saturating_sub(overlap).max(first + 1);
     }
+    chunks
+}
+
+/// Progress events emitted while a project is being analyzed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnalysisEvent {
+    /// A file has been dispatched for analysis
+    FileStarted(PathBuf),
+    /// A file analysis finished (including files skipped as unreadable)
+    FileCompleted(PathBuf),
+    /// A file analysis returned an error
+    FileFailed(PathBuf),
+    /// A directory summary has been synthesized from its children
+    DirectorySynthesized(PathBuf),
+    /// A file or directory analysis is ready, so it can be written out before the run ends
+    Analyzed(ChildAnalysis),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -77,35 +308,264 @@ pub enum AnalysisCrawlError {
     Io(#[from] std::io::Error),
     #[error("Join error: {0}")]
     Join(#[from] tokio::task::JoinError),
+    #[error("Analysis was cancelled")]
+    Cancelled,
+    #[error("File limit reached")]
+    FileLimitReached,
+}
+
+/// Everything a project analysis produced, including the analyses that failed
+#[derive(Debug)]
+pub struct ProjectRun {
+    /// The project summary, `None` for a single file or a run cut short
+    pub project: Option<ProjectAnalysis>,
+    pub children: Vec<ChildAnalysis>,
+    /// Files and directories whose analysis failed, which have no entry in `children`
+    pub failures: Vec<(PathBuf, AnalysisCrawlError)>,
 }
 
+/// Default number of LLM requests a crawler keeps in flight at once
+pub const DEFAULT_MAX_CONCURRENCY: usize = 4;
+
 pub struct AnalysisCrawler<A: LlmAnalyser + Clone + 'static> {
     analyser: A,
+    cache: Option<Mutex<AnalysisCache>>,
+    /// Resume state every fresh analysis is appended to
+    state: Option<Mutex<fs::File>>,
+    /// Bounds in-flight LLM requests across concurrently analyzed subtrees
+    semaphore: Semaphore,
+    /// Counts accumulated over every analysis this crawler has run
+    stats: Mutex<RunStats>,
+    /// Stops new work and abandons in-flight LLM requests once cancelled
+    cancel: CancellationToken,
+    /// Files sent to the analyser this run, checked against `max_files`
+    files_started: AtomicUsize,
+    /// Analyses that failed since the last `take_failures`
+    failures: Mutex<Vec<(PathBuf, AnalysisCrawlError)>>,
+    /// Directories holding files the run's `only_patterns` leave out, which aren't synthesized
+    partially_covered: Mutex<HashSet<PathBuf>>,
 }
 
 impl<A: LlmAnalyser> AnalysisCrawler<A> {
     pub fn new(analyser: A) -> Self {
-        Self { analyser }
+        Self {
+            analyser,
+            cache: None,
+            state: None,
+            semaphore: Semaphore::new(DEFAULT_MAX_CONCURRENCY),
+            stats: Mutex::new(RunStats::default()),
+            cancel: CancellationToken::new(),
+            files_started: AtomicUsize::new(0),
+            failures: Mutex::new(Vec::new()),
+            partially_covered: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Stop analyzing when `cancel` is cancelled, keeping the analyses finished so far
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Limit how many LLM requests may be in flight at once (at least one)
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.semaphore = Semaphore::new(max_concurrency.max(1));
+        self
+    }
+
+    async fn permit(&self) -> SemaphorePermit<'_> {
+        self.semaphore
+            .acquire()
+            .await
+            .expect("crawler semaphore is never closed")
+    }
+
+    /// Reuse analyses across runs of this crawler while file contents are unchanged
+    pub fn with_cache(mut self) -> Self {
+        self.cache = Some(Mutex::new(AnalysisCache::new()));
+        self
+    }
+
+    /// Record every analysis in `output_dir`'s resume state so an interrupted run can be resumed
+    ///
+    /// With `resume` set, analyses from the previous run's state are reused for files that
+    /// haven't changed since, and directories whose direct files are all unchanged aren't
+    /// re-synthesized. Otherwise the state starts empty. Enables the cache.
+    pub fn with_resume_state(mut self, output_dir: &Path, resume: bool) -> io::Result<Self> {
+        if self.cache.is_none() {
+            self = self.with_cache();
+        }
+
+        let state_path = output_dir.join(RESUME_STATE_FILE);
+        let mut restored = Vec::new();
+        if resume && let Ok(saved_at) = fs::metadata(&state_path).and_then(|m| m.modified()) {
+            let prior = load_state(output_dir);
+            let mut cache = self.cache().expect("cache enabled above");
+
+            let mut fresh_files = BTreeMap::new();
+            for record in &prior {
+                if let ChildAnalysis::File(file) = &record.analysis
+                    && let Ok(content) = fs::read_to_string(&file.file_path)
+                    && is_fresh(file, &content, saved_at)
+                {
+                    cache.restore_file(&file.file_path, content_hash(&content), file.clone());
+                    fresh_files.insert(file.file_path.clone(), record.clone());
+                }
+            }
+            for record in prior {
+                if let ChildAnalysis::Directory(dir) = &record.analysis {
+                    // Only a summary of exactly these files, all unchanged, can be reused
+                    let children: Option<Vec<ChildAnalysis>> = record
+                        .files
+                        .iter()
+                        .map(|path| fresh_files.get(path).map(|file| file.analysis.clone()))
+                        .collect();
+                    if let Some(children) = children
+                        && !children.is_empty()
+                    {
+                        cache.insert_directory(&dir.directory_path, &children, dir.clone());
+                        restored.push(record);
+                    }
+                }
+            }
+            debug!(
+                "Resuming with {} file and {} directory analyses",
+                fresh_files.len(),
+                restored.len()
+            );
+            restored.splice(0..0, fresh_files.into_values());
+        }
+
+        fs::create_dir_all(output_dir)?;
+        let mut state = fs::File::create(&state_path)?;
+        for record in &restored {
+            writeln!(state, "{}", serde_json::to_string(record)?)?;
+        }
+        self.state = Some(Mutex::new(state));
+        Ok(self)
     }
 
-    /// Crawl and analyze a project directory
+    /// Append a freshly produced analysis to the resume state, if one is kept
+    ///
+    /// `files` lists the direct child files a directory summary was synthesized from.
+    fn record(&self, analysis: ChildAnalysis, files: Vec<PathBuf>) {
+        let Some(state) = &self.state else {
+            return;
+        };
+        let mut state = state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let written = serde_json::to_string(&StateRecord { analysis, files })
+            .map_err(io::Error::from)
+            .and_then(|line| writeln!(state, "{}", line));
+        if let Err(e) = written {
+            warn!("Failed to update resume state: {}", e);
+        }
+    }
+
+    /// Files analyzed by the LLM (rather than served from the cache) since the last call
+    pub fn take_refreshed(&self) -> Vec<PathBuf> {
+        self.cache()
+            .map(|mut cache| cache.take_refreshed())
+            .unwrap_or_default()
+    }
+
+    /// Calls, failures and token usage accumulated so far
+    pub fn stats(&self) -> RunStats {
+        RunStats {
+            retries: self.analyser.retries(),
+            tokens: self.analyser.tokens_used(),
+            ..self.stats_mut().clone()
+        }
+    }
+
+    /// Files and directories whose analysis failed since the last call
+    pub fn take_failures(&self) -> Vec<(PathBuf, AnalysisCrawlError)> {
+        std::mem::take(
+            &mut *self
+                .failures
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        )
+    }
+
+    /// Note a failed analysis for `take_failures`, unless it was only cancelled
+    fn record_failure(&self, path: &Path, error: AnalysisCrawlError) {
+        if matches!(
+            error,
+            AnalysisCrawlError::Cancelled | AnalysisCrawlError::FileLimitReached
+        ) {
+            return;
+        }
+        self.failures
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push((path.to_path_buf(), error));
+    }
+
+    fn stats_mut(&self) -> MutexGuard<'_, RunStats> {
+        self.stats
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn cache(&self) -> Option<MutexGuard<'_, AnalysisCache>> {
+        self.cache.as_ref().map(|cache| {
+            cache
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+        })
+    }
+
+    /// Crawl and analyze a project directory, optionally reporting progress on `events`
+    ///
+    /// If `root_path` is a single file, only that file is analyzed and no project analysis
+    /// is returned. Files and directories that fail are left out of the analyses and listed
+    /// in the run's `failures`.
     pub async fn analyze_project<P: AsRef<Path>>(
         &self,
         root_path: P,
-        options: AnalysisCrawlOptions,
-    ) -> Result<(Option<ProjectAnalysis>, Vec<ChildAnalysis>), AnalysisCrawlError> {
+        mut options: AnalysisCrawlOptions,
+        events: Option<Sender<AnalysisEvent>>,
+    ) -> Result<ProjectRun, AnalysisCrawlError> {
         let root_path = root_path.as_ref();
         debug!("Starting analysis of: {}", root_path.display());
+        // `max_files` applies per run, watch mode reuses the crawler across runs
+        self.files_started.store(0, Ordering::Relaxed);
+
+        if options.analysis_context.project_type == ProjectType::Unknown {
+            options.analysis_context.project_type = detect_project_type(root_path);
+            debug!(
+                "Detected project type: {:?}",
+                options.analysis_context.project_type
+            );
+        }
 
         // First, crawl the directory structure
         let file_tree = crawl_directory(root_path, options.crawl_options.clone())?;
+        let partially_covered = self.partially_covered(root_path, &file_tree, &options)?;
+        let project_covered = !partially_covered.contains(root_path);
+        *self
+            .partially_covered
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = partially_covered;
 
         // Print what we found during crawling
         debug!("File tree structure: {}", file_tree.tree_string());
 
+        // A lone file has no directories or project to synthesize
+        if file_tree.is_file() {
+            let children = self
+                .analyze_file_tree(&file_tree, &options, events.as_ref())
+                .await?;
+            return Ok(self.finish_run(None, children));
+        }
+
         // Then analyze the structure
         debug!("Starting analyze_file_tree...");
-        let child_analyses = self.analyze_file_tree(&file_tree, &options).await?;
+        let child_analyses = self
+            .analyze_file_tree(&file_tree, &options, events.as_ref())
+            .await?;
 
         // Debug what analyze_file_tree returned
         debug!("analyze_file_tree returned {} items:", child_analyses.len());
@@ -125,20 +585,103 @@ impl<A: LlmAnalyser> AnalysisCrawler<A> {
             }
         }
 
+        if self.cancel.is_cancelled() {
+            warn!(
+                "Analysis cancelled, returning partial results for {} items",
+                child_analyses.len()
+            );
+            return Ok(self.finish_run(None, child_analyses));
+        }
+        if self.budget_exceeded(&options) {
+            warn!(
+                "Token budget of {} exceeded ({} used), returning partial results for {} items",
+                options.max_total_tokens.unwrap_or_default(),
+                self.analyser.tokens_used(),
+                child_analyses.len()
+            );
+            return Ok(self.finish_run(None, child_analyses));
+        }
+        if !project_covered {
+            debug!("Only part of the project was analyzed, skipping the project analysis");
+            return Ok(self.finish_run(None, child_analyses));
+        }
+
         // Finally, synthesize into project analysis
-        let project_analysis = match self
-            .analyser
-            .analyze_project(root_path, &child_analyses, &options.analysis_context)
+        let mut project_analysis = match self
+            .synthesize_project(root_path, &child_analyses, &options)
             .await
         {
-            Ok(proj) => Some(proj),
+            Ok(proj) => proj,
             Err(e) => {
-                error!("Error with Project analysis {}", e.to_string());
-                None
+                error!(
+                    "Error with Project analysis {}, falling back to a local overview",
+                    e.to_string()
+                );
+                ProjectAnalysis::from_children(&child_analyses)
             }
         };
+        if options.include_license {
+            project_analysis.license = detect_license(root_path);
+        }
+
+        Ok(self.finish_run(Some(project_analysis), child_analyses))
+    }
 
-        Ok((project_analysis, child_analyses))
+    /// Directories under `root_path`, itself included, with files that a crawl without the
+    /// `only_patterns` would reach but `file_tree` leaves out
+    ///
+    /// Summaries of these would only cover some of their files, so `--only` runs don't
+    /// synthesize them.
+    fn partially_covered(
+        &self,
+        root_path: &Path,
+        file_tree: &FileNode,
+        options: &AnalysisCrawlOptions,
+    ) -> Result<HashSet<PathBuf>, AnalysisCrawlError> {
+        let mut partially_covered = HashSet::new();
+        if options.crawl_options.only_patterns.is_empty() {
+            return Ok(partially_covered);
+        }
+
+        let full_tree = crawl_directory(
+            root_path,
+            CrawlOptions {
+                only_patterns: vec![],
+                ..options.crawl_options.clone()
+            },
+        )?;
+        let covered: HashSet<&Path> = file_tree
+            .iter()
+            .filter(|node| node.is_file())
+            .map(|node| node.path())
+            .collect();
+        for node in full_tree.iter() {
+            if node.is_file() && !covered.contains(node.path()) {
+                for directory in node
+                    .path()
+                    .ancestors()
+                    .skip(1)
+                    .take_while(|directory| directory.starts_with(root_path))
+                {
+                    if !partially_covered.insert(directory.to_path_buf()) {
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(partially_covered)
+    }
+
+    fn finish_run(
+        &self,
+        project: Option<ProjectAnalysis>,
+        children: Vec<ChildAnalysis>,
+    ) -> ProjectRun {
+        ProjectRun {
+            project,
+            children,
+            failures: self.take_failures(),
+        }
     }
 
     /// Analyze a file tree node and all its children
@@ -146,6 +689,7 @@ impl<A: LlmAnalyser> AnalysisCrawler<A> {
         &'a self,
         node: &'a FileNode,
         options: &'a AnalysisCrawlOptions,
+        events: Option<&'a Sender<AnalysisEvent>>,
     ) -> std::pin::Pin<
         Box<
             dyn std::future::Future<Output = Result<Vec<ChildAnalysis>, AnalysisCrawlError>>
@@ -157,108 +701,455 @@ impl<A: LlmAnalyser> AnalysisCrawler<A> {
             match node {
                 FileNode::File { .. } => {
                     // Single file analysis
-                    if let Some(analysis) = self.analyze_single_file(node, options).await? {
-                        Ok(vec![ChildAnalysis::File(analysis)])
+                    emit(
+                        events,
+                        AnalysisEvent::FileStarted(node.path().to_path_buf()),
+                    )
+                    .await;
+                    let result = match self.analyze_single_file(node, options).await {
+                        Err(AnalysisCrawlError::FileLimitReached) => return Ok(vec![]),
+                        result => result,
+                    };
+                    let event = match &result {
+                        Ok(_) => AnalysisEvent::FileCompleted(node.path().to_path_buf()),
+                        Err(_) => AnalysisEvent::FileFailed(node.path().to_path_buf()),
+                    };
+                    emit(events, event).await;
+
+                    if let Some(analysis) = result? {
+                        let analysis = ChildAnalysis::File(analysis);
+                        emit(events, AnalysisEvent::Analyzed(analysis.clone())).await;
+                        Ok(vec![analysis])
                     } else {
                         Ok(vec![])
                     }
                 }
                 FileNode::Directory { children, .. } => {
                     let mut child_analyses = Vec::new();
+                    let mut subdirectories = Vec::new();
+
+                    // Largest first so the likely-important files report early, then by name
+                    let mut children: Vec<&FileNode> = children.values().collect();
+                    children.sort_by(|a, b| {
+                        b.size().cmp(&a.size()).then_with(|| a.name().cmp(b.name()))
+                    });
 
                     // Process each immediate child
-                    for child in children.values() {
+                    for child in children {
+                        if self.should_stop(options) {
+                            break;
+                        }
                         match child {
                             FileNode::File { .. } => {
                                 if self.should_analyze_file(child, options) {
+                                    let child_path = child.path().to_path_buf();
+                                    emit(events, AnalysisEvent::FileStarted(child_path.clone()))
+                                        .await;
                                     match self.analyze_single_file(child, options).await {
                                         Ok(Some(file_analysis)) => {
-                                            child_analyses.push(ChildAnalysis::File(file_analysis));
+                                            emit(events, AnalysisEvent::FileCompleted(child_path))
+                                                .await;
+                                            let analysis = ChildAnalysis::File(file_analysis);
+                                            emit(events, AnalysisEvent::Analyzed(analysis.clone()))
+                                                .await;
+                                            child_analyses.push(analysis);
                                         }
                                         Ok(None) => {
+                                            emit(events, AnalysisEvent::FileCompleted(child_path))
+                                                .await;
                                             warn!("Empty analysis for {}", child.name())
                                         }
+                                        Err(AnalysisCrawlError::Cancelled) => break,
+                                        // Later siblings may still be served from the cache
+                                        Err(AnalysisCrawlError::FileLimitReached) => continue,
                                         Err(e) => {
+                                            emit(
+                                                events,
+                                                AnalysisEvent::FileFailed(child_path.clone()),
+                                            )
+                                            .await;
                                             error!(
                                                 "Analysis Failed for {} with error: {}",
                                                 child.name(),
                                                 e.to_string()
-                                            )
-                                        }
-                                    }
-                                }
-                            }
-                            FileNode::Directory { .. } => {
-                                // Recursively analyze subdirectory (boxed to avoid infinite size)
-                                let sub_analyses = self.analyze_file_tree(child, options).await?;
-
-                                if !sub_analyses.is_empty() {
-                                    child_analyses.extend(sub_analyses.clone());
-                                    // Create directory analysis for this subdirectory
-                                    match self
-                                        .analyser
-                                        .analyze_directory(
-                                            child.path(),
-                                            &sub_analyses,
-                                            &options.analysis_context,
-                                        )
-                                        .await
-                                    {
-                                        Ok(dir_analysis) => {
-                                            child_analyses
-                                                .push(ChildAnalysis::Directory(dir_analysis));
+                                            );
+                                            self.record_failure(&child_path, e);
                                         }
-                                        Err(e) => error!(
-                                            "Error with directory Analysis: {}, error: {}",
-                                            child.name(),
-                                            e.to_string()
-                                        ),
                                     }
                                 }
                             }
+                            FileNode::Directory { .. } => subdirectories.push(child),
                         }
                     }
 
+                    // Sibling subtrees are independent, so analyze them concurrently
+                    let subtree_analyses = join_all(
+                        subdirectories
+                            .into_iter()
+                            .map(|child| self.analyze_subdirectory(child, options, events)),
+                    )
+                    .await;
+                    child_analyses.extend(subtree_analyses.into_iter().flatten());
+
                     Ok(child_analyses)
                 }
             }
         })
     }
 
-    async fn analyze_single_file_static(
-        analyser: &A,
+    /// Analyze a subdirectory and synthesize its summary
+    ///
+    /// Failures are recorded and yield no analyses so they don't abort sibling subtrees.
+    async fn analyze_subdirectory(
+        &self,
+        node: &FileNode,
+        options: &AnalysisCrawlOptions,
+        events: Option<&Sender<AnalysisEvent>>,
+    ) -> Vec<ChildAnalysis> {
+        let mut sub_analyses = match self.analyze_file_tree(node, options, events).await {
+            Ok(sub_analyses) => sub_analyses,
+            Err(e) => {
+                error!(
+                    "Analysis failed for directory {} with error: {}",
+                    node.name(),
+                    e.to_string()
+                );
+                self.record_failure(node.path(), e);
+                return vec![];
+            }
+        };
+
+        if sub_analyses.is_empty() || self.should_stop(options) {
+            return sub_analyses;
+        }
+        if self
+            .partially_covered
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .contains(node.path())
+        {
+            debug!(
+                "Skipping synthesis of {}, only some of its files were analyzed",
+                node.path().display()
+            );
+            return sub_analyses;
+        }
+
+        match self
+            .synthesize_directory(node.path(), &sub_analyses, options)
+            .await
+        {
+            Ok(dir_analysis) => {
+                emit(
+                    events,
+                    AnalysisEvent::DirectorySynthesized(node.path().to_path_buf()),
+                )
+                .await;
+                let analysis = ChildAnalysis::Directory(dir_analysis);
+                emit(events, AnalysisEvent::Analyzed(analysis.clone())).await;
+                sub_analyses.push(analysis);
+            }
+            Err(e) => {
+                error!(
+                    "Error with directory Analysis: {}, error: {}",
+                    node.name(),
+                    e.to_string()
+                );
+                // Cancellation surfaces as an analysis error here, so check the token
+                if !self.cancel.is_cancelled() {
+                    self.record_failure(node.path(), e.into());
+                }
+            }
+        }
+
+        sub_analyses
+    }
+
+    #[instrument(
+        name = "analyze_file",
+        skip_all,
+        fields(path = %file_node.path().display(), elapsed_ms = Empty, tokens = Empty)
+    )]
+    async fn analyze_single_file(
+        &self,
         file_node: &FileNode,
         options: &AnalysisCrawlOptions,
     ) -> Result<Option<FileAnalysis>, AnalysisCrawlError> {
-        if let FileNode::File { path, size, .. } = file_node {
-            // Check file size limit
-            if *size > options.max_file_size {
-                return Ok(None);
+        let FileNode::File { path, size, .. } = file_node else {
+            return Ok(None);
+        };
+        // Check file size limit
+        if *size > options.max_file_size && !options.chunk_large_files {
+            return Ok(None);
+        }
+        let Some(content) = read_text(path, options.lossy_decode) else {
+            return Ok(None); // Skip binary or unreadable files
+        };
+
+        let usage = self.start_usage();
+        let result = self.analyze_content(path, &content, options).await;
+        self.record_usage(usage);
+        result.map(Some)
+    }
+
+    /// Note the time and token total for `record_usage`, unless no subscriber wants the span
+    fn start_usage(&self) -> Option<(Instant, u64)> {
+        (!Span::current().is_disabled()).then(|| (Instant::now(), self.analyser.tokens_used()))
+    }
+
+    /// Tag the current span with the time and tokens spent since `start_usage`
+    ///
+    /// Tokens are read from the analyser's running total, so they include any requests
+    /// that overlapped this one.
+    fn record_usage(&self, usage: Option<(Instant, u64)>) {
+        let Some((started, tokens_before)) = usage else {
+            return;
+        };
+        let span = Span::current();
+        span.record("elapsed_ms", started.elapsed().as_millis() as u64);
+        span.record(
+            "tokens",
+            self.analyser.tokens_used().saturating_sub(tokens_before),
+        );
+        debug!("Finished");
+    }
+
+    /// Analyze just the given files under `root`, e.g. the files changed in a PR
+    ///
+    /// Paths that no longer exist, fall outside the crawl filters or wouldn't be
+    /// analyzed in a full run are skipped. Files whose analysis fails are left out and
+    /// can be collected with `take_failures`.
+    pub async fn analyze_files(
+        &self,
+        root: impl AsRef<Path>,
+        paths: &[PathBuf],
+        options: &AnalysisCrawlOptions,
+    ) -> Result<Vec<FileAnalysis>, AnalysisCrawlError> {
+        let filter = PathFilter::new(root.as_ref(), &options.crawl_options)?;
+        let nodes: Vec<FileNode> = paths
+            .iter()
+            .filter(|path| path.is_file() && filter.allows_file(path))
+            .filter_map(|path| create_file_node(path).ok())
+            .filter(|node| self.should_analyze_file(node, options))
+            .collect();
+
+        let results = join_all(
+            nodes
+                .iter()
+                .map(|node| self.analyze_single_file(node, options)),
+        )
+        .await;
+
+        let mut analyses = Vec::new();
+        for (node, result) in nodes.iter().zip(results) {
+            match result {
+                Ok(Some(analysis)) => analyses.push(analysis),
+                Ok(None) => warn!("Empty analysis for {}", node.name()),
+                Err(AnalysisCrawlError::FileLimitReached) => {}
+                Err(e) => {
+                    error!("Analysis Failed for {} with error: {}", node.name(), e);
+                    self.record_failure(node.path(), e);
+                }
             }
+        }
+        Ok(analyses)
+    }
 
-            // Read file content
-            let content = match fs::read_to_string(path) {
-                Ok(content) => content,
-                Err(_) => return Ok(None), // Skip binary or unreadable files
-            };
+    /// Analyze content that is already in memory, without touching the filesystem
+    ///
+    /// `virtual_path` only has to identify the file (e.g. a path inside a commit or PR),
+    /// it does not need to exist on disk. Returns `FileLimitReached` if the content isn't
+    /// cached and `max_files` have already been analyzed.
+    pub async fn analyze_content(
+        &self,
+        virtual_path: &Path,
+        content: &str,
+        options: &AnalysisCrawlOptions,
+    ) -> Result<FileAnalysis, AnalysisCrawlError> {
+        let _permit = self.permit().await;
+        let hash = content_hash(content);
+        if let Some(analysis) = self
+            .cache()
+            .and_then(|cache| cache.file(virtual_path, hash))
+        {
+            debug!("Reusing cached analysis for {}", virtual_path.display());
+            self.stats_mut().files_reused += 1;
+            return Ok(analysis);
+        }
+        // Only files sent to the analyser count towards `max_files`
+        if !self.claim_file_slot(options) {
+            return Err(AnalysisCrawlError::FileLimitReached);
+        }
 
-            // Analyze with LLM
-            let analysis = analyser
-                .analyze_file(path, &content, &options.analysis_context)
-                .await?;
+        let Some(result) = self
+            .cancel
+            .run_until_cancelled(self.analyze_uncached(virtual_path, content, options))
+            .await
+        else {
+            return Err(AnalysisCrawlError::Cancelled);
+        };
+        let result = result.inspect_err(|_| self.stats_mut().files_failed += 1);
+        let mut analysis = result?.with_metrics(content);
+        self.stats_mut().files_analyzed += 1;
+        if options.include_signatures {
+            analysis.signatures = extract_signatures(virtual_path, content);
+        }
+        if let Some(mut cache) = self.cache() {
+            cache.insert_file(virtual_path, hash, analysis.clone());
+        }
+        self.record(ChildAnalysis::File(analysis.clone()), vec![]);
+        Ok(analysis)
+    }
 
-            Ok(Some(analysis))
+    /// Send `content` to the analyser, in chunks if it is too large for one request
+    async fn analyze_uncached(
+        &self,
+        virtual_path: &Path,
+        content: &str,
+        options: &AnalysisCrawlOptions,
+    ) -> Result<FileAnalysis, AnalysisError> {
+        if options.chunk_large_files && content.len() as u64 > options.max_file_size {
+            let mut chunks = chunk_content(content, chunk_size(options), options.chunk_overlap);
+            let max_chunks = options.max_chunks.max(1);
+            if chunks.len() > max_chunks {
+                warn!(
+                    "{} needs {} chunks, only analyzing the first {}",
+                    virtual_path.display(),
+                    chunks.len(),
+                    max_chunks
+                );
+                chunks.truncate(max_chunks);
+            }
+            debug!(
+                "Analyzing {} in {} chunks",
+                virtual_path.display(),
+                chunks.len()
+            );
+            let mut parts = Vec::with_capacity(chunks.len());
+            for chunk in chunks {
+                self.stats_mut().llm_calls += 1;
+                parts.push(
+                    self.analyser
+                        .analyze_file(virtual_path, chunk, &options.analysis_context)
+                        .await?,
+                );
+            }
+            Ok(merge_chunk_analyses(parts))
         } else {
-            Ok(None)
+            self.stats_mut().llm_calls += 1;
+            self.analyser
+                .analyze_file(virtual_path, content, &options.analysis_context)
+                .await
         }
     }
 
-    async fn analyze_single_file(
+    /// Synthesize a directory summary, reusing the cached one if its files are unchanged
+    #[instrument(
+        skip_all,
+        fields(path = %path.display(), elapsed_ms = Empty, tokens = Empty)
+    )]
+    async fn synthesize_directory(
         &self,
-        file_node: &FileNode,
+        path: &Path,
+        children: &[ChildAnalysis],
         options: &AnalysisCrawlOptions,
-    ) -> Result<Option<FileAnalysis>, AnalysisCrawlError> {
-        Self::analyze_single_file_static(&self.analyser, file_node, options).await
+    ) -> Result<DirectoryAnalysis, AnalysisError> {
+        if let Some(analysis) = self
+            .cache()
+            .and_then(|cache| cache.directory(path, children))
+        {
+            return Ok(analysis);
+        }
+
+        let _permit = self.permit().await;
+        let usage = self.start_usage();
+        self.stats_mut().llm_calls += 1;
+        let Some(result) = self
+            .cancel
+            .run_until_cancelled(self.analyser.analyze_directory(
+                path,
+                children,
+                &options.analysis_context,
+            ))
+            .await
+        else {
+            return Err(AnalysisError::LlmError(
+                AnalysisCrawlError::Cancelled.to_string(),
+            ));
+        };
+        self.record_usage(usage);
+        let analysis = result.inspect_err(|_| self.stats_mut().directories_failed += 1)?;
+        self.stats_mut().directories_synthesized += 1;
+        if let Some(mut cache) = self.cache() {
+            cache.insert_directory(path, children, analysis.clone());
+        }
+        let files = children
+            .iter()
+            .filter_map(|child| match child {
+                ChildAnalysis::File(file) => Some(file.file_path.clone()),
+                ChildAnalysis::Directory(_) => None,
+            })
+            .collect();
+        self.record(ChildAnalysis::Directory(analysis.clone()), files);
+        Ok(analysis)
+    }
+
+    /// Synthesize the project summary, reusing the cached one if no file has changed
+    #[instrument(
+        skip_all,
+        fields(path = %root_path.display(), elapsed_ms = Empty, tokens = Empty)
+    )]
+    async fn synthesize_project(
+        &self,
+        root_path: &Path,
+        children: &[ChildAnalysis],
+        options: &AnalysisCrawlOptions,
+    ) -> Result<ProjectAnalysis, AnalysisError> {
+        if let Some(analysis) = self.cache().and_then(|cache| cache.project(children)) {
+            return Ok(analysis);
+        }
+
+        let _permit = self.permit().await;
+        let usage = self.start_usage();
+        self.stats_mut().llm_calls += 1;
+        let result = self
+            .analyser
+            .analyze_project(root_path, children, &options.analysis_context)
+            .await;
+        self.record_usage(usage);
+        let analysis = result.inspect_err(|_| self.stats_mut().project_failed = true)?;
+        if let Some(mut cache) = self.cache() {
+            cache.insert_project(children, analysis.clone());
+        }
+        Ok(analysis)
+    }
+
+    /// Whether no more analyses should be started, due to cancellation or the token budget
+    fn should_stop(&self, options: &AnalysisCrawlOptions) -> bool {
+        self.cancel.is_cancelled() || self.budget_exceeded(options)
+    }
+
+    /// Count a file about to be sent to the analyser, or return false once `max_files` files
+    /// have been sent this run
+    fn claim_file_slot(&self, options: &AnalysisCrawlOptions) -> bool {
+        let Some(max_files) = options.max_files else {
+            return true;
+        };
+        let started = self.files_started.fetch_add(1, Ordering::Relaxed);
+        if started == max_files {
+            warn!(
+                "File limit of {} reached, skipping the remaining uncached files",
+                max_files
+            );
+        }
+        started < max_files
+    }
+
+    /// Whether the estimated token usage has gone over the configured budget
+    fn budget_exceeded(&self, options: &AnalysisCrawlOptions) -> bool {
+        options
+            .max_total_tokens
+            .is_some_and(|max| self.analyser.tokens_used() > max)
     }
 
     fn should_analyze_file(&self, file_node: &FileNode, options: &AnalysisCrawlOptions) -> bool {
@@ -268,31 +1159,55 @@ impl<A: LlmAnalyser> AnalysisCrawler<A> {
     fn should_analyze_file_static(file_node: &FileNode, options: &AnalysisCrawlOptions) -> bool {
         match file_node {
             FileNode::File {
-                extension, size, ..
+                name,
+                path,
+                extension,
+                size,
+                modified,
             } => {
                 // Check size limit
-                if *size > options.max_file_size {
+                if *size > options.max_file_size && !options.chunk_large_files {
                     return false;
                 }
-                if *size == 0 {
+                if *size == 0 || *size < options.min_file_size {
                     return false;
                 }
-                // If no extensions specified, analyze all files
-                if options.analyzable_extensions.is_empty() {
-                    return true;
+                if !Self::within_age_window(*modified, options) {
+                    return false;
                 }
 
-                // Check if extension matches
-                if let Some(ext) = extension {
-                    options.analyzable_extensions.contains(ext)
-                } else {
-                    false // Skip files without extensions unless explicitly included
+                // If no extensions specified, analyze all files
+                let allowed = options.analyzable_extensions.is_empty()
+                    || options.analyzable_filenames.contains(name)
+                    || extension
+                        .as_ref()
+                        .is_some_and(|ext| options.analyzable_extensions.contains(ext));
+                if !allowed {
+                    return false; // Skip files without extensions unless explicitly included
                 }
+
+                !(options.detect_binary && is_binary_file(path))
             }
             FileNode::Directory { .. } => true, // Always process directories
         }
     }
 
+    fn within_age_window(modified: Option<SystemTime>, options: &AnalysisCrawlOptions) -> bool {
+        if options.max_age.is_none() && options.min_age.is_none() {
+            return true;
+        }
+        // Files without a modification time can't be placed in the window
+        let Some(modified) = modified else {
+            return false;
+        };
+        let age = SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or(Duration::ZERO);
+
+        options.max_age.is_none_or(|max_age| age <= max_age)
+            && options.min_age.is_none_or(|min_age| age >= min_age)
+    }
+
     /// Get a summary of what would be analyzed without actually analyzing
     pub fn preview_analysis<P: AsRef<Path>>(
         &self,
@@ -307,6 +1222,10 @@ impl<A: LlmAnalyser> AnalysisCrawler<A> {
     fn build_preview(&self, node: &FileNode, options: &AnalysisCrawlOptions) -> AnalysisPreview {
         let mut preview = AnalysisPreview::default();
         self.collect_preview_stats(node, options, &mut preview);
+        // The root's children are summarized by the project synthesis instead
+        preview
+            .synthesized_directories
+            .retain(|directory| directory != node.path());
         preview
     }
 
@@ -329,6 +1248,8 @@ impl<A: LlmAnalyser> AnalysisCrawler<A> {
                 if self.should_analyze_file(node, options) {
                     preview.analyzable_files += 1;
                     preview.analyzable_size += size;
+                    preview.estimated_tokens += size.div_ceil(4);
+                    preview.analyzable_paths.push(path.clone());
 
                     if let Some(ext) = extension {
                         *preview.file_types.entry(ext.clone()).or_insert(0) += 1;
@@ -340,16 +1261,27 @@ impl<A: LlmAnalyser> AnalysisCrawler<A> {
                     }
                 }
             }
-            FileNode::Directory { children, .. } => {
+            FileNode::Directory { path, children, .. } => {
                 preview.total_directories += 1;
+                let analyzable_before = preview.analyzable_files;
                 for child in children.values() {
                     self.collect_preview_stats(child, options, preview);
                 }
+                if preview.analyzable_files > analyzable_before {
+                    preview.synthesized_directories.push(path.clone());
+                }
             }
         }
     }
 }
 
+/// Send a progress event if a listener is attached, ignoring a closed receiver
+async fn emit(events: Option<&Sender<AnalysisEvent>>, event: AnalysisEvent) {
+    if let Some(sender) = events {
+        let _ = sender.send(event).await;
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct AnalysisPreview {
     pub total_files: usize,
@@ -360,10 +1292,22 @@ pub struct AnalysisPreview {
     pub skipped_files: usize,
     pub oversized_files: Vec<PathBuf>,
     pub file_types: HashMap<String, usize>,
+    /// Estimated input tokens for the analyzable content (~4 bytes per token)
+    pub estimated_tokens: u64,
+    /// Files that would be analyzed
+    pub analyzable_paths: Vec<PathBuf>,
+    /// Directories below the root that would get a synthesized summary
+    pub synthesized_directories: Vec<PathBuf>,
 }
 
 impl AnalysisPreview {
-    pub fn print_summary(&self) {
+    /// Estimated USD cost of sending the analyzable content to `model`
+    pub fn estimated_cost(&self, model: &ModelId) -> Option<f64> {
+        let (input_per_1k, _) = model.pricing()?;
+        Some(self.estimated_tokens as f64 / 1000.0 * input_per_1k)
+    }
+
+    pub fn print_summary(&self, model: Option<&ModelId>) {
         println!("Analysis Preview:");
         println!("  Total files: {}", self.total_files);
         println!("  Total directories: {}", self.total_directories);
@@ -371,6 +1315,13 @@ impl AnalysisPreview {
         println!("  Analyzable files: {}", self.analyzable_files);
         println!("  Analyzable size: {} bytes", self.analyzable_size);
         println!("  Skipped files: {}", self.skipped_files);
+        println!("  Estimated input tokens: {}", self.estimated_tokens);
+        if let Some(model) = model {
+            match self.estimated_cost(model) {
+                Some(cost) => println!("  Estimated input cost ({}): ${:.4}", model, cost),
+                None => println!("  Estimated input cost ({}): unknown pricing", model),
+            }
+        }
 
         if !self.oversized_files.is_empty() {
             println!("  Oversized files ({}):", self.oversized_files.len());
@@ -390,12 +1341,79 @@ impl AnalysisPreview {
     }
 }
 
+/// What a crawler has done so far, for the summary printed after a run
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RunStats {
+    /// Files analyzed by the LLM
+    pub files_analyzed: usize,
+    /// Files served from the cache or resume state
+    pub files_reused: usize,
+    pub files_failed: usize,
+    /// Directories summarized by the LLM
+    pub directories_synthesized: usize,
+    pub directories_failed: usize,
+    /// Whether the project synthesis failed and a local overview was used instead
+    pub project_failed: bool,
+    /// LLM requests attempted, counting every chunk of a chunked file but not retries
+    pub llm_calls: usize,
+    /// Requests retried after a retryable error
+    pub retries: u64,
+    /// Estimated tokens consumed
+    pub tokens: u64,
+}
+
+impl RunStats {
+    /// Estimated USD cost of the tokens consumed, priced as input to `model`
+    pub fn estimated_cost(&self, model: &ModelId) -> Option<f64> {
+        let (input_per_1k, _) = model.pricing()?;
+        Some(self.tokens as f64 / 1000.0 * input_per_1k)
+    }
+
+    /// Summary table of the run, with the estimated cost if `model` is given
+    pub fn summary(&self, model: Option<&ModelId>) -> String {
+        let mut rows = vec![
+            ("Files analyzed", self.files_analyzed.to_string()),
+            ("Files reused", self.files_reused.to_string()),
+            ("Files failed", self.files_failed.to_string()),
+            (
+                "Directories synthesized",
+                self.directories_synthesized.to_string(),
+            ),
+            ("Directories failed", self.directories_failed.to_string()),
+            (
+                "Project synthesis",
+                if self.project_failed { "failed" } else { "ok" }.to_string(),
+            ),
+            ("LLM calls", self.llm_calls.to_string()),
+            ("Retries", self.retries.to_string()),
+            ("Estimated tokens", self.tokens.to_string()),
+        ];
+        if let Some(model) = model {
+            let cost = match self.estimated_cost(model) {
+                Some(cost) => format!("${:.4} ({})", cost, model),
+                None => format!("unknown pricing ({})", model),
+            };
+            rows.push(("Estimated cost", cost));
+        }
+
+        let mut summary = String::from("Run Summary:\n");
+        for (label, value) in rows {
+            summary.push_str(&format!("  {:<24} {}\n", label, value));
+        }
+        summary
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::analysis::summary::DirectoryAnalysis;
+    use crate::analysis::staged::StagedAnalyser;
+    use crate::analysis::summary::{DirectoryAnalysis, Interface, InterfaceType};
+    use crate::test_support::LogBuffer;
     use mockall::mock;
     use std::fs;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU64, Ordering};
     use tempfile::TempDir;
 
     mock! {
@@ -428,6 +1446,8 @@ mod tests {
                 child_analyses: &[ChildAnalysis],
                 context: &AnalysisContext,
             ) -> Result<ProjectAnalysis, AnalysisError>;
+
+            fn tokens_used(&self) -> u64;
         }
     }
 
@@ -454,4 +1474,1322 @@ mod tests {
         assert!(preview.file_types.contains_key("rs"));
         assert!(preview.file_types.contains_key("toml"));
     }
+
+    #[test]
+    fn test_preview_estimates_tokens_and_cost() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("lib.rs"), "x".repeat(4000)).unwrap();
+
+        let crawler = AnalysisCrawler::new(MockTestAnalyser::new());
+        let preview = crawler
+            .preview_analysis(temp_dir.path(), &AnalysisCrawlOptions::default())
+            .unwrap();
+
+        assert_eq!(preview.estimated_tokens, 1000);
+        let cost = preview.estimated_cost(&ModelId::Claude4Sonnet).unwrap();
+        assert!((cost - 0.003).abs() < 1e-9);
+        assert_eq!(preview.estimated_cost(&ModelId::Llama33), Some(0.0));
+        assert_eq!(preview.estimated_cost(&ModelId::Grok1), None);
+    }
+
+    fn file_analysis(path: &Path) -> FileAnalysis {
+        FileAnalysis {
+            file_path: path.to_path_buf(),
+            file_type: "rs".to_string(),
+            summary: "A test file".to_string(),
+            external_dependencies: vec![],
+            public_interfaces: vec![],
+            line_count: None,
+            byte_size: None,
+            signatures: vec![],
+        }
+    }
+
+    fn directory_analysis(path: &Path) -> DirectoryAnalysis {
+        DirectoryAnalysis {
+            directory_path: path.to_path_buf(),
+            depth_level: 1,
+            summary: "A test directory".to_string(),
+            child_summaries: vec![],
+            key_components: vec![],
+            external_dependencies: vec![],
+            public_interfaces: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_analyze_project_emits_progress_events() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        fs::create_dir(temp_path.join("src")).unwrap();
+        fs::write(temp_path.join("src/main.rs"), "fn main() {}").unwrap();
+
+        let mut analyser = MockTestAnalyser::new();
+        analyser
+            .expect_analyze_file()
+            .returning(|path, _, _| Ok(file_analysis(path)));
+        analyser
+            .expect_analyze_directory()
+            .returning(|path, _, _| Ok(directory_analysis(path)));
+        analyser
+            .expect_analyze_project()
+            .returning(|_, _, _| Err(AnalysisError::LlmError("offline".to_string())));
+        let crawler = AnalysisCrawler::new(analyser);
+
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(16);
+        crawler
+            .analyze_project(temp_path, AnalysisCrawlOptions::default(), Some(sender))
+            .await
+            .unwrap();
+
+        let mut events = Vec::new();
+        while let Some(event) = receiver.recv().await {
+            events.push(event);
+        }
+
+        let file_path = temp_path.join("src/main.rs");
+        assert_eq!(
+            events[..2],
+            [
+                AnalysisEvent::FileStarted(file_path.clone()),
+                AnalysisEvent::FileCompleted(file_path.clone()),
+            ]
+        );
+        assert!(matches!(
+            &events[2],
+            AnalysisEvent::Analyzed(ChildAnalysis::File(file)) if file.file_path == file_path
+        ));
+        assert_eq!(
+            events[3],
+            AnalysisEvent::DirectorySynthesized(temp_path.join("src"))
+        );
+        assert_eq!(
+            events[4],
+            AnalysisEvent::Analyzed(ChildAnalysis::Directory(directory_analysis(
+                &temp_path.join("src")
+            )))
+        );
+        assert_eq!(events.len(), 5);
+    }
+
+    fn project_analysis() -> ProjectAnalysis {
+        ProjectAnalysis {
+            project_overview: "A test project".to_string(),
+            architecture_summary: "Flat".to_string(),
+            core_technologies: vec![],
+            main_interfaces: vec![],
+            development_considerations: vec![],
+            extension_points: vec![],
+            risk_factors: vec![],
+            license: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_analyze_project_reports_license() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        fs::write(temp_path.join("main.rs"), "fn main() {}").unwrap();
+        fs::write(
+            temp_path.join("LICENSE"),
+            "MIT License\n\nCopyright (c) 2025 Test\n\nPermission is hereby granted, free of charge, to any person obtaining a copy",
+        )
+        .unwrap();
+
+        let mut analyser = MockTestAnalyser::new();
+        analyser
+            .expect_analyze_file()
+            .returning(|path, _, _| Ok(file_analysis(path)));
+        analyser
+            .expect_analyze_project()
+            .returning(|_, _, _| Ok(project_analysis()));
+        let crawler = AnalysisCrawler::new(analyser);
+
+        let ProjectRun { project, .. } = crawler
+            .analyze_project(temp_path, AnalysisCrawlOptions::default(), None)
+            .await
+            .unwrap();
+
+        let project = project.unwrap();
+        assert_eq!(project.license.as_deref(), Some("MIT"));
+        assert!(project.to_string().contains("**License:** `MIT`"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_project_synthesis_falls_back_to_children() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        fs::write(temp_path.join("main.rs"), "fn main() {}").unwrap();
+
+        let mut analyser = MockTestAnalyser::new();
+        analyser.expect_analyze_file().returning(|path, _, _| {
+            Ok(FileAnalysis {
+                external_dependencies: vec!["tokio".to_string()],
+                ..file_analysis(path)
+            })
+        });
+        analyser
+            .expect_analyze_project()
+            .returning(|_, _, _| Err(AnalysisError::LlmError("offline".to_string())));
+        let crawler = AnalysisCrawler::new(analyser);
+
+        let ProjectRun {
+            project, children, ..
+        } = crawler
+            .analyze_project(temp_path, AnalysisCrawlOptions::default(), None)
+            .await
+            .unwrap();
+
+        let project = project.expect("a fallback project analysis");
+        assert_eq!(children.len(), 1);
+        assert!(project.project_overview.contains("Auto-generated"));
+        assert!(project.project_overview.contains("A test file"));
+        assert_eq!(project.core_technologies, vec!["tokio"]);
+    }
+
+    #[tokio::test]
+    async fn test_run_stats_count_invocations() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        fs::create_dir_all(temp_path.join("src")).unwrap();
+        fs::write(temp_path.join("main.rs"), "fn main() {}").unwrap();
+        fs::write(temp_path.join("broken.rs"), "fn broken() {}").unwrap();
+        fs::write(temp_path.join("src/lib.rs"), "fn lib() {}").unwrap();
+
+        let mut analyser = MockTestAnalyser::new();
+        analyser
+            .expect_analyze_file()
+            .times(3)
+            .returning(|path, _, _| match path.ends_with("broken.rs") {
+                true => Err(AnalysisError::LlmError("bad response".to_string())),
+                false => Ok(file_analysis(path)),
+            });
+        analyser
+            .expect_analyze_directory()
+            .times(1)
+            .returning(|path, _, _| Ok(directory_analysis(path)));
+        analyser
+            .expect_analyze_project()
+            .times(1)
+            .returning(|_, _, _| Ok(project_analysis()));
+        analyser.expect_tokens_used().return_const(1500u64);
+        let crawler = AnalysisCrawler::new(analyser);
+
+        crawler
+            .analyze_project(temp_path, AnalysisCrawlOptions::default(), None)
+            .await
+            .unwrap();
+
+        let stats = crawler.stats();
+        assert_eq!(
+            stats,
+            RunStats {
+                files_analyzed: 2,
+                files_reused: 0,
+                files_failed: 1,
+                directories_synthesized: 1,
+                directories_failed: 0,
+                project_failed: false,
+                llm_calls: 5,
+                retries: 0,
+                tokens: 1500,
+            }
+        );
+        let summary = stats.summary(Some(&ModelId::Gpt4o));
+        assert!(summary.contains("LLM calls"));
+        assert!(summary.contains("Estimated cost"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_analyses_are_returned_as_failures() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        fs::create_dir_all(temp_path.join("src")).unwrap();
+        fs::create_dir_all(temp_path.join("docs")).unwrap();
+        fs::write(temp_path.join("main.rs"), "fn main() {}").unwrap();
+        fs::write(temp_path.join("broken.rs"), "fn broken() {}").unwrap();
+        fs::write(temp_path.join("src/lib.rs"), "fn lib() {}").unwrap();
+        fs::write(temp_path.join("docs/guide.md"), "# Guide").unwrap();
+
+        let mut analyser = MockTestAnalyser::new();
+        analyser
+            .expect_analyze_file()
+            .times(4)
+            .returning(|path, _, _| match path.ends_with("broken.rs") {
+                true => Err(AnalysisError::LlmError("bad response".to_string())),
+                false => Ok(file_analysis(path)),
+            });
+        analyser
+            .expect_analyze_directory()
+            .times(2)
+            .returning(|path, _, _| match path.ends_with("docs") {
+                true => Err(AnalysisError::LlmError("directory failed".to_string())),
+                false => Ok(directory_analysis(path)),
+            });
+        analyser
+            .expect_analyze_project()
+            .times(1)
+            .returning(|_, _, _| Ok(project_analysis()));
+        analyser.expect_tokens_used().return_const(0u64);
+        let crawler = AnalysisCrawler::new(analyser);
+
+        let run = crawler
+            .analyze_project(temp_path, AnalysisCrawlOptions::default(), None)
+            .await
+            .unwrap();
+
+        let mut failed: Vec<&Path> = run
+            .failures
+            .iter()
+            .map(|(path, _)| path.as_path())
+            .collect();
+        failed.sort();
+        assert_eq!(
+            failed,
+            vec![
+                temp_path.join("broken.rs").as_path(),
+                temp_path.join("docs").as_path()
+            ]
+        );
+        assert!(
+            run.failures
+                .iter()
+                .all(|(_, error)| matches!(error, AnalysisCrawlError::Analysis(_)))
+        );
+        // The failed file has no analysis, the docs file keeps its own
+        assert!(!run.children.iter().any(|child| matches!(
+            child,
+            ChildAnalysis::File(file) if file.file_path.ends_with("broken.rs")
+        )));
+        assert!(run.project.is_some());
+        // Failures are handed over once
+        assert!(crawler.take_failures().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_only_patterns_limit_files_and_synthesized_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        for dir in ["src/crawler", "src/output"] {
+            fs::create_dir_all(temp_path.join(dir)).unwrap();
+            fs::write(temp_path.join(dir).join("mod.rs"), "fn main() {}").unwrap();
+        }
+        fs::write(temp_path.join("main.rs"), "fn main() {}").unwrap();
+
+        let mut analyser = MockTestAnalyser::new();
+        analyser
+            .expect_analyze_file()
+            .withf(|path, _, _| path.ends_with("src/crawler/mod.rs"))
+            .times(1)
+            .returning(|path, _, _| Ok(file_analysis(path)));
+        // src and the project also hold files outside the pattern, so only src/crawler
+        // is summarized
+        analyser
+            .expect_analyze_directory()
+            .withf(|path, _, _| path.ends_with("src/crawler"))
+            .times(1)
+            .returning(|path, _, _| Ok(directory_analysis(path)));
+        analyser.expect_analyze_project().never();
+        let crawler = AnalysisCrawler::new(analyser);
+
+        let mut options = AnalysisCrawlOptions::default();
+        options.crawl_options.only_patterns = vec!["src/crawler/**".to_string()];
+        let ProjectRun {
+            project, children, ..
+        } = crawler
+            .analyze_project(temp_path, options, None)
+            .await
+            .unwrap();
+
+        assert!(project.is_none());
+        assert_eq!(children.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_children_are_analyzed_largest_first() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        fs::create_dir_all(temp_path.join("big")).unwrap();
+        fs::create_dir_all(temp_path.join("small")).unwrap();
+        fs::write(temp_path.join("big/lib.rs"), "x".repeat(500)).unwrap();
+        fs::write(temp_path.join("small/lib.rs"), "x".repeat(50)).unwrap();
+        fs::write(temp_path.join("large.rs"), "x".repeat(300)).unwrap();
+        fs::write(temp_path.join("a_tie.rs"), "x".repeat(100)).unwrap();
+        fs::write(temp_path.join("b_tie.rs"), "x".repeat(100)).unwrap();
+
+        let mut analyser = MockTestAnalyser::new();
+        analyser
+            .expect_analyze_file()
+            .returning(|path, _, _| Ok(file_analysis(path)));
+        analyser
+            .expect_analyze_directory()
+            .returning(|path, _, _| Ok(directory_analysis(path)));
+        let crawler = AnalysisCrawler::new(analyser).with_max_concurrency(1);
+
+        let tree = crawl_directory(temp_path, CrawlOptions::default()).unwrap();
+        let options = AnalysisCrawlOptions::default();
+        for _ in 0..3 {
+            let children = crawler
+                .analyze_file_tree(&tree, &options, None)
+                .await
+                .unwrap();
+            let paths: Vec<PathBuf> = children
+                .iter()
+                .map(|child| match child {
+                    ChildAnalysis::File(file) => file.file_path.strip_prefix(temp_path),
+                    ChildAnalysis::Directory(dir) => dir.directory_path.strip_prefix(temp_path),
+                })
+                .map(|path| path.unwrap().to_path_buf())
+                .collect();
+            assert_eq!(
+                paths,
+                [
+                    "large.rs",
+                    "a_tie.rs",
+                    "b_tie.rs",
+                    "big/lib.rs",
+                    "big",
+                    "small/lib.rs",
+                    "small"
+                ]
+                .map(PathBuf::from)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_single_file_root_skips_synthesis() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("main.rs");
+        fs::write(&file, "fn main() {}").unwrap();
+
+        let mut analyser = MockTestAnalyser::new();
+        analyser
+            .expect_analyze_file()
+            .times(1)
+            .returning(|path, _, _| Ok(file_analysis(path)));
+        analyser.expect_analyze_directory().never();
+        analyser.expect_analyze_project().never();
+        let crawler = AnalysisCrawler::new(analyser);
+
+        let ProjectRun {
+            project, children, ..
+        } = crawler
+            .analyze_project(&file, AnalysisCrawlOptions::default(), None)
+            .await
+            .unwrap();
+
+        assert!(project.is_none());
+        assert_eq!(children.len(), 1);
+        assert!(
+            matches!(&children[0], ChildAnalysis::File(analysis) if analysis.file_path == file)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_analysis_halts_after_token_budget() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        for i in 0..5 {
+            fs::write(temp_path.join(format!("file{}.rs", i)), "fn main() {}").unwrap();
+        }
+
+        // Every file analysis costs 100 tokens
+        let used = Arc::new(AtomicU64::new(0));
+        let mut analyser = MockTestAnalyser::new();
+        let counter = used.clone();
+        analyser
+            .expect_analyze_file()
+            .times(3)
+            .returning(move |path, _, _| {
+                counter.fetch_add(100, Ordering::Relaxed);
+                Ok(file_analysis(path))
+            });
+        analyser.expect_analyze_project().never();
+        let counter = used.clone();
+        analyser
+            .expect_tokens_used()
+            .returning(move || counter.load(Ordering::Relaxed));
+        let crawler = AnalysisCrawler::new(analyser);
+
+        let options = AnalysisCrawlOptions {
+            max_total_tokens: Some(250),
+            ..Default::default()
+        };
+        let ProjectRun {
+            project, children, ..
+        } = crawler
+            .analyze_project(temp_path, options, None)
+            .await
+            .unwrap();
+
+        assert!(project.is_none());
+        assert_eq!(children.len(), 3);
+        assert_eq!(used.load(Ordering::Relaxed), 300);
+    }
+
+    #[tokio::test]
+    async fn test_staged_analyser_uses_stage_models() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        fs::write(temp_path.join("main.rs"), "fn main() {}").unwrap();
+
+        let mut cheap = MockTestAnalyser::new();
+        cheap
+            .expect_analyze_file()
+            .times(1)
+            .returning(|path, _, _| Ok(file_analysis(path)));
+        cheap.expect_analyze_project().never();
+
+        let mut strong = MockTestAnalyser::new();
+        strong.expect_analyze_file().never();
+        strong
+            .expect_analyze_project()
+            .times(1)
+            .returning(|_, _, _| Ok(project_analysis()));
+
+        let mut default = MockTestAnalyser::new();
+        default.expect_analyze_file().never();
+        default.expect_analyze_project().never();
+
+        let analyser = StagedAnalyser::new(default)
+            .file(Some(cheap))
+            .project(Some(strong));
+        let crawler = AnalysisCrawler::new(analyser);
+
+        let ProjectRun {
+            project, children, ..
+        } = crawler
+            .analyze_project(temp_path, AnalysisCrawlOptions::default(), None)
+            .await
+            .unwrap();
+
+        assert!(project.is_some());
+        assert_eq!(children.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_resume_reuses_unchanged_analyses() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("project");
+        let output = temp_dir.path().join("docs");
+        fs::create_dir_all(root.join("a")).unwrap();
+        fs::create_dir_all(root.join("b")).unwrap();
+        fs::write(root.join("a/one.rs"), "fn one() {}").unwrap();
+        fs::write(root.join("b/two.rs"), "fn two() {}").unwrap();
+
+        let mut analyser = MockTestAnalyser::new();
+        analyser
+            .expect_analyze_file()
+            .times(2)
+            .returning(|path, _, _| Ok(file_analysis(path)));
+        analyser
+            .expect_analyze_directory()
+            .times(2)
+            .returning(|path, _, _| Ok(directory_analysis(path)));
+        analyser
+            .expect_analyze_project()
+            .returning(|_, _, _| Err(AnalysisError::LlmError("network dropped".to_string())));
+        let crawler = AnalysisCrawler::new(analyser)
+            .with_resume_state(&output, false)
+            .unwrap();
+        crawler
+            .analyze_project(&root, AnalysisCrawlOptions::default(), None)
+            .await
+            .unwrap();
+        drop(crawler);
+        assert_eq!(load_state(&output).len(), 4);
+
+        // Only a/one.rs changes, so only it and directory a are redone
+        let changed = root.join("a/one.rs");
+        fs::write(&changed, "fn one() { changed() }").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&changed)
+            .unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(60))
+            .unwrap();
+
+        let mut analyser = MockTestAnalyser::new();
+        analyser
+            .expect_analyze_file()
+            .withf(|path, _, _| path.ends_with("a/one.rs"))
+            .times(1)
+            .returning(|path, _, _| Ok(file_analysis(path)));
+        analyser
+            .expect_analyze_directory()
+            .withf(|path, _, _| path.ends_with("a"))
+            .times(1)
+            .returning(|path, _, _| Ok(directory_analysis(path)));
+        analyser
+            .expect_analyze_project()
+            .times(1)
+            .returning(|_, _, _| Ok(project_analysis()));
+        let crawler = AnalysisCrawler::new(analyser)
+            .with_resume_state(&output, true)
+            .unwrap();
+        let ProjectRun {
+            project, children, ..
+        } = crawler
+            .analyze_project(&root, AnalysisCrawlOptions::default(), None)
+            .await
+            .unwrap();
+
+        assert!(project.is_some());
+        assert_eq!(children.len(), 4);
+        assert_eq!(crawler.take_refreshed(), vec![changed]);
+    }
+
+    #[tokio::test]
+    async fn test_resume_reuses_directories_with_subdirectories() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("project");
+        let output = temp_dir.path().join("docs");
+        fs::create_dir_all(root.join("a/sub")).unwrap();
+        fs::write(root.join("a/one.rs"), "fn one() {}").unwrap();
+        fs::write(root.join("a/sub/two.rs"), "fn two() {}").unwrap();
+
+        let mut analyser = MockTestAnalyser::new();
+        analyser
+            .expect_analyze_file()
+            .times(2)
+            .returning(|path, _, _| Ok(file_analysis(path)));
+        analyser
+            .expect_analyze_directory()
+            .times(2)
+            .returning(|path, _, _| Ok(directory_analysis(path)));
+        analyser
+            .expect_analyze_project()
+            .returning(|_, _, _| Ok(project_analysis()));
+        let crawler = AnalysisCrawler::new(analyser)
+            .with_resume_state(&output, false)
+            .unwrap();
+        crawler
+            .analyze_project(&root, AnalysisCrawlOptions::default(), None)
+            .await
+            .unwrap();
+        drop(crawler);
+
+        // Directory a is keyed on its own file, not on the files of a/sub as well
+        let mut analyser = MockTestAnalyser::new();
+        analyser.expect_analyze_file().never();
+        analyser.expect_analyze_directory().never();
+        analyser
+            .expect_analyze_project()
+            .returning(|_, _, _| Ok(project_analysis()));
+        let crawler = AnalysisCrawler::new(analyser)
+            .with_resume_state(&output, true)
+            .unwrap();
+        let ProjectRun { children, .. } = crawler
+            .analyze_project(&root, AnalysisCrawlOptions::default(), None)
+            .await
+            .unwrap();
+
+        assert_eq!(children.len(), 4);
+    }
+
+    #[test]
+    fn test_load_state_keeps_latest_entry_per_path() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(load_state(temp_dir.path()).is_empty());
+
+        let mut stale = file_analysis(Path::new("src/lib.rs"));
+        stale.summary = "stale".to_string();
+        let lines = [
+            serde_json::to_string(&ChildAnalysis::File(stale)).unwrap(),
+            serde_json::to_string(&ChildAnalysis::File(file_analysis(Path::new("src/a.rs"))))
+                .unwrap(),
+            serde_json::to_string(&ChildAnalysis::File(file_analysis(Path::new("src/lib.rs"))))
+                .unwrap(),
+            r#"{"File": {"file_path": "src/tor"#.to_string(),
+        ];
+        fs::write(temp_dir.path().join(RESUME_STATE_FILE), lines.join("\n")).unwrap();
+
+        let prior = load_state(temp_dir.path());
+        assert_eq!(prior.len(), 2);
+        let ChildAnalysis::File(lib) = &prior[1].analysis else {
+            panic!("Expected a file analysis");
+        };
+        assert_eq!(lib.file_path, PathBuf::from("src/lib.rs"));
+        assert_eq!(lib.summary, "A test file");
+
+        let analyses = load_prior_analysis(temp_dir.path());
+        assert_eq!(
+            analyses,
+            prior
+                .into_iter()
+                .map(|record| record.analysis)
+                .collect::<Vec<_>>()
+        );
+        assert!(load_prior_analysis(&temp_dir.path().join("missing")).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_analysis_spans_carry_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+        fs::create_dir(temp_path.join("src")).unwrap();
+        fs::write(temp_path.join("src/main.rs"), "fn main() {}").unwrap();
+
+        let mut analyser = MockTestAnalyser::new();
+        analyser
+            .expect_analyze_file()
+            .returning(|path, _, _| Ok(file_analysis(path)));
+        analyser
+            .expect_analyze_directory()
+            .returning(|path, _, _| Ok(directory_analysis(path)));
+        analyser
+            .expect_analyze_project()
+            .returning(|_, _, _| Ok(project_analysis()));
+        analyser.expect_tokens_used().return_const(0u64);
+        let crawler = AnalysisCrawler::new(analyser);
+
+        let logs = LogBuffer::default();
+        let subscriber = tracing_subscriber::fmt()
+            .json()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_writer({
+                let logs = logs.clone();
+                move || logs.clone()
+            })
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        crawler
+            .analyze_project(temp_path, AnalysisCrawlOptions::default(), None)
+            .await
+            .unwrap();
+
+        let output = logs.contents();
+        let spans: Vec<serde_json::Value> = output
+            .lines()
+            .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+            .filter(|line| line["fields"]["message"] == "Finished")
+            .map(|line| line["span"].clone())
+            .collect();
+
+        let span_for = |name: &str| {
+            spans
+                .iter()
+                .find(|span| span["name"] == name)
+                .unwrap_or_else(|| panic!("no {} span in {}", name, output))
+        };
+        let file_span = span_for("analyze_file");
+        assert_eq!(
+            file_span["path"],
+            temp_path.join("src/main.rs").display().to_string()
+        );
+        assert!(file_span["elapsed_ms"].is_u64());
+        assert_eq!(file_span["tokens"], 0);
+        assert_eq!(
+            span_for("synthesize_directory")["path"],
+            temp_path.join("src").display().to_string()
+        );
+        assert_eq!(
+            span_for("synthesize_project")["path"],
+            temp_path.display().to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_analysis_respects_age_window() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+        let now = SystemTime::now();
+        for (name, age) in [("fresh.rs", 0), ("recent.rs", 10), ("stale.rs", 40)] {
+            let path = temp_path.join(name);
+            fs::write(&path, "fn main() {}").unwrap();
+            let file = fs::File::options().write(true).open(&path).unwrap();
+            file.set_modified(now - DAY * age).unwrap();
+        }
+
+        let mut analyser = MockTestAnalyser::new();
+        analyser
+            .expect_analyze_file()
+            .withf(|path, _, _| path.ends_with("recent.rs"))
+            .times(1)
+            .returning(|path, _, _| Ok(file_analysis(path)));
+        analyser
+            .expect_analyze_project()
+            .returning(|_, _, _| Ok(project_analysis()));
+        let crawler = AnalysisCrawler::new(analyser);
+
+        let options = AnalysisCrawlOptions {
+            max_age: Some(DAY * 30),
+            min_age: Some(DAY),
+            ..Default::default()
+        };
+        let ProjectRun { children, .. } = crawler
+            .analyze_project(temp_path, options, None)
+            .await
+            .unwrap();
+
+        assert_eq!(children.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_min_file_size_skips_stub_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        fs::write(temp_path.join("stub.rs"), "// todo").unwrap();
+        fs::write(
+            temp_path.join("main.rs"),
+            "fn main() { println!(\"hello\"); }",
+        )
+        .unwrap();
+
+        let mut analyser = MockTestAnalyser::new();
+        analyser
+            .expect_analyze_file()
+            .withf(|path, _, _| path.ends_with("main.rs"))
+            .times(1)
+            .returning(|path, _, _| Ok(file_analysis(path)));
+        analyser
+            .expect_analyze_project()
+            .returning(|_, _, _| Ok(project_analysis()));
+        let crawler = AnalysisCrawler::new(analyser);
+
+        let options = AnalysisCrawlOptions {
+            min_file_size: 20,
+            ..Default::default()
+        };
+        let ProjectRun { children, .. } = crawler
+            .analyze_project(temp_path, options, None)
+            .await
+            .unwrap();
+
+        assert_eq!(children.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_max_files_stops_dispatching_at_the_cap() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        fs::create_dir_all(temp_path.join("src")).unwrap();
+        for name in ["a.rs", "b.rs", "c.rs", "src/d.rs", "src/e.rs"] {
+            fs::write(temp_path.join(name), "fn main() {}").unwrap();
+        }
+
+        let mut analyser = MockTestAnalyser::new();
+        analyser
+            .expect_analyze_file()
+            .times(2)
+            .returning(|path, _, _| Ok(file_analysis(path)));
+        analyser
+            .expect_analyze_project()
+            .returning(|_, _, _| Ok(project_analysis()));
+        let crawler = AnalysisCrawler::new(analyser);
+
+        let options = AnalysisCrawlOptions {
+            max_files: Some(2),
+            ..Default::default()
+        };
+        let ProjectRun { children, .. } = crawler
+            .analyze_project(temp_path, options, None)
+            .await
+            .unwrap();
+
+        assert_eq!(children.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_max_files_applies_per_run_and_skips_cached_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+        for name in ["a.rs", "b.rs", "c.rs", "d.rs"] {
+            fs::write(temp_path.join(name), "fn main() {}").unwrap();
+        }
+
+        let mut analyser = MockTestAnalyser::new();
+        // 2 files on each run, the second run reusing the first run's files from the cache
+        analyser
+            .expect_analyze_file()
+            .times(4)
+            .returning(|path, _, _| Ok(file_analysis(path)));
+        analyser
+            .expect_analyze_project()
+            .returning(|_, _, _| Ok(project_analysis()));
+        let crawler = AnalysisCrawler::new(analyser).with_cache();
+        let options = AnalysisCrawlOptions {
+            max_files: Some(2),
+            ..Default::default()
+        };
+
+        let ProjectRun { children, .. } = crawler
+            .analyze_project(temp_path, options.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(children.len(), 2);
+        assert_eq!(
+            crawler.take_refreshed(),
+            vec![temp_path.join("a.rs"), temp_path.join("b.rs")]
+        );
+
+        let ProjectRun { children, .. } = crawler
+            .analyze_project(temp_path, options, None)
+            .await
+            .unwrap();
+        assert_eq!(children.len(), 4);
+        assert_eq!(
+            crawler.take_refreshed(),
+            vec![temp_path.join("c.rs"), temp_path.join("d.rs")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_binary_detection_and_extensionless_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        fs::write(
+            temp_path.join("Dockerfile"),
+            "FROM rust:latest\nRUN cargo build",
+        )
+        .unwrap();
+        fs::write(temp_path.join("blob.txt"), b"text\0\0\x01binary").unwrap();
+        fs::write(temp_path.join("LICENSE"), "All rights reserved").unwrap();
+
+        let mut analyser = MockTestAnalyser::new();
+        analyser
+            .expect_analyze_file()
+            .withf(|path, _, _| path.ends_with("Dockerfile"))
+            .times(1)
+            .returning(|path, _, _| Ok(file_analysis(path)));
+        analyser
+            .expect_analyze_project()
+            .returning(|_, _, _| Ok(project_analysis()));
+        let crawler = AnalysisCrawler::new(analyser);
+
+        let preview = crawler
+            .preview_analysis(temp_path, &AnalysisCrawlOptions::default())
+            .unwrap();
+        assert_eq!(preview.analyzable_files, 1);
+
+        let ProjectRun { children, .. } = crawler
+            .analyze_project(temp_path, AnalysisCrawlOptions::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(children.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_lossy_decode_analyzes_non_utf8_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        // Latin-1 "café", which is not valid UTF-8
+        fs::write(temp_path.join("latin1.rs"), b"// caf\xe9\nfn main() {}\n").unwrap();
+        let mut utf16 = vec![0xFF, 0xFE];
+        utf16.extend("fn utf16() {}".encode_utf16().flat_map(u16::to_le_bytes));
+        fs::write(temp_path.join("wide.rs"), utf16).unwrap();
+
+        let mut analyser = MockTestAnalyser::new();
+        analyser.expect_analyze_file().never();
+        analyser
+            .expect_analyze_project()
+            .returning(|_, _, _| Ok(project_analysis()));
+        let ProjectRun { children, .. } = AnalysisCrawler::new(analyser)
+            .analyze_project(temp_path, AnalysisCrawlOptions::default(), None)
+            .await
+            .unwrap();
+        assert!(children.is_empty());
+
+        let mut analyser = MockTestAnalyser::new();
+        analyser
+            .expect_analyze_file()
+            .withf(|path, content, _| {
+                if path.ends_with("latin1.rs") {
+                    content == "// caf\u{FFFD}\nfn main() {}\n"
+                } else {
+                    content == "fn utf16() {}"
+                }
+            })
+            .times(2)
+            .returning(|path, _, _| Ok(file_analysis(path)));
+        analyser
+            .expect_analyze_project()
+            .returning(|_, _, _| Ok(project_analysis()));
+        let options = AnalysisCrawlOptions {
+            lossy_decode: true,
+            ..Default::default()
+        };
+        let ProjectRun { children, .. } = AnalysisCrawler::new(analyser)
+            .analyze_project(temp_path, options, None)
+            .await
+            .unwrap();
+        assert_eq!(children.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_analyze_files_only_analyzes_given_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        fs::write(temp_path.join("changed.rs"), "fn changed() {}").unwrap();
+        fs::write(temp_path.join("untouched.rs"), "fn untouched() {}").unwrap();
+        fs::write(temp_path.join("notes.bin"), "not analyzable").unwrap();
+
+        let mut analyser = MockTestAnalyser::new();
+        analyser
+            .expect_analyze_file()
+            .withf(|path, _, _| path.ends_with("changed.rs"))
+            .times(1)
+            .returning(|path, _, _| Ok(file_analysis(path)));
+        let crawler = AnalysisCrawler::new(analyser);
+
+        let paths = vec![
+            temp_path.join("changed.rs"),
+            temp_path.join("notes.bin"),
+            temp_path.join("deleted.rs"),
+        ];
+        let analyses = crawler
+            .analyze_files(temp_path, &paths, &AnalysisCrawlOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(analyses.len(), 1);
+        assert_eq!(analyses[0].file_path, temp_path.join("changed.rs"));
+    }
+
+    #[test]
+    fn test_chunk_content_overlaps_lines() {
+        let content = "one\ntwo\nthree\nfour\nfive\n";
+        assert_eq!(
+            chunk_content(content, 11, 1),
+            vec![
+                "one\ntwo\n",
+                "two\nthree\n",
+                "three\nfour\n",
+                "four\nfive\n"
+            ]
+        );
+        assert_eq!(
+            chunk_content(content, 14, 0),
+            vec!["one\ntwo\nthree\n", "four\nfive\n"]
+        );
+        assert_eq!(chunk_content(content, 100, 3), vec![content]);
+        assert_eq!(
+            chunk_content("a very long line", 4, 1),
+            vec!["a very long line"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_large_files_are_analyzed_in_chunks() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+        let content: String = (0..30).map(|i| format!("fn f{:02}() {{}}\n", i)).collect();
+        fs::write(temp_path.join("big.rs"), &content).unwrap();
+
+        let mut analyser = MockTestAnalyser::new();
+        analyser
+            .expect_analyze_file()
+            .times(3)
+            .returning(|path, chunk, _| {
+                let mut analysis = file_analysis(path);
+                let first = chunk.lines().next().unwrap().to_string();
+                analysis.summary = format!("Starts with {}", first);
+                analysis.external_dependencies = vec!["std".to_string(), first.clone()];
+                analysis.public_interfaces = vec![Interface {
+                    name: first,
+                    interface_type: InterfaceType::Function,
+                    description: "A function".to_string(),
+                }];
+                Ok(analysis)
+            });
+        analyser
+            .expect_analyze_project()
+            .returning(|_, _, _| Ok(project_analysis()));
+
+        // 30 lines of 12 bytes in chunks of 13 lines, repeating 2 lines each time
+        let options = AnalysisCrawlOptions {
+            max_file_size: 160,
+            chunk_large_files: true,
+            chunk_overlap: 2,
+            ..Default::default()
+        };
+        let ProjectRun { children, .. } = AnalysisCrawler::new(analyser)
+            .analyze_project(temp_path, options, None)
+            .await
+            .unwrap();
+
+        let [ChildAnalysis::File(analysis)] = children.as_slice() else {
+            panic!("Expected a single file analysis");
+        };
+        assert_eq!(analysis.summary.matches("Starts with").count(), 3);
+        assert_eq!(analysis.external_dependencies.len(), 4);
+        assert_eq!(analysis.public_interfaces.len(), 3);
+        assert_eq!(analysis.line_count, Some(30));
+    }
+
+    #[tokio::test]
+    async fn test_chunk_count_is_capped() {
+        let content: String = (0..30).map(|i| format!("fn f{:02}() {{}}\n", i)).collect();
+
+        let mut analyser = MockTestAnalyser::new();
+        analyser
+            .expect_analyze_file()
+            .times(2)
+            .returning(|path, _, _| Ok(file_analysis(path)));
+
+        let options = AnalysisCrawlOptions {
+            max_file_size: 160,
+            chunk_large_files: true,
+            max_chunks: 2,
+            ..Default::default()
+        };
+        AnalysisCrawler::new(analyser)
+            .analyze_content(Path::new("big.rs"), &content, &options)
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn test_chunks_are_sized_from_the_context_window() {
+        let options = AnalysisCrawlOptions {
+            max_file_size: 160,
+            ..Default::default()
+        };
+        assert_eq!(chunk_size(&options), 160);
+
+        let options = AnalysisCrawlOptions {
+            context_window: Some(ModelId::Phi3.context_window().unwrap()),
+            ..options
+        };
+        assert_eq!(chunk_size(&options), 4_096 / 2 * 4);
+    }
+
+    #[tokio::test]
+    async fn test_analyze_content_skips_the_filesystem() {
+        let virtual_path = Path::new("does/not/exist.rs");
+
+        let mut analyser = MockTestAnalyser::new();
+        analyser
+            .expect_analyze_file()
+            .withf(|path, content, _| {
+                path == Path::new("does/not/exist.rs") && content == "fn added() {}\n"
+            })
+            .times(1)
+            .returning(|path, _, _| Ok(file_analysis(path)));
+        let crawler = AnalysisCrawler::new(analyser);
+
+        let analysis = crawler
+            .analyze_content(
+                virtual_path,
+                "fn added() {}\n",
+                &AnalysisCrawlOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(analysis.file_path, virtual_path);
+        assert_eq!(analysis.line_count, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_cache_only_reanalyzes_changed_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+        fs::create_dir(temp_path.join("sub")).unwrap();
+        fs::write(temp_path.join("a.rs"), "fn a() {}").unwrap();
+        fs::write(temp_path.join("b.rs"), "fn b() {}").unwrap();
+        fs::write(temp_path.join("sub/c.rs"), "fn c() {}").unwrap();
+
+        let mut analyser = MockTestAnalyser::new();
+        // 3 files on the first run, then only the edited file
+        analyser
+            .expect_analyze_file()
+            .times(4)
+            .returning(|path, _, _| Ok(file_analysis(path)));
+        analyser
+            .expect_analyze_directory()
+            .times(1)
+            .returning(|path, _, _| Ok(directory_analysis(path)));
+        analyser
+            .expect_analyze_project()
+            .times(2)
+            .returning(|_, _, _| Ok(project_analysis()));
+        let crawler = AnalysisCrawler::new(analyser).with_cache();
+
+        crawler
+            .analyze_project(temp_path, AnalysisCrawlOptions::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(crawler.take_refreshed().len(), 3);
+
+        // Nothing changed: everything is served from the cache
+        let ProjectRun {
+            project, children, ..
+        } = crawler
+            .analyze_project(temp_path, AnalysisCrawlOptions::default(), None)
+            .await
+            .unwrap();
+        assert!(project.is_some());
+        assert_eq!(children.len(), 4);
+        assert!(crawler.take_refreshed().is_empty());
+
+        fs::write(temp_path.join("a.rs"), "fn a() { changed() }").unwrap();
+        crawler
+            .analyze_project(temp_path, AnalysisCrawlOptions::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(crawler.take_refreshed(), vec![temp_path.join("a.rs")]);
+    }
+
+    /// Analyser that records how many file analyses are in flight at once
+    #[derive(Clone, Default)]
+    struct ConcurrencyProbe {
+        in_flight: Arc<AtomicU64>,
+        peak: Arc<AtomicU64>,
+    }
+
+    #[async_trait::async_trait]
+    impl LlmAnalyser for ConcurrencyProbe {
+        async fn analyze_file(
+            &self,
+            file_path: &Path,
+            _file_content: &str,
+            _context: &AnalysisContext,
+        ) -> Result<FileAnalysis, AnalysisError> {
+            let current = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(current, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(file_analysis(file_path))
+        }
+
+        async fn analyze_directory(
+            &self,
+            directory_path: &Path,
+            _child_analyses: &[ChildAnalysis],
+            _context: &AnalysisContext,
+        ) -> Result<DirectoryAnalysis, AnalysisError> {
+            Ok(directory_analysis(directory_path))
+        }
+
+        async fn analyze_project(
+            &self,
+            _project_root: &Path,
+            _child_analyses: &[ChildAnalysis],
+            _context: &AnalysisContext,
+        ) -> Result<ProjectAnalysis, AnalysisError> {
+            Ok(project_analysis())
+        }
+
+        fn tokens_used(&self) -> u64 {
+            0
+        }
+    }
+
+    /// Analyses `slow.rs` never finish; every other file finishes at once
+    #[derive(Clone)]
+    struct HangingAnalyser;
+
+    #[async_trait::async_trait]
+    impl LlmAnalyser for HangingAnalyser {
+        async fn analyze_file(
+            &self,
+            file_path: &Path,
+            _file_content: &str,
+            _context: &AnalysisContext,
+        ) -> Result<FileAnalysis, AnalysisError> {
+            if file_path.ends_with("slow.rs") {
+                std::future::pending::<()>().await;
+            }
+            Ok(file_analysis(file_path))
+        }
+
+        async fn analyze_directory(
+            &self,
+            directory_path: &Path,
+            _child_analyses: &[ChildAnalysis],
+            _context: &AnalysisContext,
+        ) -> Result<DirectoryAnalysis, AnalysisError> {
+            Ok(directory_analysis(directory_path))
+        }
+
+        async fn analyze_project(
+            &self,
+            _project_root: &Path,
+            _child_analyses: &[ChildAnalysis],
+            _context: &AnalysisContext,
+        ) -> Result<ProjectAnalysis, AnalysisError> {
+            Ok(project_analysis())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancellation_keeps_finished_analyses() {
+        let project = TempDir::new().unwrap();
+        let output = TempDir::new().unwrap();
+        fs::write(project.path().join("fast.rs"), "fn fast() {}").unwrap();
+        fs::write(project.path().join("slow.rs"), "fn slow() {}").unwrap();
+
+        let cancel = CancellationToken::new();
+        let crawler = AnalysisCrawler::new(HangingAnalyser)
+            .with_resume_state(output.path(), false)
+            .unwrap()
+            .with_cancellation(cancel.clone());
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            cancel.cancel();
+        });
+
+        let ProjectRun {
+            project: project_analysis,
+            children,
+            ..
+        } = crawler
+            .analyze_project(project.path(), AnalysisCrawlOptions::default(), None)
+            .await
+            .unwrap();
+
+        assert!(project_analysis.is_none());
+        assert_eq!(children.len(), 1);
+        let saved = load_state(output.path());
+        assert_eq!(saved.len(), 1);
+        assert!(
+            matches!(&saved[0].analysis, ChildAnalysis::File(file) if file.file_path.ends_with("fast.rs"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sibling_subtrees_are_analyzed_concurrently() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+        for dir in ["alpha", "beta", "gamma"] {
+            fs::create_dir(temp_path.join(dir)).unwrap();
+            fs::write(temp_path.join(dir).join("lib.rs"), "fn main() {}").unwrap();
+        }
+
+        let probe = ConcurrencyProbe::default();
+        let crawler = AnalysisCrawler::new(probe.clone());
+        let ProjectRun { children, .. } = crawler
+            .analyze_project(temp_path, AnalysisCrawlOptions::default(), None)
+            .await
+            .unwrap();
+
+        // Each subtree contributes its file and its directory synthesis
+        assert_eq!(children.len(), 6);
+        assert_eq!(probe.peak.load(Ordering::SeqCst), 3);
+
+        let probe = ConcurrencyProbe::default();
+        let crawler = AnalysisCrawler::new(probe.clone()).with_max_concurrency(1);
+        crawler
+            .analyze_project(temp_path, AnalysisCrawlOptions::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(probe.peak.load(Ordering::SeqCst), 1);
+    }
 }
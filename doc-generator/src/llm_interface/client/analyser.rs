@@ -2,12 +2,9 @@ use std::path::Path;
 
 use async_trait::async_trait;
 
-use crate::analysis::{
-    prompt::PromptTemplates,
-    summary::{
-        AnalysisContext, AnalysisError, ChildAnalysis, DirectoryAnalysis, FileAnalysis,
-        LlmAnalyser, ProjectAnalysis,
-    },
+use crate::analysis::summary::{
+    AnalysisContext, AnalysisError, ChildAnalysis, DirectoryAnalysis, FileAnalysis, LlmAnalyser,
+    ProjectAnalysis,
 };
 
 use super::LlmClient;
@@ -20,8 +17,9 @@ impl LlmAnalyser for LlmClient {
         file_content: &str,
         context: &AnalysisContext,
     ) -> Result<FileAnalysis, AnalysisError> {
-        let templates = PromptTemplates::default();
-        let prompt = templates.build_file_analysis_prompt(file_path, context);
+        let prompt = self
+            .prompt_templates()
+            .build_file_analysis_prompt(file_path, context);
         let request = self
             .request()
             .system_prompt(prompt)
@@ -43,8 +41,9 @@ impl LlmAnalyser for LlmClient {
         child_analyses: &[ChildAnalysis],
         context: &AnalysisContext,
     ) -> Result<DirectoryAnalysis, AnalysisError> {
-        let templates = PromptTemplates::default();
-        let prompt = templates.build_directory_synthesis_prompt(directory_path, context);
+        let prompt = self
+            .prompt_templates()
+            .build_directory_synthesis_prompt(directory_path, context);
         let content: String = serde_json::to_string_pretty(child_analyses)
             .map_err(|e| AnalysisError::ParseError(e.to_string()))?;
         let request = self
@@ -54,7 +53,7 @@ impl LlmAnalyser for LlmClient {
             .execute_structured_with_retry::<DirectoryAnalysis>()
             .await;
         match request {
-            Ok(res) => Ok(res),
+            Ok(res) => Ok(res.normalized()),
             Err(e) => {
                 eprint!("path: {:?}", directory_path);
                 Err(AnalysisError::LlmError(e.to_string()))
@@ -68,8 +67,9 @@ impl LlmAnalyser for LlmClient {
         child_analyses: &[ChildAnalysis],
         context: &AnalysisContext,
     ) -> Result<ProjectAnalysis, AnalysisError> {
-        let templates = PromptTemplates::default();
-        let prompt = templates.build_project_analysis_prompt(project_root, context);
+        let prompt = self
+            .prompt_templates()
+            .build_project_analysis_prompt(project_root, context);
         let content: String = serde_json::to_string_pretty(child_analyses)
             .map_err(|e| AnalysisError::ParseError(e.to_string()))?;
         let request = self
@@ -79,11 +79,19 @@ impl LlmAnalyser for LlmClient {
             .execute_structured_with_retry::<ProjectAnalysis>()
             .await;
         match request {
-            Ok(res) => Ok(res),
+            Ok(res) => Ok(res.normalized()),
             Err(e) => {
                 eprint!("path: {:?}", project_root);
                 Err(AnalysisError::LlmError(e.to_string()))
             }
         }
     }
+
+    fn tokens_used(&self) -> u64 {
+        LlmClient::tokens_used(self)
+    }
+
+    fn retries(&self) -> u64 {
+        LlmClient::retries(self)
+    }
 }
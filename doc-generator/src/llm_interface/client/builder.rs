@@ -2,6 +2,7 @@
 use super::LlmClient;
 use super::RetryConfig;
 use super::models;
+use crate::llm_interface::exceptions::LlmError;
 use std::time::Duration;
 
 /// Builder for constructing LlmClient instances with fluent configuration
@@ -11,6 +12,7 @@ pub struct LlmClientBuilder {
     max_tokens: Option<u32>,
     temperature: Option<f32>,
     retry_config: Option<RetryConfig>,
+    base_url: Option<String>,
 }
 
 impl LlmClientBuilder {
@@ -22,6 +24,7 @@ impl LlmClientBuilder {
             max_tokens: None,
             temperature: None,
             retry_config: None,
+            base_url: None,
         }
     }
 
@@ -49,6 +52,12 @@ impl LlmClientBuilder {
         self
     }
 
+    /// Set a custom base URL for the provider endpoint
+    pub fn base_url<S: Into<String>>(mut self, base_url: S) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
     /// Set retry configuration
     pub fn retry_config(mut self, retry_config: RetryConfig) -> Self {
         self.retry_config = Some(retry_config);
@@ -69,11 +78,21 @@ impl LlmClientBuilder {
     /// Build the LlmClient
     ///
     /// # Errors
-    /// Returns an error if no model is specified
+    /// Returns an error if no model is specified, or if no API key is set and the model's
+    /// environment variable is unset
     pub fn build(self) -> Result<LlmClient, LlmClientBuilderError> {
         let model = self.model.ok_or(LlmClientBuilderError::MissingModel)?;
 
-        let mut client = LlmClient::new(model, self.api_key, self.max_tokens, self.temperature);
+        let mut client =
+            match LlmClient::try_new(model, self.api_key, self.max_tokens, self.temperature) {
+                Ok(client) => client.with_base_url(self.base_url),
+                Err(LlmError::MissingApiKey { env_var, model }) => {
+                    return Err(LlmClientBuilderError::MissingApiKey { env_var, model });
+                }
+                Err(error) => {
+                    unreachable!("LlmClient::try_new only fails without a key: {}", error)
+                }
+            };
 
         if let Some(retry_config) = self.retry_config {
             client = client.with_retry_config(retry_config);
@@ -97,6 +116,9 @@ pub struct RetryConfigBuilder {
     max_interval: Option<Duration>,
     multiplier: Option<f64>,
     max_elapsed_time: Option<Duration>,
+    randomization_factor: Option<f64>,
+    repair_on_parse_failure: bool,
+    request_timeout: Option<Duration>,
 }
 
 impl RetryConfigBuilder {
@@ -108,6 +130,9 @@ impl RetryConfigBuilder {
             max_interval: None,
             multiplier: None,
             max_elapsed_time: None,
+            randomization_factor: None,
+            repair_on_parse_failure: false,
+            request_timeout: None,
         }
     }
 
@@ -141,6 +166,24 @@ impl RetryConfigBuilder {
         self
     }
 
+    /// Set the jitter applied to each retry delay (default: 0.5)
+    pub fn randomization_factor(mut self, randomization_factor: f64) -> Self {
+        self.randomization_factor = Some(randomization_factor);
+        self
+    }
+
+    /// Ask the model to repair its output once when it cannot be parsed (default: false)
+    pub fn repair_on_parse_failure(mut self, repair: bool) -> Self {
+        self.repair_on_parse_failure = repair;
+        self
+    }
+
+    /// Give up on any single request after this long (default: no timeout)
+    pub fn request_timeout(mut self, duration: Duration) -> Self {
+        self.request_timeout = Some(duration);
+        self
+    }
+
     /// Finish retry configuration and return to client builder
     pub fn finish(mut self) -> LlmClientBuilder {
         let retry_config = RetryConfig {
@@ -149,6 +192,9 @@ impl RetryConfigBuilder {
             max_interval: self.max_interval.unwrap_or(Duration::from_secs(60)),
             multiplier: self.multiplier.unwrap_or(2.0),
             max_elapsed_time: self.max_elapsed_time.unwrap_or(Duration::from_secs(300)),
+            randomization_factor: self.randomization_factor.unwrap_or(0.5),
+            repair_on_parse_failure: self.repair_on_parse_failure,
+            request_timeout: self.request_timeout,
         };
 
         self.client_builder.retry_config = Some(retry_config);
@@ -166,6 +212,11 @@ impl RetryConfigBuilder {
 pub enum LlmClientBuilderError {
     /// No model was specified
     MissingModel,
+    /// No API key was given and the model's environment variable is unset
+    MissingApiKey {
+        env_var: &'static str,
+        model: models::ModelId,
+    },
 }
 
 impl std::fmt::Display for LlmClientBuilderError {
@@ -174,6 +225,11 @@ impl std::fmt::Display for LlmClientBuilderError {
             LlmClientBuilderError::MissingModel => {
                 write!(f, "Model must be specified when building LlmClient")
             }
+            LlmClientBuilderError::MissingApiKey { env_var, model } => write!(
+                f,
+                "No API key given for the model {:?} and {} is not set",
+                model, env_var
+            ),
         }
     }
 }
@@ -196,6 +252,7 @@ impl LlmClient {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serial_test::serial;
 
     #[test]
     fn test_builder_errors() {
@@ -203,4 +260,32 @@ mod tests {
         let result = LlmClient::builder().build();
         assert!(matches!(result, Err(LlmClientBuilderError::MissingModel)));
     }
+
+    #[test]
+    #[serial]
+    fn test_builder_reports_missing_api_key() {
+        let env_var = models::ModelId::Llama33.api_key_env_var();
+        unsafe { std::env::remove_var(env_var) };
+
+        let result = LlmClient::builder().model(models::ModelId::Llama33).build();
+        assert!(matches!(
+            result,
+            Err(LlmClientBuilderError::MissingApiKey { env_var: missing, .. }) if missing == env_var
+        ));
+    }
+
+    #[test]
+    fn test_base_url_changes_client_id() {
+        let default = LlmClient::with_model(models::ModelId::Gpt4o)
+            .api_key("key")
+            .build()
+            .unwrap();
+        let proxied = LlmClient::with_model(models::ModelId::Gpt4o)
+            .api_key("key")
+            .base_url("http://localhost:4000/v1")
+            .build()
+            .unwrap();
+
+        assert_ne!(default.id(), proxied.id());
+    }
 }
@@ -1,28 +1,44 @@
 pub mod analyser;
 pub mod builder;
-use super::extract_json::{extract_json_aggressively, extract_json_from_response};
+pub mod rate_limit;
+use super::json_repair::{JsonRepairStrategy, default_strategies};
 use super::models;
 use super::simplified_schema::{JsonSchemaConverter, SimplifiedSchema as SimpleSchema};
+use crate::analysis::prompt::PromptTemplates;
 use crate::llm_interface::exceptions::LlmError;
 use backoff::{ExponentialBackoff, backoff::Backoff};
+use futures::{Stream, TryFutureExt, TryStreamExt};
 use llm::{
     builder::{LLMBackend, LLMBuilder},
     chat::{ChatMessage, StructuredOutputFormat},
 };
+use rate_limit::TokenBucket;
 use schemars::{JsonSchema, schema_for};
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::future::Future;
 use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use tokio::time::sleep;
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
 
 #[derive(Clone)]
 pub struct RetryConfig {
+    /// Retries allowed after the initial attempt, so a request is sent at most `1 + max_retries` times
     pub max_retries: u32,
     pub initial_interval: Duration,
     pub max_interval: Duration,
     pub multiplier: f64,
+    /// Stop retrying once this long has passed since the first attempt, even if retries remain
     pub max_elapsed_time: Duration,
+    /// Jitter applied to each delay (0.0 = none, 0.5 = +/-50%) so pooled clients don't retry in lockstep
+    pub randomization_factor: f64,
+    /// Send one follow-up request asking the model to fix a response that fails to parse
+    pub repair_on_parse_failure: bool,
+    /// Give up on a single request after this long; a timeout counts as a retryable server error
+    pub request_timeout: Option<Duration>,
 }
 
 impl Default for RetryConfig {
@@ -33,6 +49,24 @@ impl Default for RetryConfig {
             max_interval: Duration::from_secs(60),
             multiplier: 2.0,
             max_elapsed_time: Duration::from_secs(300), // 5 minutes
+            randomization_factor: 0.5,
+            repair_on_parse_failure: false,
+            request_timeout: None,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Build the exponential backoff schedule described by this config
+    pub fn backoff(&self) -> ExponentialBackoff {
+        ExponentialBackoff {
+            initial_interval: self.initial_interval,
+            current_interval: self.initial_interval,
+            max_interval: self.max_interval,
+            multiplier: self.multiplier,
+            randomization_factor: self.randomization_factor,
+            max_elapsed_time: Some(self.max_elapsed_time),
+            ..Default::default()
         }
     }
 }
@@ -44,98 +78,361 @@ pub struct LlmClient {
     max_tokens: u32,
     temperature: f32,
     retry_config: Option<RetryConfig>,
+    base_url: Option<String>,
+    token_usage: Arc<AtomicU64>,
+    retries: Arc<AtomicU64>,
+    prompt_templates: PromptTemplates,
+    /// Shared by every clone of this client, so the limit covers all of them
+    rate_limiter: Option<Arc<Mutex<TokenBucket>>>,
+    /// Bounds in-flight requests, shared by every clone of this client like `rate_limiter`
+    request_slots: Option<Arc<Semaphore>>,
+    /// Tried in order to turn a structured response into parseable JSON
+    json_repair: Arc<Vec<Box<dyn JsonRepairStrategy>>>,
+}
+
+impl std::fmt::Debug for LlmClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LlmClient")
+            .field("api_key", &"***")
+            .field("model", &self.model)
+            .field("max_tokens", &self.max_tokens)
+            .field("temperature", &self.temperature)
+            .field("base_url", &self.base_url)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Rough token estimate for budgeting, using the common ~4 characters per token heuristic
+pub fn estimate_tokens(text: &str) -> u64 {
+    (text.chars().count() as u64).div_ceil(4)
+}
+
+/// Wait for a permit from `slots`, or return at once when concurrency isn't limited
+async fn acquire_slot(slots: &Option<Arc<Semaphore>>) -> Option<OwnedSemaphorePermit> {
+    let slots = slots.clone()?;
+    Some(
+        slots
+            .acquire_owned()
+            .await
+            .expect("request slot semaphores are never closed"),
+    )
+}
+
+/// Share of a model's context window a request may fill before we warn about truncation
+const CONTEXT_WARNING_RATIO: f64 = 0.9;
+
+/// Whether a prompt of `prompt_tokens` plus room for `max_tokens` of output comes close to
+/// the model's context window. Models with an unknown window never warn.
+fn near_context_window(model: &models::ModelId, prompt_tokens: u64, max_tokens: u32) -> bool {
+    model.context_window().is_some_and(|window| {
+        (prompt_tokens + u64::from(max_tokens)) as f64 > f64::from(window) * CONTEXT_WARNING_RATIO
+    })
 }
 
-fn try_parse<T>(text: &str) -> Result<T, LlmError>
+/// Send `request` until it succeeds, fails with an error that isn't retryable, or has been
+/// retried `config.max_retries` times, i.e. at most `1 + max_retries` calls. The backoff's
+/// `max_elapsed_time` can end the retries sooner. Each retry is counted in `retries`.
+async fn retry_with_backoff<T, F, Fut>(
+    config: &RetryConfig,
+    retries: &AtomicU64,
+    mut request: F,
+) -> Result<T, LlmError>
 where
-    T: DeserializeOwned,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, LlmError>>,
 {
-    let json_candidates: Vec<Option<String>> = vec![
-        Some(text.trim().to_string()),
-        extract_json_from_response(text),
-        extract_json_aggressively(text).first().cloned(),
-    ];
+    let mut backoff = config.backoff();
+    let mut retried = 0;
+
+    loop {
+        let error = match request().await {
+            Ok(result) => return Ok(result),
+            Err(error) => error,
+        };
+
+        if !error.is_retryable() {
+            return Err(error);
+        }
+        if retried >= config.max_retries {
+            error!("Giving up after {} retries: {}", retried, error);
+            return Err(error);
+        }
+        let Some(delay) = backoff.next_backoff() else {
+            error!(
+                "Max elapsed time reached, giving up after {} retries: {}",
+                retried, error
+            );
+            return Err(error);
+        };
+
+        // Never retry sooner than the server asked us to
+        let delay = error.retry_after().map_or(delay, |after| delay.max(after));
+        retried += 1;
+        warn!(
+            "Retry {}/{} after retryable error: {}. Retrying in {:?}",
+            retried, config.max_retries, error, delay
+        );
+        retries.fetch_add(1, Ordering::Relaxed);
+        sleep(delay).await;
+    }
+}
+
+/// Await `request`, failing with a retryable `ServerError` if it outlives `timeout`
+async fn with_timeout<T, E, Fut>(
+    timeout: Option<Duration>,
+    request: Fut,
+    map_err: impl FnOnce(E) -> LlmError,
+) -> Result<T, LlmError>
+where
+    Fut: Future<Output = Result<T, E>>,
+{
+    let result = match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, request)
+            .await
+            .map_err(|_| LlmError::ServerError("timeout".to_string()))?,
+        None => request.await,
+    };
+    result.map_err(map_err)
+}
 
+fn try_parse<T>(text: &str, strategies: &[Box<dyn JsonRepairStrategy>]) -> Result<T, LlmError>
+where
+    T: DeserializeOwned,
+{
     let mut all_errors = Vec::new();
 
-    for (i, candidate) in json_candidates.iter().enumerate() {
-        if let Some(json_text) = candidate {
-            debug!("Trying parse strategy {}: {:.200}...", i + 1, json_text);
-
-            match serde_json::from_str::<T>(json_text) {
-                Ok(parsed) => {
-                    debug!("✅ Successfully parsed with strategy {}", i + 1);
-                    return Ok(parsed);
-                }
-                Err(e) => {
-                    debug!("❌ Strategy {} failed: {}", i + 1, e);
-                    all_errors.push(format!("Strategy {}: {}", i + 1, e));
-                    continue;
-                }
+    for (i, strategy) in strategies.iter().enumerate() {
+        let Some(json_text) = strategy.repair(text) else {
+            debug!(
+                "❌ Strategy {} ({}) returned no candidate",
+                i + 1,
+                strategy.name()
+            );
+            all_errors.push(format!(
+                "Strategy {} ({}): No JSON candidate found",
+                i + 1,
+                strategy.name()
+            ));
+            continue;
+        };
+        debug!(
+            "Trying parse strategy {} ({}): {:.200}...",
+            i + 1,
+            strategy.name(),
+            json_text
+        );
+
+        match serde_json::from_str::<T>(&json_text) {
+            Ok(parsed) => {
+                debug!("✅ Successfully parsed with strategy {}", i + 1);
+                return Ok(parsed);
+            }
+            Err(e) => {
+                debug!("❌ Strategy {} failed: {}", i + 1, e);
+                all_errors.push(format!("Strategy {} ({}): {}", i + 1, strategy.name(), e));
             }
-        } else {
-            debug!("❌ Strategy {} returned no candidate", i + 1);
-            all_errors.push(format!("Strategy {}: No JSON candidate found", i + 1));
         }
     }
 
     // If all strategies failed, return comprehensive error
     let error_summary = format!(
         "Failed to parse JSON with all {} strategies:\n{}",
-        json_candidates.len(),
+        strategies.len(),
         all_errors.join("\n")
     );
     error!("{:}", error_summary);
     Err(LlmError::ResponseParsing(error_summary))
 }
 
+/// Parse `text`, and if every strategy fails hand it to `repair` once and parse the result
+///
+/// The original parse error is returned when the repair request fails or its output still
+/// cannot be parsed, so callers see what the model actually got wrong.
+async fn parse_with_repair<T, F, Fut>(
+    text: &str,
+    strategies: &[Box<dyn JsonRepairStrategy>],
+    repair: Option<F>,
+) -> Result<T, LlmError>
+where
+    T: DeserializeOwned,
+    F: FnOnce(String) -> Fut,
+    Fut: Future<Output = Result<String, LlmError>>,
+{
+    let error = match try_parse::<T>(text, strategies) {
+        Ok(parsed) => return Ok(parsed),
+        Err(error) => error,
+    };
+    let Some(repair) = repair else {
+        return Err(error);
+    };
+
+    warn!("Structured response could not be parsed, requesting a repair");
+    match repair(text.to_string()).await {
+        Ok(repaired) => try_parse::<T>(&repaired, strategies).map_err(|_| error),
+        Err(repair_error) => {
+            warn!("Repair request failed: {}", repair_error);
+            Err(error)
+        }
+    }
+}
+
 impl LlmClient {
+    #[allow(dead_code)]
+    /// A client for `model`, panicking when no key is given and the model's environment
+    /// variable is unset; see `try_new`
     pub fn new(
         model: models::ModelId,
         api_key: Option<String>,
         max_tokens: Option<u32>,
         temperature: Option<f32>,
     ) -> Self {
+        Self::try_new(model, api_key, max_tokens, temperature).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like `new`, but returns an error instead of panicking when no key is configured
+    /// and the model's environment variable is unset
+    pub fn try_new(
+        model: models::ModelId,
+        api_key: Option<String>,
+        max_tokens: Option<u32>,
+        temperature: Option<f32>,
+    ) -> Result<Self, LlmError> {
         let key = match api_key {
             Some(key) => key,
             None => {
-                let env_var = match model.provider() {
-                    llm::builder::LLMBackend::Anthropic => "ANTHROPIC_API_KEY",
-                    llm::builder::LLMBackend::AzureOpenAI => "AZURE_OPENAI_API_KEY",
-                    llm::builder::LLMBackend::DeepSeek => "DEEPSEEK_KEY",
-                    llm::builder::LLMBackend::ElevenLabs => "ELEVEN_LABS_API_KEY",
-                    llm::builder::LLMBackend::Google => "GOOGLE_API_KEY",
-                    llm::builder::LLMBackend::Groq => "GROQ_API_KEY",
-                    llm::builder::LLMBackend::Ollama => "OLLAMA_API_KEY",
-                    llm::builder::LLMBackend::OpenAI => "OPENAI_API_KEY",
-                    llm::builder::LLMBackend::Phind => "PHIND_API_KEY",
-                    llm::builder::LLMBackend::XAI => "XAI_API_KEY",
-                };
-
-                std::env::var(env_var).unwrap_or_else(|_| {
-                    panic!(
-                        "Missing env variable or config value {:?} for the model {:?}",
-                        env_var, model
-                    )
-                })
+                let env_var = model.api_key_env_var();
+                std::env::var(env_var).map_err(|_| LlmError::MissingApiKey {
+                    env_var,
+                    model: model.clone(),
+                })?
             }
         };
 
-        Self {
+        Ok(Self {
             api_key: key,
             model,
             max_tokens: max_tokens.unwrap_or(1500),
             temperature: temperature.unwrap_or(0.5),
             retry_config: None,
-        }
+            base_url: None,
+            token_usage: Arc::new(AtomicU64::new(0)),
+            retries: Arc::new(AtomicU64::new(0)),
+            prompt_templates: PromptTemplates::default(),
+            rate_limiter: None,
+            request_slots: None,
+            json_repair: Arc::new(default_strategies()),
+        })
+    }
+
+    /// Estimated tokens (prompt + response) used by this client and all of its clones
+    pub fn tokens_used(&self) -> u64 {
+        self.token_usage.load(Ordering::Relaxed)
+    }
+
+    /// Requests this client and all of its clones have retried after a retryable error
+    pub fn retries(&self) -> u64 {
+        self.retries.load(Ordering::Relaxed)
+    }
+
+    fn record_usage(&self, system_prompt: &str, content: &str, response: &str) {
+        let tokens =
+            estimate_tokens(system_prompt) + estimate_tokens(content) + estimate_tokens(response);
+        self.token_usage.fetch_add(tokens, Ordering::Relaxed);
     }
 
-    #[allow(dead_code)]
     pub fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
         self.retry_config = Some(retry_config);
         self
     }
 
+    /// Send requests to a custom endpoint (self-hosted gateway or proxy) instead of the provider default
+    pub fn with_base_url(mut self, base_url: Option<String>) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    /// Use these templates, including any house style or per-model override, for analysis prompts
+    pub fn with_prompt_templates(mut self, prompt_templates: PromptTemplates) -> Self {
+        self.prompt_templates = prompt_templates;
+        self
+    }
+
+    /// Send at most `requests_per_minute` requests, waiting before a request rather than
+    /// relying on backoff after the provider rejects it
+    pub fn with_requests_per_minute(mut self, requests_per_minute: Option<u32>) -> Self {
+        self.rate_limiter =
+            requests_per_minute.map(|rpm| Arc::new(Mutex::new(TokenBucket::per_minute(rpm))));
+        self
+    }
+
+    /// Keep at most `max` requests (at least one) in flight across this client and its clones
+    pub fn with_max_concurrent_requests(mut self, max: Option<usize>) -> Self {
+        self.request_slots = max.map(|max| Arc::new(Semaphore::new(max.max(1))));
+        self
+    }
+
+    #[allow(dead_code)]
+    /// Parse structured responses with these strategies, tried in order, instead of the defaults
+    pub fn with_json_repair_strategies(
+        mut self,
+        strategies: Vec<Box<dyn JsonRepairStrategy>>,
+    ) -> Self {
+        self.json_repair = Arc::new(strategies);
+        self
+    }
+
+    /// Wait for a free request slot, if concurrency is limited; the slot is held until the
+    /// permit is dropped
+    async fn request_slot(&self) -> Option<OwnedSemaphorePermit> {
+        acquire_slot(&self.request_slots).await
+    }
+
+    /// Wait for the rate limiter, if one is configured
+    async fn wait_for_rate_limit(&self) {
+        if let Some(bucket) = &self.rate_limiter {
+            rate_limit::acquire(bucket).await;
+        }
+    }
+
+    /// Templates this client builds its analysis prompts from
+    pub fn prompt_templates(&self) -> &PromptTemplates {
+        &self.prompt_templates
+    }
+
+    /// Warn when a request is likely to be truncated by the model's context window
+    fn check_context_window(&self, system_prompt: &str, content: &str) {
+        let prompt_tokens = estimate_tokens(system_prompt) + estimate_tokens(content);
+        if near_context_window(&self.model, prompt_tokens, self.max_tokens) {
+            warn!(
+                "Prompt of ~{} tokens (plus {} for the response) is close to the context window of {}, it may be truncated",
+                prompt_tokens, self.max_tokens, self.model
+            );
+        }
+    }
+
+    /// Per-request timeout from the retry config, if any
+    fn request_timeout(&self) -> Option<Duration> {
+        self.retry_config
+            .as_ref()
+            .and_then(|config| config.request_timeout)
+    }
+
+    /// Start an `LLMBuilder` with the connection settings shared by every request
+    fn llm_builder(&self) -> LLMBuilder {
+        let builder = LLMBuilder::new()
+            .backend(self.model.provider())
+            .api_key(&self.api_key)
+            .model(self.model.to_string())
+            .max_tokens(self.max_tokens)
+            .temperature(self.temperature)
+            .stream(false);
+
+        match &self.base_url {
+            Some(base_url) => builder.base_url(base_url),
+            None => builder,
+        }
+    }
+
     pub async fn get_structured_response_with_retry<T>(
         &self,
         system_prompt: &str,
@@ -147,50 +444,10 @@ impl LlmClient {
         let default_config = RetryConfig::default();
         let retry_config = self.retry_config.as_ref().unwrap_or(&default_config);
 
-        let mut backoff = ExponentialBackoff {
-            initial_interval: retry_config.initial_interval,
-            max_interval: retry_config.max_interval,
-            multiplier: retry_config.multiplier,
-            max_elapsed_time: Some(retry_config.max_elapsed_time),
-            ..Default::default()
-        };
-
-        let mut attempt = 0;
-
-        loop {
-            match self
-                .get_structured_response(system_prompt, user_prompt)
-                .await
-            {
-                Ok(result) => return Ok(result),
-                Err(error) => {
-                    attempt += 1;
-
-                    // Check if we should retry
-                    if !error.is_retryable() || attempt > retry_config.max_retries {
-                        return Err(error);
-                    }
-
-                    // Get next backoff delay
-                    if let Some(delay) = backoff.next_backoff() {
-                        tracing::warn!(
-                            "Attempt {} failed with retryable error: {}. Retrying in {:?}",
-                            attempt,
-                            error,
-                            delay
-                        );
-                        sleep(delay).await;
-                    } else {
-                        // Backoff has given up (max_elapsed_time reached)
-                        tracing::error!(
-                            "Max elapsed time reached, giving up after {} attempts",
-                            attempt
-                        );
-                        return Err(error);
-                    }
-                }
-            }
-        }
+        retry_with_backoff(retry_config, &self.retries, || {
+            self.get_structured_response(system_prompt, user_prompt)
+        })
+        .await
     }
 
     // Update the original method to use the new error categorization
@@ -205,8 +462,6 @@ impl LlmClient {
         let schema = schema_for!(T);
         let mut value_schema = serde_json::to_value(&schema)?;
 
-        let mut prompt = system_prompt.to_string();
-
         let simplified_schema: SimpleSchema = match JsonSchemaConverter::convert(&value_schema) {
             Ok(schema) => schema,
             Err(e) => {
@@ -214,26 +469,24 @@ impl LlmClient {
                 return Err(e.into());
             }
         };
-        value_schema = serde_json::to_value(simplified_schema)?;
-        match self.model.provider() {
-            LLMBackend::Google => {}
-            _ => {
-                prompt = format!(
-                    r#"{}
-CRITICAL INSTRUCTIONS:
-- You MUST respond with ONLY a valid JSON object
-- NO explanatory text before or after the JSON
-- NO markdown code blocks or formatting
-- NO comments or additional content
-- The JSON must exactly match this schema:
+        value_schema = serde_json::to_value(&simplified_schema)?;
+        let repair_prompt = format!(
+            r#"The user message is a response that was supposed to be a JSON object but could not be parsed.
+Reformat it as valid JSON matching this schema, keeping its content unchanged:
 ```json
 {:?}
 ```
-Any response that is not pure JSON will be rejected."#,
-                    system_prompt, value_schema
-                );
-            }
-        }
+Respond with ONLY the JSON object, no explanation or markdown."#,
+            value_schema
+        );
+        let prompt = structured_system_prompt(
+            &self.model.provider(),
+            system_prompt,
+            &simplified_schema,
+            &value_schema,
+        );
+
+        self.check_context_window(&prompt, user_prompt);
 
         let output_schema = StructuredOutputFormat {
             name: T::schema_name(),
@@ -242,14 +495,9 @@ Any response that is not pure JSON will be rejected."#,
             strict: Some(true),
         };
 
-        let builder = LLMBuilder::new()
-            .backend(self.model.provider())
-            .api_key(&self.api_key)
-            .model(self.model.to_string())
-            .max_tokens(self.max_tokens)
-            .temperature(self.temperature)
-            .stream(false)
-            .system(prompt)
+        let builder = self
+            .llm_builder()
+            .system(prompt.clone())
             .schema(output_schema);
 
         let llm = builder
@@ -258,17 +506,29 @@ Any response that is not pure JSON will be rejected."#,
 
         let messages = vec![ChatMessage::user().content(user_prompt).build()];
 
-        let response = llm
-            .chat(&messages)
-            .await
-            .map_err(|e| LlmError::from_error_string(e.to_string()))?; // Use new error categorization
+        let slot = self.request_slot().await;
+        self.wait_for_rate_limit().await;
+        let response = with_timeout(self.request_timeout(), llm.chat(&messages), |e| {
+            LlmError::from_error_string(e.to_string()) // Use new error categorization
+        })
+        .await?;
+        // A repair request below takes a slot of its own
+        drop(slot);
 
         let response_text = response.text().unwrap_or_default();
+        self.record_usage(&prompt, user_prompt, &response_text);
         if response_text.is_empty() {
             return Err(LlmError::ResponseParsing("Empty Response".to_string()));
         }
 
-        try_parse::<T>(response_text.as_str())
+        let repair = self
+            .retry_config
+            .as_ref()
+            .is_some_and(|config| config.repair_on_parse_failure)
+            .then_some(|broken: String| async move {
+                self.get_simple_response(&repair_prompt, &broken).await
+            });
+        parse_with_repair::<T, _, _>(&response_text, &self.json_repair, repair).await
     }
 
     pub async fn get_simple_response(
@@ -276,28 +536,27 @@ Any response that is not pure JSON will be rejected."#,
         system_prompt: &str,
         content: &str,
     ) -> Result<String, LlmError> {
-        let llm = LLMBuilder::new()
-            .backend(self.model.provider())
-            .api_key(&self.api_key)
-            .model(self.model.to_string())
-            .max_tokens(self.max_tokens)
-            .temperature(self.temperature)
-            .stream(false)
+        self.check_context_window(system_prompt, content);
+        let llm = self
+            .llm_builder()
             .system(system_prompt)
             .build()
             .map_err(|e| LlmError::Build(e.to_string()))?;
 
         let messages = vec![ChatMessage::user().content(content).build()];
 
-        let response = llm
-            .chat(&messages)
-            .await
-            .map_err(|e| LlmError::Chat(e.to_string()))?;
+        let _slot = self.request_slot().await;
+        self.wait_for_rate_limit().await;
+        let response = with_timeout(self.request_timeout(), llm.chat(&messages), |e| {
+            LlmError::Chat(e.to_string())
+        })
+        .await?;
 
         // Match the pattern used in get_structured_response for consistency
         let response_text = response
             .text()
             .ok_or_else(|| LlmError::Chat("No text in response".to_string()))?;
+        self.record_usage(system_prompt, content, &response_text);
 
         if response_text.is_empty() {
             return Err(LlmError::ResponseParsing("Empty response".to_string()));
@@ -306,10 +565,64 @@ Any response that is not pure JSON will be rejected."#,
         Ok(response_text.to_string())
     }
 
+    #[allow(dead_code)]
+    /// Send a tiny prompt to check the provider is reachable and answering
+    pub async fn health_check(&self) -> bool {
+        match self.get_simple_response("Reply with OK.", "ping").await {
+            Ok(_) => true,
+            Err(e) => {
+                debug!("Health check failed for {}: {}", self.model, e);
+                false
+            }
+        }
+    }
+
+    /// Stream a plain text response as it is generated, one chunk per item
+    ///
+    /// Errors building the request or opening the stream are yielded as the
+    /// stream's only item.
+    #[allow(dead_code)]
+    pub fn get_simple_response_stream(
+        &self,
+        system_prompt: &str,
+        content: &str,
+    ) -> impl Stream<Item = Result<String, LlmError>> + Send + 'static {
+        let builder = self.llm_builder().stream(true).system(system_prompt);
+        let messages = vec![ChatMessage::user().content(content).build()];
+        self.record_usage(system_prompt, content, "");
+        let token_usage = self.token_usage.clone();
+        let rate_limiter = self.rate_limiter.clone();
+        let request_slots = self.request_slots.clone();
+
+        async move {
+            let slot = acquire_slot(&request_slots).await;
+            if let Some(bucket) = &rate_limiter {
+                rate_limit::acquire(bucket).await;
+            }
+            let llm = builder
+                .build()
+                .map_err(|e| LlmError::Build(e.to_string()))?;
+            let stream = llm
+                .chat_stream(&messages)
+                .await
+                .map_err(|e| LlmError::from_error_string(e.to_string()))?;
+
+            // The slot is held until the stream is dropped
+            Ok(stream
+                .map_err(|e| LlmError::from_error_string(e.to_string()))
+                .inspect_ok(move |chunk| {
+                    let _slot = &slot;
+                    token_usage.fetch_add(estimate_tokens(chunk), Ordering::Relaxed);
+                }))
+        }
+        .try_flatten_stream()
+    }
+
     pub fn id(&self) -> u64 {
         let mut hasher = DefaultHasher::new();
         self.model.hash(&mut hasher);
         self.api_key.hash(&mut hasher);
+        self.base_url.hash(&mut hasher);
         hasher.finish()
     }
 }
@@ -389,12 +702,63 @@ impl LlmClient {
 }
 
 // Example usage with proper error handling
+/// The system prompt for a structured request, telling `provider` how to shape its JSON.
+/// Google enforces the schema itself; small local Ollama models also get an example
+/// response, since they follow a worked example far better than instructions alone.
+fn structured_system_prompt(
+    provider: &LLMBackend,
+    system_prompt: &str,
+    schema: &SimpleSchema,
+    value_schema: &serde_json::Value,
+) -> String {
+    match provider {
+        LLMBackend::Google => system_prompt.to_string(),
+        LLMBackend::Ollama => {
+            let example = serde_json::to_string_pretty(&schema.example_value())
+                .expect("example values always serialize");
+            format!(
+                r#"{}
+CRITICAL INSTRUCTIONS:
+- Respond with a single JSON object and NOTHING else
+- Do NOT write any sentence before or after the JSON
+- Do NOT wrap the JSON in markdown code blocks
+- Use exactly the keys shown in the example, filled in with your own content
+The JSON must match this schema:
+```json
+{:?}
+```
+Example response (structure only, replace every value):
+```json
+{}
+```
+Your entire reply must start with {{ and end with }}."#,
+                system_prompt, value_schema, example
+            )
+        }
+        _ => format!(
+            r#"{}
+CRITICAL INSTRUCTIONS:
+- You MUST respond with ONLY a valid JSON object
+- NO explanatory text before or after the JSON
+- NO markdown code blocks or formatting
+- NO comments or additional content
+- The JSON must exactly match this schema:
+```json
+{:?}
+```
+Any response that is not pure JSON will be rejected."#,
+            system_prompt, value_schema
+        ),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use dotenv::dotenv;
     use schemars::JsonSchema;
     use serde::{Deserialize, Serialize};
+    use serial_test::serial;
 
     #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     struct TaskResponse {
@@ -408,6 +772,232 @@ mod tests {
         confidence: f32,
     }
 
+    #[test]
+    fn test_debug_hides_api_key() {
+        let client = LlmClient::new(
+            models::ModelId::Claude4Sonnet,
+            Some("sk-secret-value".to_string()),
+            None,
+            None,
+        );
+        let debug = format!("{:?}", client);
+        assert!(!debug.contains("sk-secret-value"));
+        assert!(debug.contains("***"));
+    }
+
+    #[test]
+    fn test_retry_config_jitter_applied_to_backoff() {
+        let steady = RetryConfig {
+            randomization_factor: 0.0,
+            ..Default::default()
+        };
+        let jittered = RetryConfig {
+            randomization_factor: 0.8,
+            ..Default::default()
+        };
+
+        assert_eq!(steady.backoff().randomization_factor, 0.0);
+        assert_eq!(jittered.backoff().randomization_factor, 0.8);
+
+        // Without jitter the first delay is exactly the initial interval
+        let mut backoff = steady.backoff();
+        assert_eq!(backoff.next_backoff(), Some(steady.initial_interval));
+    }
+
+    #[tokio::test]
+    async fn test_max_retries_caps_total_calls() {
+        for max_retries in [0, 1, 3] {
+            let config = RetryConfig {
+                max_retries,
+                initial_interval: Duration::ZERO,
+                randomization_factor: 0.0,
+                ..Default::default()
+            };
+            let calls = AtomicU64::new(0);
+            let retries = AtomicU64::new(0);
+
+            let result = retry_with_backoff(&config, &retries, || async {
+                calls.fetch_add(1, Ordering::Relaxed);
+                Err::<(), _>(LlmError::ServerError("503 Service Unavailable".to_string()))
+            })
+            .await;
+
+            assert!(matches!(result, Err(LlmError::ServerError(_))));
+            assert_eq!(calls.load(Ordering::Relaxed), 1 + u64::from(max_retries));
+            assert_eq!(retries.load(Ordering::Relaxed), u64::from(max_retries));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_non_retryable_errors_are_not_retried() {
+        let config = RetryConfig {
+            initial_interval: Duration::ZERO,
+            ..Default::default()
+        };
+        let calls = AtomicU64::new(0);
+        let retries = AtomicU64::new(0);
+
+        let result = retry_with_backoff(&config, &retries, || async {
+            calls.fetch_add(1, Ordering::Relaxed);
+            Err::<(), _>(LlmError::Chat("401 Unauthorized".to_string()))
+        })
+        .await;
+
+        assert!(matches!(result, Err(LlmError::Chat(_))));
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+        assert_eq!(retries.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_ollama_prompt_includes_example() {
+        let value_schema = serde_json::to_value(schema_for!(TaskResponse)).unwrap();
+        let schema = JsonSchemaConverter::convert(&value_schema).unwrap();
+        let value_schema = serde_json::to_value(&schema).unwrap();
+
+        let ollama =
+            structured_system_prompt(&LLMBackend::Ollama, "Do the task", &schema, &value_schema);
+        assert!(ollama.starts_with("Do the task"));
+        assert!(ollama.contains("Example response"));
+        assert!(ollama.contains(r#""message": "A short description of how you would do the task"#));
+
+        let hosted =
+            structured_system_prompt(&LLMBackend::OpenAI, "Do the task", &schema, &value_schema);
+        assert!(hosted.contains("ONLY a valid JSON object"));
+        assert!(!hosted.contains("Example response"));
+
+        assert_eq!(
+            structured_system_prompt(&LLMBackend::Google, "Do the task", &schema, &value_schema),
+            "Do the task"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_try_new_reports_missing_key() {
+        let env_var = models::ModelId::Llama33.api_key_env_var();
+        unsafe { std::env::remove_var(env_var) };
+
+        let error = LlmClient::try_new(models::ModelId::Llama33, None, None, None).unwrap_err();
+        assert!(matches!(error, LlmError::MissingApiKey { .. }));
+        assert!(error.to_string().contains(env_var));
+
+        assert!(
+            LlmClient::try_new(models::ModelId::Llama33, Some("key".into()), None, None).is_ok()
+        );
+    }
+
+    #[test]
+    fn test_near_context_window() {
+        let model = models::ModelId::Phi3; // 4096 token window
+        assert!(!near_context_window(&model, 2000, 1500));
+        assert!(near_context_window(&model, 2500, 1500));
+        assert!(!near_context_window(
+            &models::ModelId::ElevenFlashV25,
+            u64::MAX / 2,
+            1500
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_request_slots_are_shared_by_clones() {
+        let client = LlmClient::new(models::ModelId::Gpt4o, Some("key".to_string()), None, None)
+            .with_max_concurrent_requests(Some(2));
+        let in_flight = Arc::new(AtomicU64::new(0));
+        let peak = Arc::new(AtomicU64::new(0));
+
+        // Each clone stands in for a copy held by a different pool
+        let requests = (0..8).map(|_| {
+            let client = client.clone();
+            let in_flight = in_flight.clone();
+            let peak = peak.clone();
+            async move {
+                let _slot = client.request_slot().await;
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                sleep(Duration::from_millis(10)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+            }
+        });
+        futures::future::join_all(requests).await;
+
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout_is_retryable() {
+        let slow = async {
+            sleep(Duration::from_secs(5)).await;
+            Ok::<_, LlmError>("too late")
+        };
+        let error = with_timeout(Some(Duration::from_millis(10)), slow, |e| e)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, LlmError::ServerError(ref msg) if msg == "timeout"));
+        assert!(error.is_retryable());
+
+        // Without a timeout the request runs to completion
+        let fast = async { Ok::<_, LlmError>("done") };
+        assert_eq!(with_timeout(None, fast, |e| e).await.unwrap(), "done");
+    }
+
+    #[test]
+    fn test_try_parse_runs_the_configured_strategies() {
+        let trailing = r#"Sure: {"success": true, "message": "ok", "confidence": 0.9,}"#;
+        let parsed: TaskResponse = try_parse(trailing, &default_strategies()).unwrap();
+        assert_eq!(parsed.message, "ok");
+
+        let trimmed_only: Vec<Box<dyn JsonRepairStrategy>> =
+            vec![Box::new(crate::llm_interface::json_repair::Trimmed)];
+        assert!(matches!(
+            try_parse::<TaskResponse>(trailing, &trimmed_only),
+            Err(LlmError::ResponseParsing(_))
+        ));
+    }
+
+    #[test]
+    fn test_try_parse_tolerates_trailing_commas_and_single_quotes() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Single {
+            a: u32,
+        }
+
+        for lenient in ["{\"a\":1,}", "{'a':1}"] {
+            let parsed: Single = try_parse(lenient, &default_strategies()).unwrap();
+            assert_eq!(parsed, Single { a: 1 });
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parse_with_repair_fixes_almost_json() {
+        let almost = r#"{"success": true, "message": "ok", "confidence": 0.9,"#;
+        let repair = Some(|broken: String| async move {
+            assert!(broken.starts_with(r#"{"success""#));
+            Ok(r#"{"success": true, "message": "ok", "confidence": 0.9}"#.to_string())
+        });
+
+        let parsed: TaskResponse = parse_with_repair(almost, &default_strategies(), repair)
+            .await
+            .unwrap();
+        assert!(parsed.success);
+        assert_eq!(parsed.message, "ok");
+    }
+
+    #[tokio::test]
+    async fn test_parse_with_repair_gives_up_after_one_attempt() {
+        let almost = r#"{"success": true, "message": "ok""#;
+        let repaired_once = Some(|_: String| async { Ok("still not json".to_string()) });
+        let result =
+            parse_with_repair::<TaskResponse, _, _>(almost, &default_strategies(), repaired_once)
+                .await;
+        assert!(matches!(result, Err(LlmError::ResponseParsing(_))));
+
+        type Repair = fn(String) -> std::future::Ready<Result<String, LlmError>>;
+        let disabled: Option<Repair> = None;
+        let result =
+            parse_with_repair::<TaskResponse, _, _>(almost, &default_strategies(), disabled).await;
+        assert!(matches!(result, Err(LlmError::ResponseParsing(_))));
+    }
+
     #[tokio::test]
     async fn test_structured_response() -> Result<(), LlmError> {
         dotenv().ok();
@@ -439,4 +1029,26 @@ mod tests {
         println!("Simple response: {}", response);
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_simple_response_stream() -> Result<(), LlmError> {
+        dotenv().ok();
+        if std::env::var("ANTHROPIC_API_KEY").is_err() {
+            return Ok(());
+        }
+        let client = LlmClient::new(models::ModelId::Claude35Haiku, None, None, None);
+
+        let chunks: Vec<String> = client
+            .get_simple_response_stream(
+                "You are a helpful assistant.",
+                "What is the capital of France?",
+            )
+            .try_collect()
+            .await?;
+
+        let response = chunks.concat();
+        println!("Streamed response: {}", response);
+        assert!(!response.is_empty());
+        Ok(())
+    }
 }
@@ -0,0 +1,96 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tokio::time::sleep;
+
+/// Token bucket that spaces out requests to stay under a provider's rate limit
+///
+/// The bucket holds at most one token, so requests are spread evenly across the
+/// minute rather than sent in a burst at the start of it.
+#[derive(Debug)]
+pub struct TokenBucket {
+    tokens: f64,
+    capacity: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// A bucket allowing `requests_per_minute` requests (at least one)
+    pub fn per_minute(requests_per_minute: u32) -> Self {
+        Self {
+            tokens: 1.0,
+            capacity: 1.0,
+            refill_per_sec: f64::from(requests_per_minute.max(1)) / 60.0,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Take a token if one is available, otherwise how long until one will be
+    fn try_take(&mut self, now: Instant) -> Result<(), Duration> {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - self.tokens) / self.refill_per_sec,
+            ))
+        }
+    }
+}
+
+/// Wait until `bucket` has a token for one request, then take it
+///
+/// The lock is only held while checking the bucket, so waiting callers don't block each other.
+pub async fn acquire(bucket: &Mutex<TokenBucket>) {
+    loop {
+        let wait = match bucket
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .try_take(Instant::now())
+        {
+            Ok(()) => return,
+            Err(wait) => wait,
+        };
+        sleep(wait).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::future::join_all;
+
+    #[test]
+    fn test_bucket_refills_at_rate() {
+        let mut bucket = TokenBucket::per_minute(60);
+        let start = bucket.last_refill;
+
+        assert_eq!(bucket.try_take(start), Ok(()));
+        let wait = bucket.try_take(start).unwrap_err();
+        assert!((wait.as_secs_f64() - 1.0).abs() < 1e-9);
+
+        assert!(bucket.try_take(start + Duration::from_millis(500)).is_err());
+        assert_eq!(bucket.try_take(start + Duration::from_secs(1)), Ok(()));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_are_spaced() {
+        // 6000 requests per minute is one every 10ms
+        let bucket = Mutex::new(TokenBucket::per_minute(6000));
+        let start = Instant::now();
+
+        join_all((0..5).map(|_| acquire(&bucket))).await;
+
+        // The first request goes straight through, each of the other four waits its turn
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(40), "took {:?}", elapsed);
+        assert!(elapsed < Duration::from_secs(1), "took {:?}", elapsed);
+    }
+}
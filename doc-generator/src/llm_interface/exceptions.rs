@@ -1,5 +1,32 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::time::{Duration, SystemTime};
+
+use super::models::ModelId;
 use super::simplified_schema;
 
+// Matches Retry-After values in header ("Retry-After: 30"), key=value and JSON forms
+static RETRY_AFTER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)retry[-_ ]after"?\s*[:=]?\s*"?([^"\r\n,]+(?:,[^"\r\n]+GMT)?)"#).unwrap()
+});
+
+// A 4xx status code, which means the server answered and the request itself was rejected
+static CLIENT_ERROR_STATUS: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b4\d{2}\b").unwrap());
+
+// Transport failures where the request may not have reached the server at all
+const NETWORK_ERROR_MARKERS: &[&str] = &[
+    "connection reset",
+    "connection refused",
+    "connection closed",
+    "connection aborted",
+    "error sending request",
+    "dns error",
+    "failed to lookup address",
+    "timed out",
+    "broken pipe",
+    "unexpected eof",
+];
+
 #[derive(Debug, thiserror::Error)]
 pub enum LlmError {
     #[error("Schema serialization error: {0}")]
@@ -12,16 +39,38 @@ pub enum LlmError {
     Chat(String),
     #[error("Response parsing error: {0}")]
     ResponseParsing(String),
-    #[error("Rate limit exceeded (429): {0}")]
-    RateLimit(String),
+    #[error("Rate limit exceeded (429): {message}")]
+    RateLimit {
+        message: String,
+        /// How long the server asked us to wait, from a Retry-After header
+        retry_after: Option<Duration>,
+    },
     #[error("Server error (5xx): {0}")]
     ServerError(String),
+    #[error("Network error: {0}")]
+    Network(String),
+    #[error("Missing env variable or config value {env_var:?} for the model {model:?}")]
+    MissingApiKey {
+        env_var: &'static str,
+        model: ModelId,
+    },
 }
 
 impl LlmError {
     /// Determines if this error is retryable
     pub fn is_retryable(&self) -> bool {
-        matches!(self, LlmError::RateLimit(_) | LlmError::ServerError(_))
+        matches!(
+            self,
+            LlmError::RateLimit { .. } | LlmError::ServerError(_) | LlmError::Network(_)
+        )
+    }
+
+    /// Minimum wait requested by the server before retrying, if any
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            LlmError::RateLimit { retry_after, .. } => *retry_after,
+            _ => None,
+        }
     }
 
     /// Creates an LlmError from a generic error string, detecting specific error types
@@ -36,7 +85,10 @@ impl LlmError {
             || error_lower.contains("requests per minute")
             || error_lower.contains("requests per hour")
         {
-            return LlmError::RateLimit(error);
+            return LlmError::RateLimit {
+                retry_after: parse_retry_after(&error),
+                message: error,
+            };
         }
 
         // Check for server errors (5xx)
@@ -53,13 +105,39 @@ impl LlmError {
             return LlmError::ServerError(error);
         }
 
+        // Check for transient connection failures, unless the server rejected the request (4xx)
+        if !CLIENT_ERROR_STATUS.is_match(&error_lower)
+            && NETWORK_ERROR_MARKERS
+                .iter()
+                .any(|marker| error_lower.contains(marker))
+        {
+            return LlmError::Network(error);
+        }
+
         // Default to Chat error for other cases
         LlmError::Chat(error)
     }
 }
 
+/// Extract a Retry-After value, given either as delay seconds or an HTTP-date
+pub fn parse_retry_after(error: &str) -> Option<Duration> {
+    let value = RETRY_AFTER.captures(error)?.get(1)?.as_str().trim();
+
+    let seconds: String = value.chars().take_while(|c| c.is_ascii_digit()).collect();
+    if !seconds.is_empty() {
+        return seconds.parse().ok().map(Duration::from_secs);
+    }
+
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
     use crate::llm_interface::exceptions::LlmError;
 
     #[test]
@@ -67,18 +145,18 @@ mod tests {
         // Test rate limit detection - your specific error format
         assert!(matches!(
             LlmError::from_error_string("Chat error: HTTP Error: HTTP status client error (429 Too Many Requests) for url (https://api.anthropic.com/v1/messages)".to_string()),
-            LlmError::RateLimit(_)
+            LlmError::RateLimit { .. }
         ));
 
         // Test other rate limit formats
         assert!(matches!(
             LlmError::from_error_string("HTTP 429 Too Many Requests".to_string()),
-            LlmError::RateLimit(_)
+            LlmError::RateLimit { .. }
         ));
 
         assert!(matches!(
             LlmError::from_error_string("Rate limit exceeded".to_string()),
-            LlmError::RateLimit(_)
+            LlmError::RateLimit { .. }
         ));
 
         // Test server error detection
@@ -108,4 +186,70 @@ mod tests {
             LlmError::Chat(_)
         ));
     }
+
+    #[test]
+    fn test_network_error_detection() {
+        let error = LlmError::from_error_string(
+            "Chat error: HTTP Error: error sending request: connection reset by peer".to_string(),
+        );
+        assert!(matches!(error, LlmError::Network(_)));
+        assert!(error.is_retryable());
+
+        for message in [
+            "dns error: failed to lookup address information",
+            "operation timed out",
+            "Broken pipe (os error 32)",
+        ] {
+            assert!(LlmError::from_error_string(message.to_string()).is_retryable());
+        }
+
+        // A rejected request stays fatal even if the connection is mentioned
+        for message in [
+            "401 Unauthorized",
+            "HTTP status client error (401 Unauthorized): connection closed",
+        ] {
+            let error = LlmError::from_error_string(message.to_string());
+            assert!(matches!(error, LlmError::Chat(_)));
+            assert!(!error.is_retryable());
+        }
+    }
+
+    #[test]
+    fn test_retry_after_seconds() {
+        let error =
+            LlmError::from_error_string("HTTP 429 Too Many Requests\nRetry-After: 30".to_string());
+        assert!(matches!(error, LlmError::RateLimit { .. }));
+        assert_eq!(error.retry_after(), Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn test_retry_after_http_date() {
+        let at = SystemTime::now() + Duration::from_secs(120);
+        let error = LlmError::from_error_string(format!(
+            "429 Too Many Requests, Retry-After: {}",
+            httpdate::fmt_http_date(at)
+        ));
+
+        let wait = error.retry_after().unwrap();
+        assert!(wait > Duration::from_secs(100) && wait <= Duration::from_secs(120));
+
+        // Dates in the past mean retry immediately
+        assert_eq!(
+            parse_retry_after("Retry-After: Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_rate_limit_without_retry_after() {
+        let error = LlmError::from_error_string("HTTP 429 Too Many Requests".to_string());
+        assert!(matches!(
+            error,
+            LlmError::RateLimit {
+                retry_after: None,
+                ..
+            }
+        ));
+        assert!(error.is_retryable());
+    }
 }
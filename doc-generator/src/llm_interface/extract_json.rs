@@ -1,30 +1,83 @@
 use once_cell::sync::Lazy;
 use regex::Regex;
+use std::ops::Range;
 
 // Regex patterns for extracting JSON from various formats
 static JSON_CODE_BLOCK: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"```(?:json)?\s*(\{.*?\})\s*```").unwrap());
 
-static JSON_OBJECT: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{.*\}").unwrap());
+static JSON_ARRAY_CODE_BLOCK: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"```(?:json)?\s*(\[.*?\])\s*```").unwrap());
 
 /// Attempts to extract valid JSON from LLM response text
+///
+/// Objects are preferred over arrays, unless the object sits inside the array
+/// (a list of objects).
 pub fn extract_json_from_response(text: &str) -> Option<String> {
     // First, try to extract from code blocks
-    if let Some(captures) = JSON_CODE_BLOCK.captures(text) {
-        if let Some(json_match) = captures.get(1) {
+    for code_block in [&JSON_CODE_BLOCK, &JSON_ARRAY_CODE_BLOCK] {
+        if let Some(json_match) = code_block.captures(text).and_then(|c| c.get(1)) {
             return Some(json_match.as_str().to_string());
         }
     }
 
-    // If no code block, look for the first complete JSON object
-    if let Some(json_match) = JSON_OBJECT.find(text) {
-        return Some(json_match.as_str().to_string());
+    // If no code block, look for the first balanced JSON object or array
+    let object = balanced(text, '{', '}').next();
+    let array = balanced(text, '[', ']').next();
+    let span = match (object, array) {
+        (Some(object), Some(array)) if array.start < object.start && array.end >= object.end => {
+            Some(array)
+        }
+        (object, array) => object.or(array),
+    };
+    if let Some(span) = span {
+        return Some(text[span].to_string());
     }
 
     // If still nothing, try the original text (maybe it's already clean JSON)
     None
 }
 
+/// Byte ranges of each top-level span of `text` from `open` to its matching `close`
+///
+/// Brackets inside JSON strings are ignored, and spans may cover several lines. An
+/// `open` that is never closed is skipped, so the search carries on right after it.
+fn balanced(text: &str, open: char, close: char) -> impl Iterator<Item = Range<usize>> + '_ {
+    let mut position = 0;
+    std::iter::from_fn(move || {
+        loop {
+            let start = position + text[position..].find(open)?;
+            let mut depth = 0;
+            let mut in_string = false;
+            let mut escaped = false;
+
+            for (i, ch) in text[start..].char_indices() {
+                if in_string {
+                    match ch {
+                        _ if escaped => escaped = false,
+                        '\\' => escaped = true,
+                        '"' => in_string = false,
+                        _ => {}
+                    }
+                } else if ch == '"' {
+                    in_string = true;
+                } else if ch == open {
+                    depth += 1;
+                } else if ch == close {
+                    depth -= 1;
+                    if depth == 0 {
+                        let end = start + i + ch.len_utf8();
+                        position = end;
+                        return Some(start..end);
+                    }
+                }
+            }
+            position = start + open.len_utf8();
+        }
+    })
+}
+
+/// Every plausible JSON candidate in `text`, objects before arrays
 pub fn extract_json_aggressively(text: &str) -> Vec<String> {
     let mut candidates = Vec::new();
 
@@ -35,38 +88,24 @@ pub fn extract_json_aggressively(text: &str) -> Vec<String> {
         }
     }
 
-    // Strategy 2: All JSON objects in text
-    for json_match in JSON_OBJECT.find_iter(text) {
-        candidates.push(json_match.as_str().to_string());
+    // Strategy 2: Every balanced JSON object in text
+    for span in balanced(text, '{', '}') {
+        candidates.push(text[span].to_string());
     }
 
-    // Strategy 3: Try to find JSON by looking for balanced braces
-    if let Some(start) = text.find('{') {
-        let mut brace_count = 0;
-        let mut end_pos = start;
-
-        for (i, ch) in text[start..].char_indices() {
-            match ch {
-                '{' => brace_count += 1,
-                '}' => {
-                    brace_count -= 1;
-                    if brace_count == 0 {
-                        end_pos = start + i + 1;
-                        break;
-                    }
-                }
-                _ => {}
-            }
-        }
-
-        if brace_count == 0 && end_pos > start {
-            candidates.push(text[start..end_pos].to_string());
+    // Strategy 3: The same again for arrays
+    for captures in JSON_ARRAY_CODE_BLOCK.captures_iter(text) {
+        if let Some(json_match) = captures.get(1) {
+            candidates.push(json_match.as_str().to_string());
         }
     }
+    for span in balanced(text, '[', ']') {
+        candidates.push(text[span].to_string());
+    }
 
-    // Remove duplicates and return
-    candidates.sort();
-    candidates.dedup();
+    // Remove duplicates, keeping the first occurrence so objects stay ahead
+    let mut seen = std::collections::HashSet::new();
+    candidates.retain(|candidate| seen.insert(candidate.clone()));
     candidates
 }
 
@@ -105,4 +144,80 @@ The result is: {"name": "test", "value": 42}
             Some(r#"{"name": "test", "value": 42}"#.to_string())
         );
     }
+
+    #[test]
+    fn test_json_array_extraction() {
+        let expected = r#"[{"name": "a"}, {"name": "b"}]"#;
+
+        let in_prose = format!("The items are: {} as requested.", expected);
+        assert_eq!(
+            extract_json_from_response(&in_prose),
+            Some(expected.to_string())
+        );
+
+        let fenced = format!("Here you go:\n```json\n{}\n```\nDone.", expected);
+        assert_eq!(
+            extract_json_from_response(&fenced),
+            Some(expected.to_string())
+        );
+
+        let candidates = extract_json_aggressively(&in_prose);
+        assert!(candidates.contains(&expected.to_string()));
+    }
+
+    #[test]
+    fn test_objects_prioritized_over_arrays() {
+        let response = r#"Tags ["x", "y"] then {"name": "test", "tags": ["x"]}"#;
+        let object = r#"{"name": "test", "tags": ["x"]}"#;
+
+        assert_eq!(
+            extract_json_from_response(response),
+            Some(object.to_string())
+        );
+        assert_eq!(
+            extract_json_aggressively(response).first(),
+            Some(&object.to_string())
+        );
+    }
+
+    #[test]
+    fn test_multiline_json_in_prose() {
+        let response = "The result is:\n{\n  \"name\": \"test\",\n  \"value\": 42\n}\nThanks!";
+
+        assert_eq!(
+            extract_json_from_response(response),
+            Some("{\n  \"name\": \"test\",\n  \"value\": 42\n}".to_string())
+        );
+    }
+
+    #[test]
+    fn test_adjacent_objects_not_merged() {
+        let response = r#"First {"name": "a", "note": "has } brace"} then {"name": "b"}"#;
+
+        assert_eq!(
+            extract_json_from_response(response),
+            Some(r#"{"name": "a", "note": "has } brace"}"#.to_string())
+        );
+        assert_eq!(
+            extract_json_aggressively(response),
+            vec![
+                r#"{"name": "a", "note": "has } brace"}"#.to_string(),
+                r#"{"name": "b"}"#.to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unclosed_brace_does_not_hide_later_json() {
+        let response = r#"use {x to wrap the result: {"a":1}"#;
+
+        assert_eq!(
+            extract_json_from_response(response),
+            Some(r#"{"a":1}"#.to_string())
+        );
+        assert_eq!(
+            extract_json_aggressively(response),
+            vec![r#"{"a":1}"#.to_string()]
+        );
+    }
 }
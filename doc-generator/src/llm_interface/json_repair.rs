@@ -0,0 +1,220 @@
+use super::extract_json::{extract_json_aggressively, extract_json_from_response};
+
+/// One way of turning an LLM response into text that might parse as JSON
+///
+/// Strategies are tried in order until one yields text that deserializes, so cheap and
+/// conservative strategies should come before ones that rewrite the response.
+pub trait JsonRepairStrategy: Send + Sync {
+    /// Short name used when logging which strategy was tried
+    fn name(&self) -> &'static str;
+
+    /// A JSON candidate from `text`, or `None` if this strategy finds nothing to offer
+    fn repair(&self, text: &str) -> Option<String>;
+}
+
+/// The response as-is, minus surrounding whitespace
+pub struct Trimmed;
+
+impl JsonRepairStrategy for Trimmed {
+    fn name(&self) -> &'static str {
+        "trimmed"
+    }
+
+    fn repair(&self, text: &str) -> Option<String> {
+        Some(text.trim().to_string())
+    }
+}
+
+/// The JSON in a code block, or the first balanced object or array in the response
+pub struct Extracted;
+
+impl JsonRepairStrategy for Extracted {
+    fn name(&self) -> &'static str {
+        "extracted"
+    }
+
+    fn repair(&self, text: &str) -> Option<String> {
+        extract_json_from_response(text)
+    }
+}
+
+/// The first candidate found by aggressive extraction
+pub struct AggressiveFirst;
+
+impl JsonRepairStrategy for AggressiveFirst {
+    fn name(&self) -> &'static str {
+        "aggressive-first"
+    }
+
+    fn repair(&self, text: &str) -> Option<String> {
+        extract_json_aggressively(text).into_iter().next()
+    }
+}
+
+/// The extracted JSON (or the whole response) with commas before `}` or `]` removed
+pub struct StripTrailingCommas;
+
+impl JsonRepairStrategy for StripTrailingCommas {
+    fn name(&self) -> &'static str {
+        "strip-trailing-commas"
+    }
+
+    fn repair(&self, text: &str) -> Option<String> {
+        let json = extract_json_from_response(text).unwrap_or_else(|| text.trim().to_string());
+        let stripped = strip_trailing_commas(&json);
+        (stripped != json).then_some(stripped)
+    }
+}
+
+/// The extracted JSON (or the whole response) with single-quoted strings double-quoted,
+/// and any trailing commas removed
+///
+/// Double-quoted strings are copied untouched, so apostrophes in them survive. An
+/// apostrophe inside a single-quoted string still ends it, so the result may not parse.
+pub struct SingleQuotes;
+
+impl JsonRepairStrategy for SingleQuotes {
+    fn name(&self) -> &'static str {
+        "single-quotes"
+    }
+
+    fn repair(&self, text: &str) -> Option<String> {
+        let json = extract_json_from_response(text).unwrap_or_else(|| text.trim().to_string());
+        let converted = strip_trailing_commas(&double_quote_strings(&json));
+        (converted != json).then_some(converted)
+    }
+}
+
+/// Rewrite `'...'` strings outside double-quoted strings as `"..."`, escaping the double
+/// quotes they contain and unescaping their `\'`
+fn double_quote_strings(json: &str) -> String {
+    let mut converted = String::with_capacity(json.len());
+    let mut chars = json.chars();
+    let mut in_double = false;
+    let mut in_single = false;
+    let mut escaped = false;
+
+    while let Some(ch) = chars.next() {
+        if in_double {
+            match ch {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_double = false,
+                _ => {}
+            }
+            converted.push(ch);
+        } else if in_single {
+            match ch {
+                '\\' => match chars.next() {
+                    Some('\'') => converted.push('\''),
+                    Some(next) => {
+                        converted.push('\\');
+                        converted.push(next);
+                    }
+                    None => converted.push('\\'),
+                },
+                '"' => converted.push_str("\\\""),
+                '\'' => {
+                    in_single = false;
+                    converted.push('"');
+                }
+                _ => converted.push(ch),
+            }
+        } else {
+            match ch {
+                '"' => in_double = true,
+                '\'' => in_single = true,
+                _ => {}
+            }
+            converted.push(if ch == '\'' { '"' } else { ch });
+        }
+    }
+    converted
+}
+
+/// Remove commas that only have whitespace between them and a closing bracket,
+/// leaving anything inside strings alone
+fn strip_trailing_commas(json: &str) -> String {
+    let mut stripped = String::with_capacity(json.len());
+    let mut chars = json.chars();
+    let mut in_string = false;
+    let mut escaped = false;
+
+    while let Some(ch) = chars.next() {
+        if in_string {
+            match ch {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+        } else if ch == '"' {
+            in_string = true;
+        } else if ch == ',' {
+            let rest = chars.clone().find(|next| !next.is_whitespace());
+            if matches!(rest, Some('}' | ']')) {
+                continue;
+            }
+        }
+        stripped.push(ch);
+    }
+    stripped
+}
+
+/// The built-in strategies, in the order they are tried
+pub fn default_strategies() -> Vec<Box<dyn JsonRepairStrategy>> {
+    vec![
+        Box::new(Trimmed),
+        Box::new(Extracted),
+        Box::new(AggressiveFirst),
+        Box::new(StripTrailingCommas),
+        Box::new(SingleQuotes),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trimmed() {
+        assert_eq!(Trimmed.repair("  {\"a\": 1}\n").unwrap(), "{\"a\": 1}");
+    }
+
+    #[test]
+    fn test_extracted() {
+        let text = "Here you go:\n```json\n{\"a\": 1}\n```\nThanks";
+        assert_eq!(Extracted.repair(text).unwrap(), "{\"a\": 1}");
+        assert!(Extracted.repair("no json here").is_none());
+    }
+
+    #[test]
+    fn test_aggressive_first() {
+        let text = "First {\"a\": 1} then {\"b\": 2}";
+        assert_eq!(AggressiveFirst.repair(text).unwrap(), "{\"a\": 1}");
+        assert!(AggressiveFirst.repair("nothing").is_none());
+    }
+
+    #[test]
+    fn test_strip_trailing_commas() {
+        let text = "Result: {\"items\": [1, 2, ], \"note\": \"a, }\",\n}";
+        assert_eq!(
+            StripTrailingCommas.repair(text).unwrap(),
+            "{\"items\": [1, 2 ], \"note\": \"a, }\"\n}"
+        );
+        // Nothing to strip means nothing new to try
+        assert!(StripTrailingCommas.repair("{\"a\": 1}").is_none());
+    }
+
+    #[test]
+    fn test_single_quotes() {
+        assert_eq!(SingleQuotes.repair("{'a':1}").unwrap(), "{\"a\":1}");
+        assert_eq!(
+            SingleQuotes
+                .repair(r#"{'quote': 'say "hi"', 'it\'s': "don't", 'list': ['x',],}"#)
+                .unwrap(),
+            r#"{"quote": "say \"hi\"", "it's": "don't", "list": ["x"]}"#
+        );
+        assert!(SingleQuotes.repair("{\"a\": \"don't\"}").is_none());
+    }
+}
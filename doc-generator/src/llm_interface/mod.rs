@@ -1,6 +1,7 @@
 pub mod client;
 pub mod exceptions;
 pub mod extract_json;
+pub mod json_repair;
 pub mod models;
 pub mod pool;
 mod simplified_schema;
@@ -160,6 +160,108 @@ impl ModelId {
         }
     }
 
+    /// The provider for this model, by its config and CLI name
+    pub fn provider_enum(&self) -> Provider {
+        self.provider().into()
+    }
+
+    /// Environment variable read for the API key when none is configured
+    pub fn api_key_env_var(&self) -> &'static str {
+        provider_api_key_env_var(&self.provider())
+    }
+
+    /// Returns the (input, output) price in USD per 1K tokens, if known
+    ///
+    /// Local Ollama models are free; voice models aren't priced per token.
+    pub fn pricing(&self) -> Option<(f64, f64)> {
+        match self {
+            Self::Gpt4o => Some((0.0025, 0.01)),
+            Self::Gpt4oMini => Some((0.00015, 0.0006)),
+            Self::Gpt41 => Some((0.002, 0.008)),
+            Self::Gpt41Mini => Some((0.0004, 0.0016)),
+            Self::Gpt41Nano => Some((0.0001, 0.0004)),
+            Self::O3 => Some((0.002, 0.008)),
+            Self::O3Mini | Self::O4Mini => Some((0.0011, 0.0044)),
+            Self::Gpt4Turbo => Some((0.01, 0.03)),
+            Self::Gpt35Turbo => Some((0.0005, 0.0015)),
+
+            Self::Claude35Sonnet | Self::Claude37Sonnet | Self::Claude4Sonnet => {
+                Some((0.003, 0.015))
+            }
+            Self::Claude35Haiku => Some((0.0008, 0.004)),
+            Self::Claude3Opus | Self::Claude4Opus => Some((0.015, 0.075)),
+
+            Self::Gemini15Pro => Some((0.00125, 0.005)),
+            Self::Gemini15Flash => Some((0.000075, 0.0003)),
+            Self::Gemini15Flash8b => Some((0.0000375, 0.00015)),
+            Self::Gemini20FlashExp => Some((0.0001, 0.0004)),
+            Self::Gemini25Pro => Some((0.00125, 0.01)),
+            Self::Gemini25Flash => Some((0.00015, 0.0006)),
+
+            Self::DeepseekChat | Self::DeepseekCoder => Some((0.00027, 0.0011)),
+            Self::DeepseekReason => Some((0.00055, 0.00219)),
+
+            Self::Grok3 | Self::Grok3Reasoning => Some((0.003, 0.015)),
+            Self::Grok3Mini | Self::Grok3MiniReasoning => Some((0.0003, 0.0005)),
+            Self::Grok2 => Some((0.002, 0.01)),
+            Self::Grok1 => None,
+
+            Self::Llama33
+            | Self::Llama32
+            | Self::Codellama
+            | Self::Mistral
+            | Self::Mistral7b
+            | Self::Gemma2
+            | Self::Qwen25
+            | Self::Phi3 => Some((0.0, 0.0)),
+
+            Self::ElevenMultilingualV2 | Self::ElevenFlashV25 | Self::ElevenTurboV25 => None,
+        }
+    }
+
+    /// Returns the context window in tokens, if known
+    ///
+    /// Ollama models report their native window, which the server may be configured below.
+    pub fn context_window(&self) -> Option<u32> {
+        match self {
+            Self::Gpt4o | Self::Gpt4oMini | Self::Gpt4Turbo => Some(128_000),
+            Self::Gpt41 | Self::Gpt41Mini | Self::Gpt41Nano => Some(1_047_576),
+            Self::O3 | Self::O3Mini | Self::O4Mini => Some(200_000),
+            Self::Gpt35Turbo => Some(16_385),
+
+            Self::Claude35Sonnet
+            | Self::Claude35Haiku
+            | Self::Claude3Opus
+            | Self::Claude37Sonnet
+            | Self::Claude4Sonnet
+            | Self::Claude4Opus => Some(200_000),
+
+            Self::Gemini15Pro => Some(2_097_152),
+            Self::Gemini15Flash
+            | Self::Gemini15Flash8b
+            | Self::Gemini20FlashExp
+            | Self::Gemini25Pro
+            | Self::Gemini25Flash => Some(1_048_576),
+
+            Self::DeepseekChat | Self::DeepseekReason | Self::DeepseekCoder => Some(64_000),
+
+            Self::Grok3
+            | Self::Grok3Mini
+            | Self::Grok3Reasoning
+            | Self::Grok3MiniReasoning
+            | Self::Grok2 => Some(131_072),
+            Self::Grok1 => Some(8_192),
+
+            Self::Llama33 | Self::Llama32 => Some(131_072),
+            Self::Codellama => Some(16_384),
+            Self::Mistral | Self::Mistral7b | Self::Qwen25 => Some(32_768),
+            Self::Gemma2 => Some(8_192),
+            Self::Phi3 => Some(4_096),
+
+            Self::ElevenMultilingualV2 | Self::ElevenFlashV25 | Self::ElevenTurboV25 => None,
+        }
+    }
+
     #[allow(dead_code)]
     /// Returns true if this model supports structured output
     pub fn supports_structured_output(&self) -> bool {
@@ -240,26 +342,105 @@ impl ModelId {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, ValueEnum)]
 pub enum Provider {
     #[serde(rename = "openai")]
+    #[value(name = "openai")]
     OpenAI,
     #[serde(rename = "anthropic")]
+    #[value(name = "anthropic")]
     Anthropic,
     #[serde(rename = "google")]
+    #[value(name = "google")]
     Google,
     #[serde(rename = "azure_openai")]
+    #[value(name = "azure_openai")]
     AzureOpenAI,
     #[serde(rename = "deepseek")]
+    #[value(name = "deepseek")]
     DeepSeek,
     #[serde(rename = "xai")]
+    #[value(name = "xai")]
     Xai,
     #[serde(rename = "groq")]
+    #[value(name = "groq")]
     Groq,
     #[serde(rename = "ollama")]
+    #[value(name = "ollama")]
     Ollama,
     #[serde(rename = "elevenlabs")]
+    #[value(name = "elevenlabs")]
     ElevenLabs,
+    #[serde(rename = "phind")]
+    #[value(name = "phind")]
+    Phind,
+    #[serde(rename = "cohere")]
+    #[value(name = "cohere")]
+    Cohere,
+    #[serde(rename = "mistral")]
+    #[value(name = "mistral")]
+    Mistral,
+    #[serde(rename = "openrouter")]
+    #[value(name = "openrouter")]
+    OpenRouter,
+    #[serde(rename = "huggingface")]
+    #[value(name = "huggingface")]
+    HuggingFace,
+    #[serde(rename = "aws_bedrock")]
+    #[value(name = "aws_bedrock")]
+    AwsBedrock,
+}
+
+impl Provider {
+    /// The `llm` crate backend that serves this provider's models
+    pub fn backend(&self) -> LLMBackend {
+        match self {
+            Self::OpenAI => LLMBackend::OpenAI,
+            Self::Anthropic => LLMBackend::Anthropic,
+            Self::Google => LLMBackend::Google,
+            Self::AzureOpenAI => LLMBackend::AzureOpenAI,
+            Self::DeepSeek => LLMBackend::DeepSeek,
+            Self::Xai => LLMBackend::XAI,
+            Self::Groq => LLMBackend::Groq,
+            Self::Ollama => LLMBackend::Ollama,
+            Self::ElevenLabs => LLMBackend::ElevenLabs,
+            Self::Phind => LLMBackend::Phind,
+            Self::Cohere => LLMBackend::Cohere,
+            Self::Mistral => LLMBackend::Mistral,
+            Self::OpenRouter => LLMBackend::OpenRouter,
+            Self::HuggingFace => LLMBackend::HuggingFace,
+            Self::AwsBedrock => LLMBackend::AwsBedrock,
+        }
+    }
+}
+
+impl From<LLMBackend> for Provider {
+    fn from(backend: LLMBackend) -> Self {
+        match backend {
+            LLMBackend::OpenAI => Self::OpenAI,
+            LLMBackend::Anthropic => Self::Anthropic,
+            LLMBackend::Google => Self::Google,
+            LLMBackend::AzureOpenAI => Self::AzureOpenAI,
+            LLMBackend::DeepSeek => Self::DeepSeek,
+            LLMBackend::XAI => Self::Xai,
+            LLMBackend::Groq => Self::Groq,
+            LLMBackend::Ollama => Self::Ollama,
+            LLMBackend::ElevenLabs => Self::ElevenLabs,
+            LLMBackend::Phind => Self::Phind,
+            LLMBackend::Cohere => Self::Cohere,
+            LLMBackend::Mistral => Self::Mistral,
+            LLMBackend::OpenRouter => Self::OpenRouter,
+            LLMBackend::HuggingFace => Self::HuggingFace,
+            LLMBackend::AwsBedrock => Self::AwsBedrock,
+        }
+    }
+}
+
+impl std::fmt::Display for Provider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let serialized = serde_json::to_string(self).map_err(|_| std::fmt::Error)?;
+        write!(f, "{}", serialized.trim_matches('"'))
+    }
 }
 
 impl std::fmt::Display for ModelId {
@@ -276,3 +457,77 @@ impl std::str::FromStr for ModelId {
         serde_json::from_str(&format!("\"{}\"", s))
     }
 }
+
+/// The documented environment variable holding `provider`'s API key
+pub fn provider_api_key_env_var(provider: &LLMBackend) -> &'static str {
+    match provider {
+        LLMBackend::Anthropic => "ANTHROPIC_API_KEY",
+        LLMBackend::AzureOpenAI => "AZURE_OPENAI_API_KEY",
+        LLMBackend::DeepSeek => "DEEPSEEK_API_KEY",
+        LLMBackend::ElevenLabs => "ELEVEN_LABS_API_KEY",
+        LLMBackend::Google => "GOOGLE_API_KEY",
+        LLMBackend::Groq => "GROQ_API_KEY",
+        LLMBackend::Ollama => "OLLAMA_API_KEY",
+        LLMBackend::OpenAI => "OPENAI_API_KEY",
+        LLMBackend::Phind => "PHIND_API_KEY",
+        LLMBackend::XAI => "XAI_API_KEY",
+        LLMBackend::Cohere => "COHERE_API_KEY",
+        LLMBackend::Mistral => "MISTRAL_API_KEY",
+        LLMBackend::OpenRouter => "OPENROUTER_API_KEY",
+        LLMBackend::HuggingFace => "HF_TOKEN",
+        LLMBackend::AwsBedrock => "AWS_ACCESS_KEY_ID",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_windows() {
+        assert_eq!(ModelId::Gpt4o.context_window(), Some(128_000));
+        assert_eq!(ModelId::Claude4Sonnet.context_window(), Some(200_000));
+        assert_eq!(ModelId::Gemini15Pro.context_window(), Some(2_097_152));
+        assert_eq!(ModelId::Phi3.context_window(), Some(4_096));
+        assert_eq!(ModelId::ElevenFlashV25.context_window(), None);
+    }
+
+    #[test]
+    fn test_provider_backend_round_trip() {
+        for provider in Provider::value_variants() {
+            assert_eq!(&Provider::from(provider.backend()), provider);
+        }
+        assert_eq!(ModelId::Claude4Sonnet.provider_enum(), Provider::Anthropic);
+        assert_eq!(ModelId::Grok3.provider_enum().to_string(), "xai");
+    }
+
+    #[test]
+    fn test_api_key_env_vars() {
+        let expected = [
+            (LLMBackend::Anthropic, "ANTHROPIC_API_KEY"),
+            (LLMBackend::AzureOpenAI, "AZURE_OPENAI_API_KEY"),
+            (LLMBackend::DeepSeek, "DEEPSEEK_API_KEY"),
+            (LLMBackend::ElevenLabs, "ELEVEN_LABS_API_KEY"),
+            (LLMBackend::Google, "GOOGLE_API_KEY"),
+            (LLMBackend::Groq, "GROQ_API_KEY"),
+            (LLMBackend::Ollama, "OLLAMA_API_KEY"),
+            (LLMBackend::OpenAI, "OPENAI_API_KEY"),
+            (LLMBackend::Phind, "PHIND_API_KEY"),
+            (LLMBackend::XAI, "XAI_API_KEY"),
+            (LLMBackend::Cohere, "COHERE_API_KEY"),
+            (LLMBackend::Mistral, "MISTRAL_API_KEY"),
+            (LLMBackend::OpenRouter, "OPENROUTER_API_KEY"),
+            (LLMBackend::HuggingFace, "HF_TOKEN"),
+            (LLMBackend::AwsBedrock, "AWS_ACCESS_KEY_ID"),
+        ];
+        for (provider, env_var) in expected {
+            assert_eq!(provider_api_key_env_var(&provider), env_var);
+        }
+
+        assert_eq!(ModelId::DeepseekChat.api_key_env_var(), "DEEPSEEK_API_KEY");
+        assert_eq!(
+            ModelId::Claude4Sonnet.api_key_env_var(),
+            "ANTHROPIC_API_KEY"
+        );
+    }
+}
@@ -2,12 +2,9 @@ use std::path::Path;
 
 use async_trait::async_trait;
 
-use crate::analysis::{
-    prompt::PromptTemplates,
-    summary::{
-        AnalysisContext, AnalysisError, ChildAnalysis, DirectoryAnalysis, FileAnalysis,
-        LlmAnalyser, ProjectAnalysis,
-    },
+use crate::analysis::summary::{
+    AnalysisContext, AnalysisError, ChildAnalysis, DirectoryAnalysis, FileAnalysis, LlmAnalyser,
+    ProjectAnalysis,
 };
 
 use super::LlmPool;
@@ -20,11 +17,11 @@ impl LlmAnalyser for LlmPool {
         file_content: &str,
         context: &AnalysisContext,
     ) -> Result<FileAnalysis, AnalysisError> {
-        let templates = PromptTemplates::default();
-        let prompt = templates.build_file_analysis_prompt(file_path, context);
         let request = self
             .execute_request(|client| {
-                let prompt = prompt.clone();
+                let prompt = client
+                    .prompt_templates()
+                    .build_file_analysis_prompt(file_path, context);
                 async move {
                     client
                         .request()
@@ -51,13 +48,13 @@ impl LlmAnalyser for LlmPool {
         child_analyses: &[ChildAnalysis],
         context: &AnalysisContext,
     ) -> Result<DirectoryAnalysis, AnalysisError> {
-        let templates = PromptTemplates::default();
-        let prompt = templates.build_directory_synthesis_prompt(directory_path, context);
         let content: String = serde_json::to_string_pretty(child_analyses)
             .map_err(|e| AnalysisError::ParseError(e.to_string()))?;
         let request = self
             .execute_request(|client| {
-                let prompt = prompt.clone();
+                let prompt = client
+                    .prompt_templates()
+                    .build_directory_synthesis_prompt(directory_path, context);
                 let content = content.clone();
                 async move {
                     client
@@ -71,7 +68,7 @@ impl LlmAnalyser for LlmPool {
             })
             .await;
         match request {
-            Ok(res) => Ok(res),
+            Ok(res) => Ok(res.normalized()),
             Err(e) => {
                 eprint!("path: {:?}", directory_path);
                 Err(AnalysisError::LlmError(e.to_string()))
@@ -85,14 +82,14 @@ impl LlmAnalyser for LlmPool {
         child_analyses: &[ChildAnalysis],
         context: &AnalysisContext,
     ) -> Result<ProjectAnalysis, AnalysisError> {
-        let templates = PromptTemplates::default();
-        let prompt = templates.build_project_analysis_prompt(project_root, context);
         let content: String = serde_json::to_string_pretty(child_analyses)
             .map_err(|e| AnalysisError::ParseError(e.to_string()))?;
 
         let request = self
             .execute_request(|client| {
-                let prompt = prompt.clone();
+                let prompt = client
+                    .prompt_templates()
+                    .build_project_analysis_prompt(project_root, context);
                 let content = content.clone();
                 async move {
                     client
@@ -107,11 +104,19 @@ impl LlmAnalyser for LlmPool {
             .await;
 
         match request {
-            Ok(res) => Ok(res),
+            Ok(res) => Ok(res.normalized()),
             Err(e) => {
                 eprint!("path: {:?}", project_root);
                 Err(AnalysisError::LlmError(e.to_string()))
             }
         }
     }
+
+    fn tokens_used(&self) -> u64 {
+        LlmPool::tokens_used(self)
+    }
+
+    fn retries(&self) -> u64 {
+        LlmPool::retries(self)
+    }
 }
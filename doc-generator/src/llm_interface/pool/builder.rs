@@ -1,4 +1,6 @@
 #![allow(dead_code)]
+use std::time::Duration;
+
 use super::{Behaviour, LlmPool, PoolMember};
 use crate::llm_interface::client::LlmClient;
 
@@ -6,6 +8,7 @@ use crate::llm_interface::client::LlmClient;
 pub struct LlmPoolBuilder {
     members: Vec<PoolMember>,
     behaviour: Option<Behaviour>,
+    error_cooldown: Option<Duration>,
 }
 
 impl LlmPoolBuilder {
@@ -14,6 +17,7 @@ impl LlmPoolBuilder {
         Self {
             members: Vec::new(),
             behaviour: None,
+            error_cooldown: None,
         }
     }
 
@@ -23,6 +27,12 @@ impl LlmPoolBuilder {
         self
     }
 
+    /// Skip errored clients for this long before trying them again
+    pub fn error_cooldown(mut self, cooldown: Duration) -> Self {
+        self.error_cooldown = Some(cooldown);
+        self
+    }
+
     /// Add a client with default priority (0)
     pub fn add_client(mut self, client: LlmClient) -> Self {
         self.members.push(PoolMember {
@@ -97,13 +107,14 @@ impl LlmPoolBuilder {
     pub fn build(self) -> Result<LlmPool, LlmPoolBuilderError> {
         let behaviour = self
             .behaviour
+            .clone()
             .ok_or(LlmPoolBuilderError::Missingbehaviour)?;
 
         if self.members.is_empty() {
             return Err(LlmPoolBuilderError::NoClients);
         }
 
-        Ok(LlmPool::new(self.members, behaviour))
+        Ok(self.finish(behaviour))
     }
 
     /// Build the LlmPool with a default behaviour if none is set
@@ -113,8 +124,16 @@ impl LlmPoolBuilder {
             return Err(LlmPoolBuilderError::NoClients);
         }
 
-        let behaviour = self.behaviour.unwrap_or(Behaviour::Failover);
-        Ok(LlmPool::new(self.members, behaviour))
+        let behaviour = self.behaviour.clone().unwrap_or(Behaviour::Failover);
+        Ok(self.finish(behaviour))
+    }
+
+    fn finish(self, behaviour: Behaviour) -> LlmPool {
+        let pool = LlmPool::new(self.members, behaviour);
+        match self.error_cooldown {
+            Some(cooldown) => pool.with_error_cooldown(cooldown),
+            None => pool,
+        }
     }
 }
 
@@ -131,6 +150,8 @@ pub enum LlmPoolBuilderError {
     Missingbehaviour,
     /// No clients were added to the pool
     NoClients,
+    /// A configured client could not be created, e.g. because its API key is missing
+    Client(String),
 }
 
 impl std::fmt::Display for LlmPoolBuilderError {
@@ -142,6 +163,7 @@ impl std::fmt::Display for LlmPoolBuilderError {
             LlmPoolBuilderError::NoClients => {
                 write!(f, "At least one client must be added to the pool")
             }
+            LlmPoolBuilderError::Client(message) => write!(f, "{}", message),
         }
     }
 }
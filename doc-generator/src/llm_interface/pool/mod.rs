@@ -29,6 +29,7 @@ pub struct PoolMember {
 }
 
 impl PoolMember {
+    #[allow(dead_code)]
     pub fn new(priority: usize, client: LlmClient) -> Self {
         Self {
             priority,
@@ -43,8 +44,13 @@ pub struct LlmPool {
     client_order: Vec<u64>,
     pub behaviour: Behaviour,
     round_robin_index: Arc<AtomicUsize>,
+    /// How long an errored client is skipped before it is tried again
+    error_cooldown: Duration,
 }
 
+/// Default time an errored client sits out before being retried
+pub const DEFAULT_ERROR_COOLDOWN: Duration = Duration::from_secs(60);
+
 // Manual Clone implementation
 impl Clone for LlmPool {
     fn clone(&self) -> Self {
@@ -55,6 +61,7 @@ impl Clone for LlmPool {
             round_robin_index: Arc::new(AtomicUsize::new(
                 self.round_robin_index.load(Ordering::Relaxed),
             )),
+            error_cooldown: self.error_cooldown,
         }
     }
 }
@@ -75,9 +82,16 @@ impl LlmPool {
             client_order,
             behaviour,
             round_robin_index: Arc::new(AtomicUsize::new(0)),
+            error_cooldown: DEFAULT_ERROR_COOLDOWN,
         }
     }
 
+    /// Skip errored clients for `cooldown` before trying them again
+    pub fn with_error_cooldown(mut self, cooldown: Duration) -> Self {
+        self.error_cooldown = cooldown;
+        self
+    }
+
     /// Returns a client based on behaviour
     pub fn get_client(&self) -> Arc<LlmClient> {
         if self.clients.is_empty() {
@@ -91,9 +105,19 @@ impl LlmPool {
         }
     }
 
+    /// Advance the round robin counter, keeping it bounded by `len` so it can never overflow
+    fn next_round_robin_index(&self, len: usize) -> usize {
+        let previous = self
+            .round_robin_index
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |current| {
+                Some((current % len + 1) % len)
+            })
+            .expect("update closure always returns Some");
+        previous % len
+    }
+
     fn get_distribute_client(&self) -> Arc<LlmClient> {
-        let current_index =
-            self.round_robin_index.fetch_add(1, Ordering::Relaxed) % self.client_order.len();
+        let current_index = self.next_round_robin_index(self.client_order.len());
 
         let client_id = self.client_order[current_index];
         let member = &self.clients[&client_id];
@@ -101,19 +125,16 @@ impl LlmPool {
     }
 
     fn get_failover_client(&self) -> Arc<LlmClient> {
-        // Sort by priority (lower number = higher priority)
+        // Sort by priority (lower number = higher priority), breaking ties by client id
         let mut sorted_clients: Vec<_> = self.clients.values().collect();
-        sorted_clients.sort_by_key(|member| member.priority);
+        sorted_clients.sort_by_key(|member| (member.priority, member.client.id()));
 
         let now = SystemTime::now();
-        const ERROR_COOLDOWN: Duration = Duration::from_secs(60);
 
         // Find the highest priority client that's not in error state
         for member in &sorted_clients {
-            if let Some(last_error) = member.last_error {
-                if now.duration_since(last_error).unwrap_or(Duration::ZERO) < ERROR_COOLDOWN {
-                    continue;
-                }
+            if self.in_cooldown(member, now) {
+                continue;
             }
             return Arc::new(member.client.clone());
         }
@@ -127,13 +148,17 @@ impl LlmPool {
 
     fn get_combination_client(&self) -> Arc<LlmClient> {
         let now = SystemTime::now();
-        const ERROR_COOLDOWN: Duration = Duration::from_secs(60);
 
         // Group by priority
         let mut priority_groups: std::collections::BTreeMap<usize, Vec<&PoolMember>> =
             std::collections::BTreeMap::new();
 
-        for member in self.clients.values() {
+        // Walk in client id order so each group's members keep a stable order,
+        // whatever order the pool was built in
+        let mut client_ids: Vec<_> = self.clients.keys().collect();
+        client_ids.sort();
+        for client_id in client_ids {
+            let member = &self.clients[client_id];
             priority_groups
                 .entry(member.priority)
                 .or_default()
@@ -145,23 +170,14 @@ impl LlmPool {
             // Filter out clients in error cooldown
             let available_clients: Vec<_> = group
                 .into_iter()
-                .filter(|member| {
-                    member
-                        .last_error
-                        .map(|last_error| {
-                            now.duration_since(last_error).unwrap_or(Duration::ZERO)
-                                >= ERROR_COOLDOWN
-                        })
-                        .unwrap_or(true)
-                })
+                .filter(|member| !self.in_cooldown(member, now))
                 .collect();
 
             if !available_clients.is_empty() {
-                // Distribute among available clients in this priority group
-                let current_index = self.round_robin_index.fetch_add(1, Ordering::Relaxed)
-                    % self.client_order.len();
+                // Distribute evenly among available clients in this priority group
+                let current_index = self.next_round_robin_index(available_clients.len());
 
-                let selected_client = available_clients[current_index % available_clients.len()];
+                let selected_client = available_clients[current_index];
                 return Arc::new(selected_client.client.clone());
             }
         }
@@ -169,11 +185,39 @@ impl LlmPool {
         // If no clients are available, return the highest priority one anyway
         self.clients
             .values()
-            .min_by_key(|member| member.priority)
+            .min_by_key(|member| (member.priority, member.client.id()))
             .map(|member| Arc::new(member.client.clone()))
             .expect("Should contain at least one client")
     }
 
+    /// Whether `member` errored recently enough that it should still be skipped
+    fn in_cooldown(&self, member: &PoolMember, now: SystemTime) -> bool {
+        member.last_error.is_some_and(|last_error| {
+            now.duration_since(last_error).unwrap_or(Duration::ZERO) < self.error_cooldown
+        })
+    }
+
+    #[allow(dead_code)]
+    /// Health check every errored client and clear the error of those that respond,
+    /// so they rejoin the pool without waiting out the cooldown
+    pub async fn probe_errored_clients(&mut self) {
+        self.probe_errored_clients_with(|client| async move { client.health_check().await })
+            .await
+    }
+
+    /// `probe_errored_clients` with a custom probe
+    async fn probe_errored_clients_with<F, Fut>(&mut self, probe: F)
+    where
+        F: Fn(LlmClient) -> Fut,
+        Fut: std::future::Future<Output = bool>,
+    {
+        for member in self.clients.values_mut() {
+            if member.last_error.is_some() && probe(member.client.clone()).await {
+                member.last_error = None;
+            }
+        }
+    }
+
     /// Mark a client as having an error - now uses client ID for O(1) lookup
     pub fn mark_error(&mut self, client: &LlmClient) {
         let client_id = client.id();
@@ -212,6 +256,22 @@ impl LlmPool {
         self.clients.len()
     }
 
+    /// Estimated tokens used across every client in the pool
+    pub fn tokens_used(&self) -> u64 {
+        self.clients
+            .values()
+            .map(|member| member.client.tokens_used())
+            .sum()
+    }
+
+    /// Retries made across every client in the pool
+    pub fn retries(&self) -> u64 {
+        self.clients
+            .values()
+            .map(|member| member.client.retries())
+            .sum()
+    }
+
     #[allow(dead_code)]
     /// Check if the pool is empty
     pub fn is_empty(&self) -> bool {
@@ -238,8 +298,9 @@ impl LlmPool {
 
                 for attempt in 0..max_attempts {
                     let client = self.get_client();
+                    let result = request_fn(client.clone()).await;
 
-                    match request_fn(client.clone()).await {
+                    match result {
                         Ok(result) => return Ok(result),
                         Err(e) => {
                             error!("Attempt {} failed: {}", attempt + 1, e);
@@ -443,6 +504,34 @@ mod tests {
         assert_ne!(id3, low_priority_id);
     }
 
+    #[test]
+    fn test_equal_priority_ties_are_broken_by_client_id() {
+        let build = |behaviour: Behaviour, reversed: bool| {
+            let mut members = vec![
+                create_pool_member("key1", ModelId::Gpt4o, 1),
+                create_pool_member("key2", ModelId::Claude35Sonnet, 1),
+            ];
+            if reversed {
+                members.reverse();
+            }
+            LlmPool::new(members, behaviour)
+        };
+        let lowest_id = client_id(&create_test_client("key1", ModelId::Gpt4o)).min(client_id(
+            &create_test_client("key2", ModelId::Claude35Sonnet),
+        ));
+
+        for reversed in [false, true] {
+            let pool = build(Behaviour::Failover, reversed);
+            assert_eq!(client_id(&pool.get_client()), lowest_id);
+        }
+
+        let selections =
+            |pool: LlmPool| -> Vec<u64> { (0..4).map(|_| client_id(&pool.get_client())).collect() };
+        let forward = selections(build(Behaviour::Combination, false));
+        assert_eq!(forward, selections(build(Behaviour::Combination, true)));
+        assert_eq!(forward[0], lowest_id);
+    }
+
     #[test]
     fn test_combination_falls_back_to_lower_priority() {
         let now = SystemTime::now();
@@ -566,6 +655,28 @@ mod tests {
         assert_eq!(id1, id3); // Should wrap around to first client
     }
 
+    #[test]
+    fn test_round_robin_near_usize_max() {
+        let members = vec![
+            create_pool_member("key1", ModelId::Gpt4o, 1),
+            create_pool_member("key2", ModelId::Claude35Sonnet, 2),
+            create_pool_member("key3", ModelId::Gemini15Pro, 3),
+        ];
+        let pool = LlmPool::new(members, Behaviour::Distribute);
+        pool.round_robin_index
+            .store(usize::MAX - 1, Ordering::Relaxed);
+
+        let ids: Vec<u64> = (0..6).map(|_| client_id(&pool.get_client())).collect();
+
+        // Still cycles through every client in order across the old wrap boundary
+        assert_ne!(ids[0], ids[1]);
+        assert_ne!(ids[1], ids[2]);
+        assert_ne!(ids[0], ids[2]);
+        assert_eq!(ids[0..3], ids[3..6]);
+        // And the counter stays bounded by the number of clients
+        assert!(pool.round_robin_index.load(Ordering::Relaxed) < 3);
+    }
+
     #[test]
     fn test_clone_preserves_state() {
         let members = vec![
@@ -646,4 +757,97 @@ mod tests {
         assert_ne!(client_id(&client1), client_id(&client3)); // Different models
         assert_eq!(client_id(&client1), client_id(&client4)); // Same key + model
     }
+
+    #[test]
+    fn test_combination_distributes_evenly_within_fallback_group() {
+        let error_time = SystemTime::now();
+        let members = vec![
+            create_pool_member_with_error("key1", ModelId::Gpt4o, 1, error_time),
+            create_pool_member_with_error("key2", ModelId::Gpt4oMini, 1, error_time),
+            create_pool_member("key3", ModelId::Claude35Sonnet, 2),
+            create_pool_member("key4", ModelId::Claude35Haiku, 2),
+            create_pool_member("key5", ModelId::Gemini15Pro, 2),
+        ];
+        let fallback_ids: Vec<u64> = members[2..]
+            .iter()
+            .map(|member| client_id(&member.client))
+            .collect();
+        let pool = LlmPool::new(members, Behaviour::Combination);
+
+        let mut counts: HashMap<u64, usize> = HashMap::new();
+        for _ in 0..300 {
+            *counts.entry(client_id(&pool.get_client())).or_default() += 1;
+        }
+
+        assert_eq!(counts.len(), 3);
+        for id in fallback_ids {
+            assert_eq!(counts[&id], 100);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_probe_clears_errors_of_recovered_clients() {
+        let now = SystemTime::now();
+        let members = vec![
+            create_pool_member_with_error("key1", ModelId::Gpt4o, 1, now),
+            create_pool_member_with_error("key2", ModelId::Claude35Sonnet, 2, now),
+            create_pool_member("key3", ModelId::Gemini15Pro, 3),
+        ];
+        let recovered = client_id(&members[0].client);
+        let mut pool = LlmPool::new(members, Behaviour::Failover);
+
+        let probed = Arc::new(AtomicUsize::new(0));
+        pool.probe_errored_clients_with(|client| {
+            let probed = probed.clone();
+            async move {
+                probed.fetch_add(1, Ordering::SeqCst);
+                client.id() == recovered
+            }
+        })
+        .await;
+
+        // Only errored clients are probed, and only the passing one is cleared
+        assert_eq!(probed.load(Ordering::SeqCst), 2);
+        assert_eq!(client_id(&pool.get_client()), recovered);
+        assert!(
+            pool.clients
+                .values()
+                .filter(|member| member.last_error.is_some())
+                .all(|member| member.priority == 2)
+        );
+    }
+
+    #[test]
+    fn test_error_cooldown_is_configurable() {
+        let error_time = SystemTime::now() - Duration::from_secs(10);
+        let members = vec![
+            create_pool_member_with_error("key1", ModelId::Gpt4o, 1, error_time),
+            create_pool_member("key2", ModelId::Claude35Sonnet, 2),
+        ];
+        let errored = client_id(&members[0].client);
+        let pool = LlmPool::new(members, Behaviour::Failover);
+        assert_ne!(client_id(&pool.get_client()), errored);
+
+        let pool = pool.with_error_cooldown(Duration::from_secs(5));
+        assert_eq!(client_id(&pool.get_client()), errored);
+    }
+
+    #[test]
+    fn test_long_cooldown_keeps_errored_client_skipped() {
+        let two_minutes_ago = SystemTime::now() - Duration::from_secs(120);
+        let members = vec![
+            create_pool_member_with_error("key1", ModelId::Gpt4o, 1, two_minutes_ago),
+            create_pool_member_with_error("key2", ModelId::Gpt4oMini, 1, two_minutes_ago),
+            create_pool_member("key3", ModelId::Claude35Sonnet, 2),
+        ];
+        let fallback = client_id(&members[2].client);
+
+        for behaviour in [Behaviour::Failover, Behaviour::Combination] {
+            let pool = LlmPool::new(members.clone(), behaviour)
+                .with_error_cooldown(Duration::from_secs(5 * 60));
+            for _ in 0..4 {
+                assert_eq!(client_id(&pool.get_client()), fallback);
+            }
+        }
+    }
 }
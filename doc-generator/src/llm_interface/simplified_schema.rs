@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "UPPERCASE")]
@@ -42,6 +43,12 @@ pub struct SimplifiedSchema {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub properties: Option<HashMap<String, SimplifiedSchema>>,
 
+    #[serde(
+        rename = "additionalProperties",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub additional_properties: Option<Box<SimplifiedSchema>>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub required: Option<Vec<String>>,
 
@@ -82,6 +89,73 @@ pub struct SimplifiedSchema {
     pub maximum: Option<f64>,
 }
 
+impl SimplifiedSchema {
+    /// A schema of the given type with every other field unset
+    fn of_type(schema_type: SchemaType) -> Self {
+        Self {
+            schema_type,
+            format: None,
+            title: None,
+            description: None,
+            nullable: None,
+            enum_values: None,
+            max_items: None,
+            min_items: None,
+            properties: None,
+            additional_properties: None,
+            required: None,
+            min_properties: None,
+            max_properties: None,
+            min_length: None,
+            max_length: None,
+            pattern: None,
+            example: None,
+            any_of: None,
+            property_ordering: None,
+            default: None,
+            items: None,
+            minimum: None,
+            maximum: None,
+        }
+    }
+
+    /// A placeholder value matching this schema, for showing models an example response.
+    /// Explicit examples and defaults win; otherwise strings carry their description.
+    pub fn example_value(&self) -> Value {
+        if let Some(example) = self.example.as_ref().or(self.default.as_ref()) {
+            return example.clone();
+        }
+        if let Some(first) = self.enum_values.as_ref().and_then(|values| values.first()) {
+            return Value::String(first.clone());
+        }
+        if let Some(first) = self.any_of.as_ref().and_then(|variants| variants.first()) {
+            return first.example_value();
+        }
+
+        match self.schema_type {
+            SchemaType::String => {
+                Value::String(self.description.clone().unwrap_or_else(|| "string".into()))
+            }
+            SchemaType::Number => Value::from(0.0),
+            SchemaType::Integer => Value::from(0),
+            SchemaType::Boolean => Value::Bool(false),
+            SchemaType::Array => Value::Array(
+                self.items
+                    .iter()
+                    .map(|items| items.example_value())
+                    .collect(),
+            ),
+            SchemaType::Object => Value::Object(
+                self.properties
+                    .iter()
+                    .flatten()
+                    .map(|(name, property)| (name.clone(), property.example_value()))
+                    .collect::<Map<_, _>>(),
+            ),
+        }
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ConversionError {
     #[error("Unsupported schema type: {0}")]
@@ -96,12 +170,15 @@ pub enum ConversionError {
 
 pub struct JsonSchemaConverter {
     definitions: HashMap<String, Value>,
+    /// `$ref` paths currently being expanded, used to stop on recursive schemas
+    resolving: RefCell<HashSet<String>>,
 }
 
 impl JsonSchemaConverter {
     pub fn new() -> Self {
         Self {
             definitions: HashMap::new(),
+            resolving: RefCell::new(HashSet::new()),
         }
     }
 
@@ -154,6 +231,15 @@ impl JsonSchemaConverter {
         )))
     }
 
+    /// Generic object standing in for a recursive reference
+    fn recursive_placeholder(ref_path: &str) -> SimplifiedSchema {
+        let name = ref_path.rsplit('/').next().unwrap_or(ref_path);
+        SimplifiedSchema {
+            description: Some(format!("Recursive reference to {}", name)),
+            ..SimplifiedSchema::of_type(SchemaType::Object)
+        }
+    }
+
     #[allow(clippy::only_used_in_recursion)]
     /// Remove JSON Schema specific fields that start with '$'
     fn clean_schema(&self, schema: &Value) -> Value {
@@ -177,8 +263,15 @@ impl JsonSchemaConverter {
         // Handle $ref first, before any other processing
         if let Some(obj) = schema.as_object() {
             if let Some(ref_path) = obj.get("$ref").and_then(|v| v.as_str()) {
-                let resolved = self.resolve_ref(ref_path)?;
-                return self.convert_schema(&resolved);
+                // A reference back to a definition we're already inside would recurse forever
+                if !self.resolving.borrow_mut().insert(ref_path.to_string()) {
+                    return Ok(Self::recursive_placeholder(ref_path));
+                }
+                let converted = self
+                    .resolve_ref(ref_path)
+                    .and_then(|resolved| self.convert_schema(&resolved));
+                self.resolving.borrow_mut().remove(ref_path);
+                return converted;
             }
         }
 
@@ -214,30 +307,7 @@ impl JsonSchemaConverter {
         // Determine the type from cleaned schema
         let schema_type = Self::determine_type(schema_obj)?;
 
-        let mut gemini_schema = SimplifiedSchema {
-            schema_type: schema_type.clone(),
-            format: None,
-            title: None,
-            description: None,
-            nullable: None,
-            enum_values: None,
-            max_items: None,
-            min_items: None,
-            properties: None,
-            required: None,
-            min_properties: None,
-            max_properties: None,
-            min_length: None,
-            max_length: None,
-            pattern: None,
-            example: None,
-            any_of: None,
-            property_ordering: None,
-            default: None,
-            items: None,
-            minimum: None,
-            maximum: None,
-        };
+        let mut gemini_schema = SimplifiedSchema::of_type(schema_type.clone());
 
         // Set basic fields from cleaned schema
         if let Some(title) = schema_obj.get("title").and_then(|v| v.as_str()) {
@@ -298,40 +368,10 @@ impl JsonSchemaConverter {
         }
 
         // Check if all variants are string enums - if so, merge them
-        let mut all_enum_values = Vec::new();
-        let mut all_are_string_enums = true;
-
-        for variant in one_of {
-            if let Some(variant_obj) = variant.as_object() {
-                if let Some(type_str) = variant_obj.get("type").and_then(|v| v.as_str()) {
-                    if type_str == "string" {
-                        if let Some(enum_vals) = variant_obj.get("enum").and_then(|v| v.as_array())
-                        {
-                            for enum_val in enum_vals {
-                                if let Some(val_str) = enum_val.as_str() {
-                                    all_enum_values.push(val_str.to_string());
-                                } else {
-                                    all_are_string_enums = false;
-                                    break;
-                                }
-                            }
-                        } else {
-                            all_are_string_enums = false;
-                        }
-                    } else {
-                        all_are_string_enums = false;
-                    }
-                } else {
-                    all_are_string_enums = false;
-                }
-            } else {
-                all_are_string_enums = false;
-            }
-
-            if !all_are_string_enums {
-                break;
-            }
-        }
+        let string_enums: Option<Vec<Vec<String>>> =
+            one_of.iter().map(Self::string_enum_values).collect();
+        let all_are_string_enums = string_enums.is_some();
+        let all_enum_values: Vec<String> = string_enums.into_iter().flatten().flatten().collect();
 
         if all_are_string_enums && !all_enum_values.is_empty() {
             // Create a merged enum schema
@@ -345,6 +385,7 @@ impl JsonSchemaConverter {
                 max_items: None,
                 min_items: None,
                 properties: None,
+                additional_properties: None,
                 required: None,
                 min_properties: None,
                 max_properties: None,
@@ -368,10 +409,104 @@ impl JsonSchemaConverter {
             return Ok(merged_schema);
         }
 
+        // Tagged unions become one object whose discriminant lists every tag
+        if let Some(merged) = self.merge_tagged_variants(one_of)? {
+            return Ok(merged);
+        }
+
         // Otherwise, use the first variant
         self.convert_schema(&one_of[0])
     }
 
+    /// The values of a string `enum` or `const` variant, `None` for any other schema
+    fn string_enum_values(variant: &Value) -> Option<Vec<String>> {
+        let variant_obj = variant.as_object()?;
+        match variant_obj.get("type") {
+            Some(Value::String(type_str)) if type_str == "string" => {}
+            None if variant_obj.contains_key("const") => {}
+            _ => return None,
+        }
+
+        if let Some(constant) = variant_obj.get("const") {
+            return constant.as_str().map(|value| vec![value.to_string()]);
+        }
+
+        variant_obj
+            .get("enum")?
+            .as_array()?
+            .iter()
+            .map(|value| value.as_str().map(|s| s.to_string()))
+            .collect()
+    }
+
+    /// Merge object variants that share a single-value discriminant property
+    ///
+    /// Properties are unioned (first variant wins), the discriminant keeps every
+    /// tag, and only fields required by all variants stay required.
+    fn merge_tagged_variants(
+        &self,
+        variants: &[Value],
+    ) -> Result<Option<SimplifiedSchema>, ConversionError> {
+        if variants.len() < 2 {
+            return Ok(None);
+        }
+
+        let mut converted = Vec::with_capacity(variants.len());
+        for variant in variants {
+            match self.convert_schema(variant) {
+                Ok(schema) if schema.schema_type == SchemaType::Object => converted.push(schema),
+                _ => return Ok(None),
+            }
+        }
+
+        let is_tag = |schema: &SimplifiedSchema, key: &str| {
+            schema
+                .properties
+                .as_ref()
+                .and_then(|properties| properties.get(key))
+                .and_then(|property| property.enum_values.as_ref())
+                .is_some_and(|values| values.len() == 1)
+        };
+        let Some(tag_key) = converted[0].properties.as_ref().and_then(|properties| {
+            let mut keys: Vec<&String> = properties.keys().collect();
+            keys.sort();
+            keys.into_iter()
+                .find(|key| converted.iter().all(|schema| is_tag(schema, key)))
+                .cloned()
+        }) else {
+            return Ok(None);
+        };
+
+        let mut variants = converted.into_iter();
+        let mut merged = variants.next().expect("at least two variants");
+        let properties = merged.properties.get_or_insert_with(HashMap::new);
+        let mut tags = properties[&tag_key].enum_values.clone().unwrap_or_default();
+        let mut required = merged.required.clone().unwrap_or_default();
+
+        for variant in variants {
+            let variant_properties = variant.properties.unwrap_or_default();
+            tags.extend(
+                variant_properties[&tag_key]
+                    .enum_values
+                    .clone()
+                    .unwrap_or_default(),
+            );
+            for (key, property) in variant_properties {
+                properties.entry(key).or_insert(property);
+            }
+            let variant_required = variant.required.unwrap_or_default();
+            required.retain(|field| variant_required.contains(field));
+        }
+
+        if let Some(tag) = properties.get_mut(&tag_key) {
+            tag.enum_values = Some(tags);
+        }
+        merged.required = Some(required);
+        merged.title = None;
+
+        Ok(Some(merged))
+    }
+
     /// Flatten allOf by merging all schemas together
     fn flatten_all_of(
         &self,
@@ -459,7 +594,7 @@ impl JsonSchemaConverter {
             Ok(SchemaType::Object)
         } else if schema.contains_key("items") {
             Ok(SchemaType::Array)
-        } else if schema.contains_key("enum") {
+        } else if schema.contains_key("enum") || schema.contains_key("const") {
             Ok(SchemaType::String)
         } else if schema.is_empty() {
             Err(ConversionError::InvalidSchema("Empty schema after cleaning - this might be a $ref-only schema that wasn't resolved".to_string()))
@@ -502,6 +637,15 @@ impl JsonSchemaConverter {
             gemini_schema.format = Some("enum".to_string());
         }
 
+        // Handle const as a single value enum (unit variants and tag discriminants)
+        if let Some(constant) = schema.get("const") {
+            let constant = constant.as_str().ok_or_else(|| {
+                ConversionError::InvalidSchema("Const values must be strings".to_string())
+            })?;
+            gemini_schema.enum_values = Some(vec![constant.to_string()]);
+            gemini_schema.format = Some("enum".to_string());
+        }
+
         // Handle string constraints
         if let Some(min_length) = schema.get("minLength").and_then(|v| v.as_u64()) {
             gemini_schema.min_length = Some(min_length.to_string());
@@ -586,6 +730,12 @@ impl JsonSchemaConverter {
             gemini_schema.properties = Some(converted_properties);
         }
 
+        // Handle map value types (e.g. HashMap<String, T>), boolean forms carry no type to keep
+        if let Some(additional) = schema.get("additionalProperties").filter(|v| v.is_object()) {
+            let converted = self.convert_schema(additional)?;
+            gemini_schema.additional_properties = Some(Box::new(converted));
+        }
+
         // Handle required fields
         if let Some(required) = schema.get("required").and_then(|v| v.as_array()) {
             let required_strings: Result<Vec<String>, _> = required
@@ -752,6 +902,42 @@ mod tests {
         assert_eq!(user_props["name"].schema_type, SchemaType::String);
     }
 
+    #[test]
+    fn test_recursive_ref_stops_at_cycle() {
+        let json_schema = json!({
+            "type": "object",
+            "properties": {
+                "root": { "$ref": "#/$defs/node" }
+            },
+            "$defs": {
+                "node": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" },
+                        "children": {
+                            "type": "array",
+                            "items": { "$ref": "#/$defs/node" }
+                        }
+                    }
+                }
+            }
+        });
+
+        let result = JsonSchemaConverter::convert(&json_schema).unwrap();
+
+        let root = &result.properties.unwrap()["root"];
+        let root_props = root.properties.as_ref().unwrap();
+        assert_eq!(root_props["name"].schema_type, SchemaType::String);
+
+        let child = root_props["children"].items.as_ref().unwrap();
+        assert_eq!(child.schema_type, SchemaType::Object);
+        assert!(child.properties.is_none());
+        assert_eq!(
+            child.description.as_deref(),
+            Some("Recursive reference to node")
+        );
+    }
+
     #[test]
     fn test_ref_resolution() {
         let json_schema = json!({
@@ -900,4 +1086,136 @@ mod tests {
             Some(vec!["veggieName".to_string(), "veggieLike".to_string()])
         );
     }
+
+    #[test]
+    fn test_example_value_follows_schema() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "name": { "type": "string", "description": "Name of the thing" },
+                "count": { "type": "integer" },
+                "kind": { "type": "string", "enum": ["Function", "Struct"] },
+                "tags": { "type": "array", "items": { "type": "string" } }
+            },
+            "required": ["name", "count", "kind", "tags"]
+        });
+
+        let simplified = JsonSchemaConverter::convert(&schema).unwrap();
+        assert_eq!(
+            simplified.example_value(),
+            json!({
+                "name": "Name of the thing",
+                "count": 0,
+                "kind": "Function",
+                "tags": ["string"]
+            })
+        );
+    }
+
+    #[test]
+    fn test_additional_properties_schema() {
+        let json_schema = json!({
+            "type": "object",
+            "additionalProperties": { "type": "integer" }
+        });
+
+        let result = JsonSchemaConverter::convert(&json_schema).unwrap();
+
+        assert_eq!(result.schema_type, SchemaType::Object);
+        assert!(result.properties.is_none());
+        let values = result.additional_properties.as_ref().unwrap();
+        assert_eq!(values.schema_type, SchemaType::Integer);
+
+        let serialized = serde_json::to_value(&result).unwrap();
+        assert_eq!(
+            serialized,
+            json!({
+                "type": "OBJECT",
+                "additionalProperties": { "type": "INTEGER" }
+            })
+        );
+    }
+
+    #[test]
+    fn test_additional_properties_boolean_ignored() {
+        let json_schema = json!({
+            "type": "object",
+            "properties": { "name": { "type": "string" } },
+            "additionalProperties": false
+        });
+
+        let result = JsonSchemaConverter::convert(&json_schema).unwrap();
+
+        assert!(result.properties.is_some());
+        assert!(result.additional_properties.is_none());
+    }
+
+    #[test]
+    fn test_const_schema_becomes_single_value_enum() {
+        let result = JsonSchemaConverter::convert(&json!({"const": "File"})).unwrap();
+
+        assert_eq!(result.schema_type, SchemaType::String);
+        assert_eq!(result.format, Some("enum".to_string()));
+        assert_eq!(result.enum_values, Some(vec!["File".to_string()]));
+    }
+
+    #[test]
+    fn test_const_variants_merge_into_enum() {
+        let json_schema = json!({
+            "oneOf": [
+                {"type": "string", "const": "Surface"},
+                {"const": "Deep"}
+            ]
+        });
+
+        let result = JsonSchemaConverter::convert(&json_schema).unwrap();
+
+        assert_eq!(
+            result.enum_values,
+            Some(vec!["Surface".to_string(), "Deep".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_tagged_union_with_const_discriminant() {
+        let json_schema = json!({
+            "oneOf": [
+                {
+                    "type": "object",
+                    "properties": {
+                        "type": {"const": "File"},
+                        "file_path": {"type": "string"}
+                    },
+                    "required": ["type", "file_path"]
+                },
+                {
+                    "type": "object",
+                    "properties": {
+                        "type": {"type": "string", "const": "Directory"},
+                        "directory_path": {"type": "string"}
+                    },
+                    "required": ["type", "directory_path"]
+                }
+            ]
+        });
+
+        let result = JsonSchemaConverter::convert(&json_schema).unwrap();
+
+        assert_eq!(result.schema_type, SchemaType::Object);
+        assert_eq!(result.required, Some(vec!["type".to_string()]));
+
+        let properties = result.properties.unwrap();
+        assert!(properties.contains_key("file_path"));
+        assert!(properties.contains_key("directory_path"));
+        assert_eq!(
+            properties["type"].enum_values,
+            Some(vec!["File".to_string(), "Directory".to_string()])
+        );
+
+        let variant = JsonSchemaConverter::convert(&json_schema["oneOf"][0]).unwrap();
+        assert_eq!(
+            variant.properties.unwrap()["type"].enum_values,
+            Some(vec!["File".to_string()])
+        );
+    }
 }
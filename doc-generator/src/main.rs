@@ -5,9 +5,15 @@ mod generate;
 mod llm_interface;
 mod output;
 mod settings;
+#[cfg(test)]
+mod test_support;
 use crate::cli::run_application;
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    run_application().await
+async fn main() {
+    // Report failures as a one line message rather than the Debug output `main` would print
+    if let Err(e) = run_application().await {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
 }
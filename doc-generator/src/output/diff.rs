@@ -0,0 +1,318 @@
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::analysis::summary::{ChildAnalysis, FileAnalysis};
+use crate::output::ndjson::NdjsonRecord;
+
+/// Load saved analyses, either NDJSON (one analysis per line) or a JSON array
+pub fn load_analyses(path: &Path) -> Result<Vec<ChildAnalysis>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read analysis file {}", path.display()))?;
+
+    if content.trim_start().starts_with('[') {
+        return serde_json::from_str(&content)
+            .with_context(|| format!("Invalid analysis JSON in {}", path.display()));
+    }
+
+    // The project record that closes a generate run isn't a file or directory, so it's skipped
+    let mut analyses = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = serde_json::from_str(line)
+            .with_context(|| format!("Invalid analysis on line {} of {}", i + 1, path.display()))?;
+        match record {
+            NdjsonRecord::File(analysis) => analyses.push(ChildAnalysis::File(analysis)),
+            NdjsonRecord::Directory(analysis) => analyses.push(ChildAnalysis::Directory(analysis)),
+            NdjsonRecord::Project(_) => {}
+        }
+    }
+    Ok(analyses)
+}
+
+/// Swap fresh file analyses into a saved run, replacing entries for the same path,
+/// appending files it didn't have and dropping the `deleted` files. Returns how many
+/// saved file analyses were reused as is.
+pub fn merge_file_analyses(
+    existing: &mut Vec<ChildAnalysis>,
+    updated: Vec<FileAnalysis>,
+    deleted: &[PathBuf],
+) -> usize {
+    let mut updated: BTreeMap<PathBuf, FileAnalysis> = updated
+        .into_iter()
+        .map(|analysis| (analysis.file_path.clone(), analysis))
+        .collect();
+
+    existing.retain(|analysis| match analysis {
+        ChildAnalysis::File(file) => !deleted.contains(&file.file_path),
+        ChildAnalysis::Directory(_) => true,
+    });
+
+    let mut reused = 0;
+    for analysis in existing.iter_mut() {
+        if let ChildAnalysis::File(file) = analysis {
+            match updated.remove(&file.file_path) {
+                Some(fresh) => *file = fresh,
+                None => reused += 1,
+            }
+        }
+    }
+    existing.extend(updated.into_values().map(ChildAnalysis::File));
+
+    reused
+}
+
+/// Changes to a file present in both analysis runs
+#[derive(Debug, Default, PartialEq)]
+pub struct FileChange {
+    pub file_path: PathBuf,
+    pub summary_changed: bool,
+    pub added_interfaces: Vec<String>,
+    pub removed_interfaces: Vec<String>,
+    pub added_dependencies: Vec<String>,
+    pub removed_dependencies: Vec<String>,
+}
+
+impl FileChange {
+    fn is_empty(&self) -> bool {
+        !self.summary_changed
+            && self.added_interfaces.is_empty()
+            && self.removed_interfaces.is_empty()
+            && self.added_dependencies.is_empty()
+            && self.removed_dependencies.is_empty()
+    }
+}
+
+/// Changelog-style comparison of the file analyses from two runs
+#[derive(Debug, Default)]
+pub struct AnalysisDiff {
+    pub added_files: Vec<PathBuf>,
+    pub removed_files: Vec<PathBuf>,
+    pub changed_files: Vec<FileChange>,
+}
+
+impl AnalysisDiff {
+    pub fn between(old: &[ChildAnalysis], new: &[ChildAnalysis]) -> Self {
+        let old_files = files_by_path(old);
+        let new_files = files_by_path(new);
+
+        let mut diff = AnalysisDiff::default();
+        for (path, new_file) in &new_files {
+            match old_files.get(path) {
+                None => diff.added_files.push(path.to_path_buf()),
+                Some(old_file) => {
+                    let change = compare_files(old_file, new_file);
+                    if !change.is_empty() {
+                        diff.changed_files.push(change);
+                    }
+                }
+            }
+        }
+        diff.removed_files = old_files
+            .keys()
+            .filter(|path| !new_files.contains_key(*path))
+            .map(|path| path.to_path_buf())
+            .collect();
+
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added_files.is_empty()
+            && self.removed_files.is_empty()
+            && self.changed_files.is_empty()
+    }
+}
+
+fn files_by_path(analyses: &[ChildAnalysis]) -> BTreeMap<&Path, &FileAnalysis> {
+    analyses
+        .iter()
+        .filter_map(|analysis| match analysis {
+            ChildAnalysis::File(file) => Some((file.file_path.as_path(), file)),
+            ChildAnalysis::Directory(_) => None,
+        })
+        .collect()
+}
+
+/// Items in `new` but not `old`, and items in `old` but not `new`
+fn set_difference<'a>(
+    old: impl Iterator<Item = &'a String>,
+    new: impl Iterator<Item = &'a String>,
+) -> (Vec<String>, Vec<String>) {
+    let old: BTreeSet<&String> = old.collect();
+    let new: BTreeSet<&String> = new.collect();
+    (
+        new.difference(&old).map(|s| s.to_string()).collect(),
+        old.difference(&new).map(|s| s.to_string()).collect(),
+    )
+}
+
+fn compare_files(old: &FileAnalysis, new: &FileAnalysis) -> FileChange {
+    let (added_interfaces, removed_interfaces) = set_difference(
+        old.public_interfaces.iter().map(|i| &i.name),
+        new.public_interfaces.iter().map(|i| &i.name),
+    );
+    let (added_dependencies, removed_dependencies) = set_difference(
+        old.external_dependencies.iter(),
+        new.external_dependencies.iter(),
+    );
+
+    FileChange {
+        file_path: new.file_path.clone(),
+        summary_changed: old.summary != new.summary,
+        added_interfaces,
+        removed_interfaces,
+        added_dependencies,
+        removed_dependencies,
+    }
+}
+
+impl Display for AnalysisDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "# 📝 Documentation Changes")?;
+        writeln!(f)?;
+
+        if self.is_empty() {
+            writeln!(f, "No changes.")?;
+            return Ok(());
+        }
+
+        if !self.added_files.is_empty() {
+            writeln!(f, "## ➕ Added Files")?;
+            for path in &self.added_files {
+                writeln!(f, "- `{}`", path.display())?;
+            }
+            writeln!(f)?;
+        }
+
+        if !self.removed_files.is_empty() {
+            writeln!(f, "## ➖ Removed Files")?;
+            for path in &self.removed_files {
+                writeln!(f, "- `{}`", path.display())?;
+            }
+            writeln!(f)?;
+        }
+
+        if !self.changed_files.is_empty() {
+            writeln!(f, "## ✏️ Changed Files")?;
+            for change in &self.changed_files {
+                writeln!(f, "### `{}`", change.file_path.display())?;
+                if change.summary_changed {
+                    writeln!(f, "- Summary updated")?;
+                }
+                for name in &change.added_interfaces {
+                    writeln!(f, "- Added interface `{}`", name)?;
+                }
+                for name in &change.removed_interfaces {
+                    writeln!(f, "- Removed interface `{}`", name)?;
+                }
+                for dep in &change.added_dependencies {
+                    writeln!(f, "- Added dependency `{}`", dep)?;
+                }
+                for dep in &change.removed_dependencies {
+                    writeln!(f, "- Removed dependency `{}`", dep)?;
+                }
+                writeln!(f)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::summary::{Interface, InterfaceType};
+
+    fn file(path: &str, interfaces: &[&str]) -> ChildAnalysis {
+        ChildAnalysis::File(FileAnalysis {
+            file_path: PathBuf::from(path),
+            file_type: "rs".to_string(),
+            summary: "Unchanged summary".to_string(),
+            external_dependencies: vec!["serde".to_string()],
+            public_interfaces: interfaces
+                .iter()
+                .map(|name| Interface {
+                    name: name.to_string(),
+                    interface_type: InterfaceType::Function,
+                    description: "A function".to_string(),
+                })
+                .collect(),
+            line_count: None,
+            byte_size: None,
+            signatures: vec![],
+        })
+    }
+
+    #[test]
+    fn test_diff_reports_added_file_and_removed_interface() {
+        let old = vec![file("src/lib.rs", &["parse", "render"])];
+        let new = vec![file("src/lib.rs", &["parse"]), file("src/cli.rs", &["run"])];
+
+        let diff = AnalysisDiff::between(&old, &new);
+        assert_eq!(diff.added_files, vec![PathBuf::from("src/cli.rs")]);
+        assert!(diff.removed_files.is_empty());
+        assert_eq!(diff.changed_files.len(), 1);
+        assert_eq!(diff.changed_files[0].removed_interfaces, vec!["render"]);
+
+        let report = diff.to_string();
+        assert!(report.contains("## ➕ Added Files\n- `src/cli.rs`"));
+        assert!(report.contains("- Removed interface `render`"));
+    }
+
+    #[test]
+    fn test_load_analyses_from_ndjson() {
+        let temp_file = tempfile::NamedTempFile::new().unwrap();
+        let mut lines: Vec<String> = [file("a.rs", &[]), file("b.rs", &["b"])]
+            .iter()
+            .map(|analysis| serde_json::to_string(analysis).unwrap())
+            .collect();
+        // A generate run ends with the project record, which isn't loaded as an analysis
+        lines.push(
+            r#"{"Project":{"project_overview":"A CLI","architecture_summary":"Flat","core_technologies":[],"main_interfaces":[],"development_considerations":[],"extension_points":[],"risk_factors":[],"license":null}}"#
+                .to_string(),
+        );
+        fs::write(temp_file.path(), lines.join("\n")).unwrap();
+
+        let analyses = load_analyses(temp_file.path()).unwrap();
+        assert_eq!(analyses.len(), 2);
+    }
+
+    #[test]
+    fn test_merge_replaces_changed_and_appends_new_files() {
+        let mut existing = vec![file("a.rs", &["old"]), file("b.rs", &["b"])];
+        let updated = vec![file("a.rs", &["new"]), file("c.rs", &["c"])]
+            .into_iter()
+            .filter_map(|analysis| match analysis {
+                ChildAnalysis::File(file) => Some(file),
+                ChildAnalysis::Directory(_) => None,
+            })
+            .collect();
+
+        let reused = merge_file_analyses(&mut existing, updated, &[]);
+        assert_eq!(reused, 1);
+
+        let files = files_by_path(&existing);
+        assert_eq!(files.len(), 3);
+        assert_eq!(files[Path::new("a.rs")].public_interfaces[0].name, "new");
+        assert_eq!(files[Path::new("b.rs")].public_interfaces[0].name, "b");
+        assert!(files.contains_key(Path::new("c.rs")));
+    }
+
+    #[test]
+    fn test_merge_drops_deleted_files() {
+        let mut existing = vec![file("a.rs", &["a"]), file("b.rs", &["b"])];
+
+        let reused = merge_file_analyses(&mut existing, vec![], &[PathBuf::from("b.rs")]);
+        assert_eq!(reused, 1);
+
+        let files = files_by_path(&existing);
+        assert_eq!(files.len(), 1);
+        assert!(files.contains_key(Path::new("a.rs")));
+    }
+}
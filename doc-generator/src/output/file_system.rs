@@ -1,9 +1,56 @@
 use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
 use std::path::{Path, PathBuf};
-use tokio::fs;
+use tracing::debug;
 
+use super::DocBackend;
+use super::sink::{FileSystemSink, OutputSink};
+use super::slug::{anchor_headings, anchor_link, slugify};
+use crate::analysis::display::RenderStyle;
 use crate::analysis::summary::{ChildAnalysis, DirectoryAnalysis, FileAnalysis, ProjectAnalysis};
 
+/// How to name the pages of files whose paths differ only by extension, like `foo.rs` and `foo.py`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CollisionStrategy {
+    /// Keep the source extension in colliding names: `foo.rs.md` and `foo.py.md`
+    #[default]
+    KeepExtension,
+    /// Number colliding names in path order: `foo.md`, then `foo-2.md`
+    Counter,
+}
+
+/// File name of directory index pages, optionally different for the project root
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexName {
+    /// The same name for the project root and every directory
+    Fixed(String),
+    /// One name for the project root's page and another for nested directories,
+    /// e.g. `index.md` at the root and `README.md` below it
+    RootVsNested { root: String, nested: String },
+}
+
+impl IndexName {
+    /// Name of the project root's page
+    pub fn root(&self) -> &str {
+        match self {
+            IndexName::Fixed(name) => name,
+            IndexName::RootVsNested { root, .. } => root,
+        }
+    }
+
+    /// Name of every other directory's index
+    pub fn nested(&self) -> &str {
+        match self {
+            IndexName::Fixed(name) => name,
+            IndexName::RootVsNested { nested, .. } => nested,
+        }
+    }
+}
+
 /// Configuration for markdown generation
 #[derive(Debug, Clone)]
 pub struct MarkdownConfig {
@@ -11,10 +58,22 @@ pub struct MarkdownConfig {
     pub output_dir: PathBuf,
     /// Whether to create directory index files (README.md or index.md)
     pub create_directory_indices: bool,
-    /// Name for directory index files
-    pub directory_index_name: String,
+    /// Name for directory index files, including the project overview at the root
+    pub directory_index_name: IndexName,
     /// Base path to strip from file paths when creating relative structure
     pub project_root: Option<PathBuf>,
+    /// Whether to append a Mermaid dependency diagram to the project README
+    pub include_diagrams: bool,
+    /// Whether to prepend YAML front-matter for static site generators
+    pub front_matter: bool,
+    /// Whether headers and interface labels carry emoji or plain ASCII markers
+    pub render_style: RenderStyle,
+    /// How to name pages that would otherwise overwrite each other
+    pub collision_strategy: CollisionStrategy,
+    /// Skip the "Documentation generated in" message
+    pub quiet: bool,
+    /// Keep hand-written regions of pages already on disk, replacing only the generated region
+    pub merge_existing: bool,
 }
 
 impl Default for MarkdownConfig {
@@ -22,8 +81,14 @@ impl Default for MarkdownConfig {
         Self {
             output_dir: PathBuf::from("./_docs"),
             create_directory_indices: true,
-            directory_index_name: "README.md".to_string(),
+            directory_index_name: IndexName::Fixed("README.md".to_string()),
             project_root: None,
+            include_diagrams: true,
+            front_matter: false,
+            render_style: RenderStyle::Emoji,
+            collision_strategy: CollisionStrategy::default(),
+            quiet: false,
+            merge_existing: false,
         }
     }
 }
@@ -62,16 +127,33 @@ impl MarkdownConfigBuilder {
     }
 
     #[allow(dead_code)]
-    /// Set the name for directory index files
+    /// Set the name for directory index files, and for the root's page unless
+    /// `root_index_name` gives it its own
     pub fn directory_index_name<S: Into<String>>(mut self, name: S) -> Self {
-        self.config.directory_index_name = name.into();
+        self.config.directory_index_name = match self.config.directory_index_name {
+            IndexName::Fixed(_) => IndexName::Fixed(name.into()),
+            IndexName::RootVsNested { root, .. } => IndexName::RootVsNested {
+                root,
+                nested: name.into(),
+            },
+        };
         self
     }
 
     #[allow(dead_code)]
     /// Use "index.md" instead of "README.md" for directory indices
-    pub fn use_index_md(mut self) -> Self {
-        self.config.directory_index_name = "index.md".to_string();
+    pub fn use_index_md(self) -> Self {
+        self.directory_index_name("index.md")
+    }
+
+    #[allow(dead_code)]
+    /// Give the project root's page a different name from nested directory indices
+    pub fn root_index_name<S: Into<String>>(mut self, name: S) -> Self {
+        let nested = self.config.directory_index_name.nested().to_string();
+        self.config.directory_index_name = IndexName::RootVsNested {
+            root: name.into(),
+            nested,
+        };
         self
     }
 
@@ -89,6 +171,43 @@ impl MarkdownConfigBuilder {
         self
     }
 
+    #[allow(dead_code)]
+    /// Set whether to append a Mermaid dependency diagram to the project README
+    pub fn include_diagrams(mut self, include: bool) -> Self {
+        self.config.include_diagrams = include;
+        self
+    }
+
+    /// Set whether to prepend YAML front-matter (title, path, type) to file and directory pages
+    pub fn front_matter(mut self, enabled: bool) -> Self {
+        self.config.front_matter = enabled;
+        self
+    }
+
+    /// Set whether pages use emoji markers or plain ASCII ones
+    pub fn render_style(mut self, style: RenderStyle) -> Self {
+        self.config.render_style = style;
+        self
+    }
+
+    /// Set how pages of files differing only by extension are named apart
+    pub fn collision_strategy(mut self, strategy: CollisionStrategy) -> Self {
+        self.config.collision_strategy = strategy;
+        self
+    }
+
+    /// Set whether to skip the message saying where the docs were written
+    pub fn quiet(mut self, quiet: bool) -> Self {
+        self.config.quiet = quiet;
+        self
+    }
+
+    /// Set whether to merge into existing pages, keeping their `auto-doc:keep` regions
+    pub fn merge_existing(mut self, merge: bool) -> Self {
+        self.config.merge_existing = merge;
+        self
+    }
+
     /// Build the final MarkdownConfig
     pub fn build(self) -> MarkdownConfig {
         self.config
@@ -107,94 +226,467 @@ impl MarkdownConfig {
         MarkdownConfigBuilder::new()
     }
 }
+/// Markers around hand-written regions that survive regeneration
+const KEEP_BEGIN: &str = "<!-- auto-doc:keep -->";
+const KEEP_END: &str = "<!-- /auto-doc:keep -->";
+/// Markers around the region replaced on every regeneration
+const GENERATED_BEGIN: &str = "<!-- auto-doc:begin -->";
+const GENERATED_END: &str = "<!-- /auto-doc:begin -->";
+
+/// Merge freshly `generated` content into the `existing` page, if there is one
+///
+/// A page that already has a generated region gets only that region replaced, leaving
+/// everything around it untouched. Otherwise the page's keep regions are carried over
+/// ahead of the generated region, and anything else in it is dropped.
+pub fn merge_with_existing(existing: Option<&str>, generated: &str) -> String {
+    let generated = format!(
+        "{}\n{}\n{}\n",
+        GENERATED_BEGIN,
+        generated.trim_end(),
+        GENERATED_END
+    );
+    let Some(existing) = existing else {
+        return generated;
+    };
+
+    if let Some((before, rest)) = existing.split_once(GENERATED_BEGIN)
+        && let Some((_, after)) = rest.split_once(GENERATED_END)
+    {
+        let after = after.strip_prefix('\n').unwrap_or(after);
+        return format!("{}{}{}", before, generated, after);
+    }
+
+    let mut merged = String::new();
+    let mut rest = existing;
+    while let Some((_, from_keep)) = rest.split_once(KEEP_BEGIN)
+        && let Some((kept, after)) = from_keep.split_once(KEEP_END)
+    {
+        let _ = writeln!(merged, "{}{}{}", KEEP_BEGIN, kept, KEEP_END);
+        rest = after;
+    }
+    merged.push_str(&generated);
+    merged
+}
+
+/// `page` without the front-matter block it starts with, if any
+fn strip_front_matter(page: &str) -> &str {
+    page.strip_prefix("---\n")
+        .and_then(|rest| rest.split_once("\n---\n"))
+        .map(|(_, body)| body.strip_prefix('\n').unwrap_or(body))
+        .unwrap_or(page)
+}
+
+/// YAML front-matter fields read by Hugo, Jekyll and Docusaurus
+#[derive(Debug, Serialize)]
+struct FrontMatter<'a> {
+    title: &'a str,
+    path: &'a str,
+    #[serde(rename = "type")]
+    kind: &'a str,
+}
+
 /// Generates markdown documentation from project analysis
 pub struct MarkdownGenerator {
     config: MarkdownConfig,
+    sink: Box<dyn OutputSink>,
 }
 
 impl MarkdownGenerator {
+    /// A generator writing pages under the configured output directory
     pub fn new(config: MarkdownConfig) -> Self {
-        Self { config }
+        let sink = Box::new(FileSystemSink::new(config.output_dir.clone()));
+        Self { config, sink }
     }
 
-    /// Generate markdown files for the entire project analysis
-    pub async fn generate_documentation(
+    /// Send pages to `sink` instead of the output directory
+    pub fn with_sink<S: OutputSink + 'static>(mut self, sink: S) -> Self {
+        self.sink = Box::new(sink);
+        self
+    }
+
+    /// Generate project-level overview markdown using Display impl
+    async fn generate_project_overview(
         &self,
-        project_analysis: &Option<ProjectAnalysis>,
-        child_analyses: &[ChildAnalysis],
+        analysis: &ProjectAnalysis,
+        children: &[ChildAnalysis],
+        pages: &HashMap<PathBuf, PathBuf>,
     ) -> Result<()> {
-        // Create output directory
-        fs::create_dir_all(&self.config.output_dir).await?;
+        let mut content = analysis.render(self.config.render_style);
+        content.push_str(&self.render_table_of_contents(children, pages));
+        if self.config.include_diagrams {
+            content.push_str(&self.render_dependency_diagram(children));
+        }
+        self.write_page(
+            Path::new(self.config.directory_index_name.root()),
+            "",
+            &content,
+        )
+        .await
+    }
 
-        // Generate project overview using Display implementation
-        if let Some(project_analysis) = project_analysis {
-            self.generate_project_overview(project_analysis).await?;
+    /// Write a page to the sink, merging it into the page on disk if `merge_existing` is set
+    ///
+    /// The front-matter always opens the file, ahead of any merged regions, where static
+    /// site generators look for it.
+    async fn write_page(&self, path: &Path, front_matter: &str, content: &str) -> Result<()> {
+        let content = &anchor_headings(content);
+        if !self.config.merge_existing {
+            let page = format!("{}{}", front_matter, content);
+            return self.sink.write_page(path, &page).await;
         }
 
-        for analysis in child_analyses {
-            match &analysis {
-                ChildAnalysis::Directory(dir) => {
-                    self.generate_directory_markdown(dir).await?;
+        let existing = match self.sink.destination() {
+            Some(destination) => tokio::fs::read_to_string(destination.join(path)).await.ok(),
+            None => None,
+        };
+        // The front-matter of the previous run is replaced, not carried over
+        let existing = match self.config.front_matter {
+            true => existing.as_deref().map(strip_front_matter),
+            false => existing.as_deref(),
+        };
+        let merged = merge_with_existing(existing, content);
+        self.sink
+            .write_page(path, &format!("{}{}", front_matter, merged))
+            .await
+    }
+
+    /// Render a Mermaid graph of directories and their cross-dependencies
+    ///
+    /// Directories sharing an external dependency are linked by an undirected
+    /// edge labelled with the shared dependencies. A directory whose summaries
+    /// mention another directory's public interface gets a directed edge to it.
+    pub fn render_dependency_diagram(&self, children: &[ChildAnalysis]) -> String {
+        let directories: Vec<&DirectoryAnalysis> = children
+            .iter()
+            .filter_map(|child| match child {
+                ChildAnalysis::Directory(dir) => Some(dir),
+                ChildAnalysis::File(_) => None,
+            })
+            .collect();
+
+        if directories.is_empty() {
+            return String::new();
+        }
+
+        let mut diagram = format!(
+            "\n## {}\n\n```mermaid\ngraph LR\n",
+            self.config.render_style.marker("🗺️", "Dependency Diagram")
+        );
+        for (i, dir) in directories.iter().enumerate() {
+            let label = self.make_relative_path(&dir.directory_path);
+            let label = if label.as_os_str().is_empty() {
+                ".".to_string()
+            } else {
+                label.display().to_string().replace('"', "'")
+            };
+            let _ = writeln!(diagram, "    d{}[\"{}\"]", i, label);
+        }
+
+        let mentions: Vec<BTreeSet<&str>> = directories
+            .iter()
+            .map(|dir| {
+                std::iter::once(&dir.summary)
+                    .chain(dir.child_summaries.iter())
+                    .flat_map(|text| text.split(|c: char| !c.is_alphanumeric() && c != '_'))
+                    .filter(|word| !word.is_empty())
+                    .collect()
+            })
+            .collect();
+
+        for (i, a) in directories.iter().enumerate() {
+            for (j, b) in directories.iter().enumerate().skip(i + 1) {
+                let shared: Vec<&str> = a
+                    .external_dependencies
+                    .iter()
+                    .filter(|dep| b.external_dependencies.contains(dep))
+                    .map(String::as_str)
+                    .collect();
+                if !shared.is_empty() {
+                    let _ = writeln!(diagram, "    d{} ---|\"{}\"| d{}", i, shared.join(", "), j);
                 }
-                ChildAnalysis::File(file) => {
-                    self.generate_file_markdown(file).await?;
+            }
+        }
+
+        let mut references = BTreeSet::new();
+        for (i, words) in mentions.iter().enumerate() {
+            for (j, target) in directories.iter().enumerate() {
+                if i != j
+                    && target
+                        .public_interfaces
+                        .iter()
+                        .any(|interface| words.contains(interface.name.as_str()))
+                {
+                    references.insert((i, j));
                 }
             }
         }
+        for (i, j) in references {
+            let _ = writeln!(diagram, "    d{} --> d{}", i, j);
+        }
 
-        println!(
-            "Documentation generated in: {}",
-            self.config.output_dir.display()
-        );
-        Ok(())
+        diagram.push_str("```\n");
+        diagram
     }
 
-    /// Generate project-level overview markdown using Display impl
-    async fn generate_project_overview(&self, analysis: &ProjectAnalysis) -> Result<()> {
-        let content = analysis.to_string();
-        let output_path = self.config.output_dir.join("README.md");
-        fs::write(output_path, content).await?;
-        Ok(())
+    /// Generate markdown for a directory analysis using Display impl
+    async fn generate_directory_markdown(
+        &self,
+        analysis: &DirectoryAnalysis,
+        children: &[ChildAnalysis],
+        pages: &HashMap<PathBuf, PathBuf>,
+    ) -> Result<()> {
+        let Some(index_path) = pages.get(&analysis.directory_path) else {
+            return Ok(());
+        };
+        let front_matter = self.render_front_matter(&analysis.directory_path, "directory")?;
+        let mut content = analysis.render(self.config.render_style);
+        content.push_str(&self.render_directory_links(analysis, children, pages));
+
+        self.write_page(index_path, &front_matter, &content).await
     }
 
-    /// Generate markdown for a directory analysis using Display impl
-    async fn generate_directory_markdown(&self, analysis: &DirectoryAnalysis) -> Result<()> {
-        let content = analysis.to_string();
+    /// Generate markdown for a file analysis using Display impl
+    async fn generate_file_markdown(
+        &self,
+        analysis: &FileAnalysis,
+        pages: &HashMap<PathBuf, PathBuf>,
+    ) -> Result<()> {
+        let Some(output_path) = pages.get(&analysis.file_path) else {
+            return Ok(());
+        };
+        let front_matter = self.render_front_matter(&analysis.file_path, "file")?;
+        let content = analysis.render(self.config.render_style);
 
-        // Create the directory structure in output
-        let relative_path = self.make_relative_path(&analysis.directory_path);
-        let output_dir = self.config.output_dir.join(&relative_path);
-        fs::create_dir_all(&output_dir).await?;
+        self.write_page(output_path, &front_matter, &content).await
+    }
 
-        // Write directory index file
-        if self.config.create_directory_indices {
-            let index_path = output_dir.join(&self.config.directory_index_name);
-            fs::write(index_path, content).await?;
+    /// YAML front-matter block for a page, empty unless enabled in the config
+    ///
+    /// The title is the last path component: the file name or directory name.
+    fn render_front_matter(&self, path: &Path, kind: &str) -> Result<String> {
+        if !self.config.front_matter {
+            return Ok(String::new());
         }
 
-        Ok(())
+        let relative_path = self.make_relative_path(path);
+        let title = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
+        let yaml = serde_yaml::to_string(&FrontMatter {
+            title: &title,
+            path: &relative_path.to_string_lossy(),
+            kind,
+        })?;
+
+        Ok(format!("---\n{}---\n\n", yaml))
     }
 
-    /// Generate markdown for a file analysis using Display impl
-    async fn generate_file_markdown(&self, analysis: &FileAnalysis) -> Result<()> {
-        let content = analysis.to_string();
+    /// Output path of every child's page, relative to the output directory, keyed by
+    /// the child's source path
+    ///
+    /// Directories are only included when directory indices are enabled.
+    fn page_map(&self, children: &[ChildAnalysis]) -> HashMap<PathBuf, PathBuf> {
+        let mut pages = self.file_pages(&file_paths(children));
+        for child in children {
+            if let ChildAnalysis::Directory(dir) = child
+                && let Some(index_path) = self.directory_index_path(&dir.directory_path)
+            {
+                pages.insert(dir.directory_path.clone(), index_path);
+            }
+        }
+        pages
+    }
+
+    /// Output path of each file's markdown, relative to the output directory
+    ///
+    /// The extension is replaced with `.md`, unless several of `files` would then
+    /// get the same page, in which case the collision strategy names them apart.
+    fn file_pages(&self, files: &[&Path]) -> HashMap<PathBuf, PathBuf> {
+        let mut by_page: HashMap<PathBuf, Vec<&Path>> = HashMap::new();
+        for file in files {
+            by_page.entry(self.page_path(file)).or_default().push(file);
+        }
+
+        let mut pages = HashMap::new();
+        for (output_path, mut colliding) in by_page {
+            colliding.sort();
+            colliding.dedup();
+            if let [path] = colliding[..] {
+                pages.insert(path.to_path_buf(), output_path);
+                continue;
+            }
+            for (index, path) in colliding.into_iter().enumerate() {
+                let page = self.colliding_page_path(path, &output_path, index);
+                pages.insert(path.to_path_buf(), page);
+            }
+        }
+        pages
+    }
 
-        // Create output path with .md extension
-        let relative_path = self.make_relative_path(&analysis.file_path);
-        let mut output_path = self.config.output_dir.join(&relative_path);
+    /// Page of the `index`-th (in path order) of several files that would all get `output_path`
+    fn colliding_page_path(&self, path: &Path, output_path: &Path, index: usize) -> PathBuf {
+        match self.config.collision_strategy {
+            CollisionStrategy::KeepExtension => {
+                let file_name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                output_path.with_file_name(format!("{}.md", file_name))
+            }
+            CollisionStrategy::Counter if index == 0 => output_path.to_path_buf(),
+            CollisionStrategy::Counter => {
+                let stem = output_path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                output_path.with_file_name(format!("{}-{}.md", stem, index + 1))
+            }
+        }
+    }
 
-        // Replace original extension with .md
+    /// A file's page with its extension replaced by `.md`, before collisions are resolved
+    fn page_path(&self, path: &Path) -> PathBuf {
+        let mut output_path = self.make_relative_path(path);
         if let Some(file_name) = output_path.file_stem() {
             output_path.set_file_name(format!("{}.md", file_name.to_string_lossy()));
         }
+        output_path
+    }
+
+    /// Output path of a directory's index, relative to the output directory
+    ///
+    /// Returns `None` when directory indices are disabled.
+    fn directory_index_path(&self, path: &Path) -> Option<PathBuf> {
+        self.config.create_directory_indices.then(|| {
+            let relative = self.make_relative_path(path);
+            let name = match relative.as_os_str().is_empty() {
+                true => self.config.directory_index_name.root(),
+                false => self.config.directory_index_name.nested(),
+            };
+            relative.join(name)
+        })
+    }
+
+    /// Output paths of the project README (if `include_project`), each directory's
+    /// index and each file's page, in that order
+    pub fn plan_paths(
+        &self,
+        include_project: bool,
+        files: &[PathBuf],
+        directories: &[PathBuf],
+    ) -> Vec<PathBuf> {
+        let project =
+            include_project.then(|| PathBuf::from(self.config.directory_index_name.root()));
+        let directories = directories
+            .iter()
+            .filter_map(|path| self.directory_index_path(path));
+        let file_refs: Vec<&Path> = files.iter().map(PathBuf::as_path).collect();
+        let pages = self.file_pages(&file_refs);
+        let files = files.iter().filter_map(|path| pages.get(path).cloned());
+
+        project
+            .into_iter()
+            .chain(directories)
+            .chain(files)
+            .map(|path| self.config.output_dir.join(path))
+            .collect()
+    }
 
-        // Ensure parent directory exists
-        if let Some(parent) = output_path.parent() {
-            fs::create_dir_all(parent).await?;
+    /// Nested table of contents linking every generated page from the project README
+    fn render_table_of_contents(
+        &self,
+        children: &[ChildAnalysis],
+        pages: &HashMap<PathBuf, PathBuf>,
+    ) -> String {
+        let mut entries: Vec<(PathBuf, Option<(String, String)>)> = children
+            .iter()
+            .map(|child| {
+                let source = child_path(child);
+                let link = pages
+                    .get(source)
+                    .map(|page| (markdown_link(page), self.page_anchor(child)));
+                (self.make_relative_path(source), link)
+            })
+            .filter(|(source, _)| !source.as_os_str().is_empty())
+            .collect();
+
+        if entries.is_empty() {
+            return String::new();
         }
+        entries.sort();
 
-        fs::write(output_path, content).await?;
-        Ok(())
+        let mut toc = format!(
+            "\n## {}\n\n",
+            self.config.render_style.marker("🧭", "Contents")
+        );
+        for (source, link) in entries {
+            let indent = "  ".repeat(source.components().count() - 1);
+            let name = source
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| source.display().to_string());
+            let _ = match link {
+                Some((page, anchor)) => writeln!(
+                    toc,
+                    "{}- {}",
+                    indent,
+                    anchor_link(&name, Some(&page), &anchor)
+                ),
+                None => writeln!(toc, "{}- {}", indent, name),
+            };
+        }
+        toc
+    }
+
+    /// Links from a directory index to the pages of its direct children
+    fn render_directory_links(
+        &self,
+        dir: &DirectoryAnalysis,
+        children: &[ChildAnalysis],
+        pages: &HashMap<PathBuf, PathBuf>,
+    ) -> String {
+        let base = self.make_relative_path(&dir.directory_path);
+        let mut links: Vec<(String, PathBuf, String)> = children
+            .iter()
+            .filter(|child| child_path(child).parent() == Some(dir.directory_path.as_path()))
+            .filter_map(|child| {
+                let source = child_path(child);
+                let name = source.file_name()?.to_string_lossy().to_string();
+                let target = pages.get(source)?.strip_prefix(&base).ok()?.to_path_buf();
+                Some((name, target, self.page_anchor(child)))
+            })
+            .collect();
+
+        if links.is_empty() {
+            return String::new();
+        }
+        links.sort();
+
+        let mut section = format!(
+            "\n## {}\n\n",
+            self.config.render_style.marker("🔗", "Contents")
+        );
+        for (name, target, anchor) in links {
+            let _ = writeln!(
+                section,
+                "- {}",
+                anchor_link(&name, Some(&markdown_link(&target)), &anchor)
+            );
+        }
+        section
+    }
+
+    /// Anchor of the title heading at the top of a child's page
+    ///
+    /// The title is the first heading `anchor_headings` slugs on the page, so it never
+    /// needs a numeric suffix.
+    fn page_anchor(&self, child: &ChildAnalysis) -> String {
+        let title = match child {
+            ChildAnalysis::File(file) => file.title(self.config.render_style),
+            ChildAnalysis::Directory(dir) => dir.title(self.config.render_style),
+        };
+        slugify(&title)
     }
 
     /// Convert paths to relative paths for output structure
@@ -212,8 +704,8 @@ impl MarkdownGenerator {
                 .or_else(|_| path.strip_prefix("./"))
                 .unwrap_or(path)
                 .to_path_buf();
-            println!(
-                "DEBUG: No project root, processed '{}' -> '{}'",
+            debug!(
+                "No project root, processed '{}' -> '{}'",
                 path.display(),
                 result.display()
             );
@@ -221,3 +713,577 @@ impl MarkdownGenerator {
         }
     }
 }
+
+/// Source path of a file or directory analysis
+fn child_path(child: &ChildAnalysis) -> &Path {
+    match child {
+        ChildAnalysis::File(file) => &file.file_path,
+        ChildAnalysis::Directory(dir) => &dir.directory_path,
+    }
+}
+
+/// Paths of every file analysis among `children`
+fn file_paths(children: &[ChildAnalysis]) -> Vec<&Path> {
+    children
+        .iter()
+        .filter_map(|child| match child {
+            ChildAnalysis::File(file) => Some(file.file_path.as_path()),
+            ChildAnalysis::Directory(_) => None,
+        })
+        .collect()
+}
+
+/// Planned output paths that more than one page would be written to, e.g. `main.rs` and `main.py`
+pub fn output_collisions(planned: &[PathBuf]) -> BTreeSet<&PathBuf> {
+    let mut seen = BTreeSet::new();
+    planned.iter().filter(|path| !seen.insert(*path)).collect()
+}
+
+/// Format a relative path as a markdown link target
+fn markdown_link(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy().replace(' ', "%20"))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[async_trait]
+impl DocBackend for MarkdownGenerator {
+    /// Generate markdown files for the entire project analysis
+    async fn generate_documentation(
+        &self,
+        project_analysis: &Option<ProjectAnalysis>,
+        child_analyses: &[ChildAnalysis],
+    ) -> Result<()> {
+        let pages = self.page_map(child_analyses);
+
+        // Generate project overview using Display implementation
+        if let Some(project_analysis) = project_analysis {
+            self.generate_project_overview(project_analysis, child_analyses, &pages)
+                .await?;
+        }
+
+        for analysis in child_analyses {
+            match &analysis {
+                ChildAnalysis::Directory(dir) => {
+                    self.generate_directory_markdown(dir, child_analyses, &pages)
+                        .await?;
+                }
+                ChildAnalysis::File(file) => {
+                    self.generate_file_markdown(file, &pages).await?;
+                }
+            }
+        }
+
+        if let Some(destination) = self.sink.destination()
+            && !self.config.quiet
+        {
+            println!("Documentation generated in: {}", destination.display());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::summary::{Interface, InterfaceType};
+    use crate::test_support::LogBuffer;
+    use std::sync::{Arc, Mutex};
+
+    fn directory(path: &str, summary: &str, deps: &[&str], interfaces: &[&str]) -> ChildAnalysis {
+        ChildAnalysis::Directory(DirectoryAnalysis {
+            directory_path: PathBuf::from(path),
+            depth_level: 1,
+            summary: summary.to_string(),
+            child_summaries: vec![],
+            key_components: vec![],
+            external_dependencies: deps.iter().map(|d| d.to_string()).collect(),
+            public_interfaces: interfaces
+                .iter()
+                .map(|name| Interface {
+                    name: name.to_string(),
+                    interface_type: InterfaceType::Struct,
+                    description: String::new(),
+                })
+                .collect(),
+        })
+    }
+
+    #[test]
+    fn test_dependency_diagram_edges() {
+        let generator =
+            MarkdownGenerator::new(MarkdownConfig::builder().project_root("/repo").build());
+        let children = vec![
+            directory(
+                "/repo/src/cli",
+                "Drives the LlmPool",
+                &["clap", "tokio"],
+                &[],
+            ),
+            directory("/repo/src/pool", "Client pooling", &["tokio"], &["LlmPool"]),
+        ];
+
+        let diagram = generator.render_dependency_diagram(&children);
+        assert!(diagram.contains("```mermaid\ngraph LR\n"));
+        assert!(diagram.contains("d0[\"src/cli\"]"));
+        assert!(diagram.contains("d1[\"src/pool\"]"));
+        assert!(diagram.contains("d0 ---|\"tokio\"| d1"));
+        assert!(diagram.contains("d0 --> d1"));
+        assert!(!diagram.contains("d1 --> d0"));
+    }
+
+    #[test]
+    fn test_dependency_diagram_empty_without_directories() {
+        let generator = MarkdownGenerator::new(MarkdownConfig::default());
+        assert!(generator.render_dependency_diagram(&[]).is_empty());
+    }
+
+    fn file(path: &str) -> ChildAnalysis {
+        ChildAnalysis::File(FileAnalysis {
+            file_path: PathBuf::from(path),
+            file_type: "rs".to_string(),
+            summary: String::new(),
+            external_dependencies: vec![],
+            public_interfaces: vec![],
+            line_count: None,
+            byte_size: None,
+            signatures: vec![],
+        })
+    }
+
+    fn project_tree() -> Vec<ChildAnalysis> {
+        vec![
+            file("/repo/src/output/diff.rs"),
+            directory("/repo/src/output", "", &[], &[]),
+            file("/repo/src/main.rs"),
+            directory("/repo/src", "", &[], &[]),
+        ]
+    }
+
+    #[test]
+    fn test_table_of_contents_is_nested() {
+        for index in ["README.md", "index.md"] {
+            let generator = MarkdownGenerator::new(
+                MarkdownConfig::builder()
+                    .project_root("/repo")
+                    .directory_index_name(index)
+                    .build(),
+            );
+
+            let children = project_tree();
+            let toc = generator.render_table_of_contents(&children, &generator.page_map(&children));
+            let expected = format!(
+                "- [src](src/{index}#-directory-reposrc)\n  - [main.rs](src/main.md#-file-analysis-reposrcmainrs)\n  - [output](src/output/{index}#-directory-reposrcoutput)\n    - [diff.rs](src/output/diff.md#-file-analysis-reposrcoutputdiffrs)\n"
+            );
+            assert!(toc.ends_with(&expected), "unexpected toc:\n{}", toc);
+        }
+    }
+
+    #[test]
+    fn test_directory_links_to_direct_children() {
+        let generator = MarkdownGenerator::new(
+            MarkdownConfig::builder()
+                .project_root("/repo")
+                .use_index_md()
+                .build(),
+        );
+        let children = project_tree();
+        let ChildAnalysis::Directory(src) = &children[3] else {
+            unreachable!()
+        };
+
+        let links =
+            generator.render_directory_links(src, &children, &generator.page_map(&children));
+        assert!(links.ends_with(
+            "- [main.rs](main.md#-file-analysis-reposrcmainrs)\n- [output](output/index.md#-directory-reposrcoutput)\n"
+        ));
+        assert!(!links.contains("diff"));
+    }
+
+    #[test]
+    fn test_plan_paths_lists_every_page() {
+        let generator = MarkdownGenerator::new(
+            MarkdownConfig::builder()
+                .output_dir("docs")
+                .project_root("/repo")
+                .build(),
+        );
+
+        let planned = generator.plan_paths(
+            false,
+            &["/repo/src/output/diff.rs", "/repo/src/main.rs"].map(PathBuf::from),
+            &["/repo/src/output", "/repo/src"].map(PathBuf::from),
+        );
+        assert_eq!(
+            planned,
+            [
+                "docs/src/output/README.md",
+                "docs/src/README.md",
+                "docs/src/output/diff.md",
+                "docs/src/main.md",
+            ]
+            .map(PathBuf::from)
+        );
+        assert!(output_collisions(&planned).is_empty());
+
+        let planned = generator.plan_paths(
+            true,
+            &[
+                PathBuf::from("/repo/main.rs"),
+                PathBuf::from("/repo/main.py"),
+            ],
+            &[],
+        );
+        assert_eq!(
+            planned,
+            ["docs/README.md", "docs/main.rs.md", "docs/main.py.md"].map(PathBuf::from)
+        );
+        assert!(output_collisions(&planned).is_empty());
+    }
+
+    #[test]
+    fn test_root_and_nested_indices_can_differ() {
+        let generator = MarkdownGenerator::new(
+            MarkdownConfig::builder()
+                .output_dir("docs")
+                .project_root("/repo")
+                .root_index_name("index.md")
+                .build(),
+        );
+        let planned = generator.plan_paths(
+            true,
+            &[PathBuf::from("/repo/src/main.rs")],
+            &[PathBuf::from("/repo/src")],
+        );
+        assert_eq!(
+            planned,
+            ["docs/index.md", "docs/src/README.md", "docs/src/main.md"].map(PathBuf::from)
+        );
+
+        // The nested name can be changed independently, in either order
+        let config = MarkdownConfig::builder()
+            .directory_index_name("_index.md")
+            .root_index_name("index.md")
+            .build();
+        assert_eq!(
+            config.directory_index_name,
+            IndexName::RootVsNested {
+                root: "index.md".to_string(),
+                nested: "_index.md".to_string()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_same_stem_files_get_separate_pages() -> Result<()> {
+        let children = vec![file("/repo/src/foo.rs"), file("/repo/src/foo.py")];
+        let strategies = [
+            (
+                CollisionStrategy::KeepExtension,
+                ["src/foo.rs.md", "src/foo.py.md"],
+            ),
+            (CollisionStrategy::Counter, ["src/foo-2.md", "src/foo.md"]),
+        ];
+
+        for (strategy, expected) in strategies {
+            let temp_dir = tempfile::TempDir::new()?;
+            let generator = MarkdownGenerator::new(
+                MarkdownConfig::builder()
+                    .output_dir(temp_dir.path())
+                    .project_root("/repo")
+                    .collision_strategy(strategy)
+                    .build(),
+            );
+            generator.generate_documentation(&None, &children).await?;
+
+            for page in expected {
+                assert!(
+                    temp_dir.path().join(page).exists(),
+                    "{:?}: {}",
+                    strategy,
+                    page
+                );
+            }
+            let toc = generator.render_table_of_contents(&children, &generator.page_map(&children));
+            assert!(toc.contains(&format!("({}#", expected[0])), "{}", toc);
+        }
+        Ok(())
+    }
+
+    /// Sink keeping every page in memory, in the order written
+    #[derive(Clone, Default)]
+    struct MemorySink(Arc<Mutex<Vec<(PathBuf, String)>>>);
+
+    #[async_trait]
+    impl OutputSink for MemorySink {
+        async fn write_page(&self, path: &Path, content: &str) -> Result<()> {
+            self.0
+                .lock()
+                .unwrap()
+                .push((path.to_path_buf(), content.to_string()));
+            Ok(())
+        }
+
+        fn destination(&self) -> Option<&Path> {
+            None
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pages_go_to_the_sink() -> Result<()> {
+        let sink = MemorySink::default();
+        let generator = MarkdownGenerator::new(
+            MarkdownConfig::builder()
+                .output_dir("/nonexistent/docs")
+                .project_root("/repo")
+                .build(),
+        )
+        .with_sink(sink.clone());
+
+        generator
+            .generate_documentation(&None, &project_tree())
+            .await?;
+
+        let pages = sink.0.lock().unwrap();
+        let paths: Vec<&PathBuf> = pages.iter().map(|(path, _)| path).collect();
+        assert_eq!(
+            paths,
+            [
+                "src/output/diff.md",
+                "src/output/README.md",
+                "src/main.md",
+                "src/README.md",
+            ]
+            .map(PathBuf::from)
+            .iter()
+            .collect::<Vec<_>>()
+        );
+        assert!(pages[3].1.contains("[main.rs](main.md#"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_links_resolve_to_heading_anchors() -> Result<()> {
+        let sink = MemorySink::default();
+        let generator = MarkdownGenerator::new(
+            MarkdownConfig::builder()
+                .project_root("/repo")
+                .render_style(RenderStyle::Plain)
+                .build(),
+        )
+        .with_sink(sink.clone());
+        let project = ProjectAnalysis {
+            project_overview: "A CLI".to_string(),
+            architecture_summary: "Flat".to_string(),
+            core_technologies: vec![],
+            main_interfaces: vec![],
+            development_considerations: vec![],
+            extension_points: vec![],
+            risk_factors: vec![],
+            license: None,
+        };
+
+        generator
+            .generate_documentation(&Some(project), &project_tree())
+            .await?;
+
+        let pages: HashMap<PathBuf, String> = sink.0.lock().unwrap().iter().cloned().collect();
+        let mut resolved = 0;
+        for (page, content) in &pages {
+            for link in content.split("](").skip(1) {
+                let (target, _) = link.split_once(')').unwrap();
+                let (document, anchor) = target.split_once('#').expect("links carry an anchor");
+                let document = page.parent().unwrap().join(document);
+                let linked = pages.get(&document).expect("link to a generated page");
+                assert!(
+                    linked.contains(&format!("<a id=\"{}\"></a>\n# ", anchor)),
+                    "{} -> {}",
+                    page.display(),
+                    target
+                );
+                resolved += 1;
+            }
+        }
+        // Four TOC entries in the README and three links from the directory indices
+        assert_eq!(resolved, 7);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_no_debug_output_without_project_root() -> Result<()> {
+        let logs = LogBuffer::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_writer({
+                let logs = logs.clone();
+                move || logs.clone()
+            })
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let sink = MemorySink::default();
+        let generator = MarkdownGenerator::new(MarkdownConfig::builder().no_project_root().build())
+            .with_sink(sink.clone());
+        generator
+            .generate_documentation(&None, &project_tree())
+            .await?;
+
+        let pages = sink.0.lock().unwrap();
+        assert!(
+            pages
+                .iter()
+                .any(|(path, _)| path == Path::new("repo/src/main.md"))
+        );
+        assert!(pages.iter().all(|(_, content)| !content.contains("DEBUG:")));
+
+        // The path mapping is only traced, at debug level
+        let logs = logs.contents();
+        let mapping = logs
+            .lines()
+            .find(|line| {
+                line.contains(
+                    "No project root, processed '/repo/src/main.rs' -> 'repo/src/main.rs'",
+                )
+            })
+            .expect("path mapping traced");
+        assert!(mapping.contains("DEBUG"));
+        assert!(logs.lines().all(|line| line.contains("DEBUG")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_table_of_contents_without_indices() {
+        let generator = MarkdownGenerator::new(
+            MarkdownConfig::builder()
+                .project_root("/repo")
+                .no_directory_indices()
+                .build(),
+        );
+
+        let children = project_tree();
+        let toc = generator.render_table_of_contents(&children, &generator.page_map(&children));
+        assert!(toc.contains("\n- src\n"));
+        assert!(toc.contains("  - [main.rs](src/main.md#-file-analysis-reposrcmainrs)\n"));
+    }
+
+    #[tokio::test]
+    async fn test_front_matter_prepended_to_pages() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let generator = MarkdownGenerator::new(
+            MarkdownConfig::builder()
+                .output_dir(temp_dir.path())
+                .project_root("/repo")
+                .front_matter(true)
+                .build(),
+        );
+
+        generator
+            .generate_documentation(&None, &project_tree())
+            .await?;
+
+        let content = std::fs::read_to_string(temp_dir.path().join("src/main.md"))?;
+        let block = content
+            .strip_prefix("---\n")
+            .and_then(|rest| rest.split_once("---\n"))
+            .map(|(yaml, _)| yaml)
+            .expect("page should start with a front-matter block");
+        let front_matter: serde_yaml::Value = serde_yaml::from_str(block)?;
+        assert_eq!(front_matter["title"], "main.rs");
+        assert_eq!(front_matter["path"], "src/main.rs");
+        assert_eq!(front_matter["type"], "file");
+
+        let index = std::fs::read_to_string(temp_dir.path().join("src/README.md"))?;
+        assert!(index.starts_with("---\ntitle: src\npath: src\ntype: directory\n---\n\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_carries_keep_regions_over() {
+        let existing =
+            "# Notes\n<!-- auto-doc:keep -->\nHand-written\n<!-- /auto-doc:keep -->\nOld docs\n";
+        assert_eq!(
+            merge_with_existing(Some(existing), "New docs\n"),
+            "<!-- auto-doc:keep -->\nHand-written\n<!-- /auto-doc:keep -->\n\
+             <!-- auto-doc:begin -->\nNew docs\n<!-- /auto-doc:begin -->\n"
+        );
+        assert_eq!(
+            merge_with_existing(None, "New docs"),
+            "<!-- auto-doc:begin -->\nNew docs\n<!-- /auto-doc:begin -->\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merge_existing_preserves_and_replaces() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let readme = temp_dir.path().join("src/README.md");
+        std::fs::create_dir_all(readme.parent().unwrap())?;
+        std::fs::write(
+            &readme,
+            "<!-- auto-doc:keep -->\nRun `make setup` first.\n<!-- /auto-doc:keep -->\n\
+             <!-- auto-doc:begin -->\nStale summary\n<!-- /auto-doc:begin -->\n\
+             Footer\n",
+        )?;
+        let generator = MarkdownGenerator::new(
+            MarkdownConfig::builder()
+                .output_dir(temp_dir.path())
+                .project_root("/repo")
+                .merge_existing(true)
+                .build(),
+        );
+
+        for _ in 0..2 {
+            generator
+                .generate_documentation(&None, &project_tree())
+                .await?;
+
+            let content = std::fs::read_to_string(&readme)?;
+            assert!(content.starts_with(
+                "<!-- auto-doc:keep -->\nRun `make setup` first.\n<!-- /auto-doc:keep -->\n<!-- auto-doc:begin -->\n"
+            ));
+            assert!(content.ends_with("<!-- /auto-doc:begin -->\nFooter\n"));
+            assert!(!content.contains("Stale summary"));
+            assert!(content.contains("[main.rs](main.md#"));
+            assert_eq!(content.matches("<!-- auto-doc:begin -->").count(), 1);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_merged_pages_open_with_front_matter() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let readme = temp_dir.path().join("src/README.md");
+        std::fs::create_dir_all(readme.parent().unwrap())?;
+        std::fs::write(
+            &readme,
+            "<!-- auto-doc:keep -->\nRun `make setup` first.\n<!-- /auto-doc:keep -->\n",
+        )?;
+        let generator = MarkdownGenerator::new(
+            MarkdownConfig::builder()
+                .output_dir(temp_dir.path())
+                .project_root("/repo")
+                .front_matter(true)
+                .merge_existing(true)
+                .build(),
+        );
+
+        for _ in 0..2 {
+            generator
+                .generate_documentation(&None, &project_tree())
+                .await?;
+
+            let content = std::fs::read_to_string(&readme)?;
+            assert!(content.starts_with(
+                "---\ntitle: src\npath: src\ntype: directory\n---\n\n<!-- auto-doc:keep -->\n"
+            ));
+            assert_eq!(content.matches("title: src").count(), 1);
+            assert!(
+                content.contains("<!-- auto-doc:begin -->\n<a id=\"-directory-reposrc\"></a>\n# ")
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -1 +1,21 @@
+pub mod diff;
 pub mod file_system;
+pub mod ndjson;
+pub mod sink;
+pub mod slug;
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::analysis::summary::{ChildAnalysis, ProjectAnalysis};
+
+/// A destination that renders completed analyses into documentation
+#[async_trait]
+pub trait DocBackend: Send + Sync {
+    /// Write documentation for the project and every child analysis
+    async fn generate_documentation(
+        &self,
+        project_analysis: &Option<ProjectAnalysis>,
+        child_analyses: &[ChildAnalysis],
+    ) -> Result<()>;
+}
@@ -0,0 +1,154 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use super::DocBackend;
+use crate::analysis::summary::{ChildAnalysis, DirectoryAnalysis, FileAnalysis, ProjectAnalysis};
+
+/// One NDJSON line: a file or directory analysis, tagged as in `ChildAnalysis`, or the
+/// project summary that ends a run
+#[derive(Debug, Serialize, Deserialize)]
+pub enum NdjsonRecord {
+    File(FileAnalysis),
+    Directory(DirectoryAnalysis),
+    Project(ProjectAnalysis),
+}
+
+/// Writes each analysis as a single line of JSON (NDJSON)
+///
+/// Records can be streamed with `write_child` as analyses complete, then closed off with
+/// `write_project`, or written all at once through `DocBackend`.
+pub struct NdjsonGenerator {
+    /// File to write to, stdout when not set
+    output_file: Option<PathBuf>,
+    /// Opened on the first record, so an existing file is only replaced once there is output
+    writer: Mutex<Option<Box<dyn Write + Send>>>,
+}
+
+impl NdjsonGenerator {
+    pub fn new(output_file: Option<PathBuf>) -> Self {
+        Self {
+            output_file,
+            writer: Mutex::new(None),
+        }
+    }
+
+    /// Write a file or directory analysis as soon as it is ready
+    pub fn write_child(&self, analysis: &ChildAnalysis) -> Result<()> {
+        self.write_record(analysis)
+    }
+
+    /// Write the project summary, the last record of a run
+    pub fn write_project(&self, analysis: &ProjectAnalysis) -> Result<()> {
+        self.write_record(&NdjsonRecord::Project(analysis.clone()))
+    }
+
+    /// Write one JSON object and a newline, flushing so consumers can stream the lines
+    fn write_record<T: Serialize>(&self, record: &T) -> Result<()> {
+        let mut writer = self
+            .writer
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let writer = match &mut *writer {
+            Some(writer) => writer,
+            None => writer.insert(match &self.output_file {
+                Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+                None => Box::new(io::stdout()),
+            }),
+        };
+        serde_json::to_writer(&mut *writer, record)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl DocBackend for NdjsonGenerator {
+    async fn generate_documentation(
+        &self,
+        project_analysis: &Option<ProjectAnalysis>,
+        child_analyses: &[ChildAnalysis],
+    ) -> Result<()> {
+        for analysis in child_analyses {
+            self.write_child(analysis)?;
+        }
+        if let Some(project_analysis) = project_analysis {
+            self.write_project(project_analysis)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn project_analysis() -> ProjectAnalysis {
+        ProjectAnalysis {
+            project_overview: "A CLI".to_string(),
+            architecture_summary: "Flat".to_string(),
+            core_technologies: vec![],
+            main_interfaces: vec![],
+            development_considerations: vec![],
+            extension_points: vec![],
+            risk_factors: vec![],
+            license: None,
+        }
+    }
+
+    #[test]
+    fn test_each_line_is_a_child_analysis_then_the_project() {
+        let analyses = [
+            ChildAnalysis::File(FileAnalysis {
+                file_path: PathBuf::from("src/main.rs"),
+                file_type: "rs".to_string(),
+                summary: "Entry point.\nStarts the CLI.".to_string(),
+                external_dependencies: vec!["tokio".to_string()],
+                public_interfaces: vec![],
+                line_count: None,
+                byte_size: None,
+                signatures: vec![],
+            }),
+            ChildAnalysis::Directory(DirectoryAnalysis {
+                directory_path: PathBuf::from("src"),
+                depth_level: 1,
+                summary: "Source code".to_string(),
+                child_summaries: vec!["Entry point".to_string()],
+                key_components: vec!["main.rs".to_string()],
+                external_dependencies: vec![],
+                public_interfaces: vec![],
+            }),
+        ];
+        let temp_file = tempfile::NamedTempFile::new().unwrap();
+        let generator = NdjsonGenerator::new(Some(temp_file.path().to_path_buf()));
+
+        // Each record is on disk as soon as it is written
+        generator.write_child(&analyses[0]).unwrap();
+        let output = std::fs::read_to_string(temp_file.path()).unwrap();
+        assert_eq!(output.lines().count(), 1);
+
+        generator.write_child(&analyses[1]).unwrap();
+        generator.write_project(&project_analysis()).unwrap();
+        let output = std::fs::read_to_string(temp_file.path()).unwrap();
+
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 3);
+        for line in &lines[..2] {
+            serde_json::from_str::<ChildAnalysis>(line).unwrap();
+        }
+        assert!(matches!(
+            serde_json::from_str::<ChildAnalysis>(lines[1]).unwrap(),
+            ChildAnalysis::Directory(_)
+        ));
+        assert!(matches!(
+            serde_json::from_str::<NdjsonRecord>(lines[2]).unwrap(),
+            NdjsonRecord::Project(_)
+        ));
+    }
+}
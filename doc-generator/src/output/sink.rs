@@ -0,0 +1,60 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::io::{self, AsyncWriteExt};
+
+/// Where rendered documentation pages are written
+#[async_trait]
+pub trait OutputSink: Send + Sync {
+    /// Write the page at `path`, relative to the root of the documentation
+    async fn write_page(&self, path: &Path, content: &str) -> Result<()>;
+
+    /// Directory the pages end up in, if they are written to disk
+    fn destination(&self) -> Option<&Path>;
+}
+
+/// Writes each page to a file under `root`, creating directories as needed
+pub struct FileSystemSink {
+    root: PathBuf,
+}
+
+impl FileSystemSink {
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+#[async_trait]
+impl OutputSink for FileSystemSink {
+    async fn write_page(&self, path: &Path, content: &str) -> Result<()> {
+        let output_path = self.root.join(path);
+        if let Some(parent) = output_path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        fs::write(output_path, content).await?;
+        Ok(())
+    }
+
+    fn destination(&self) -> Option<&Path> {
+        Some(&self.root)
+    }
+}
+
+/// Prints every page to stdout, each preceded by a `==> path <==` separator line
+pub struct StdoutSink;
+
+#[async_trait]
+impl OutputSink for StdoutSink {
+    async fn write_page(&self, path: &Path, content: &str) -> Result<()> {
+        let mut stdout = io::stdout();
+        let page = format!("==> {} <==\n\n{}\n", path.display(), content.trim_end());
+        stdout.write_all(page.as_bytes()).await?;
+        stdout.flush().await?;
+        Ok(())
+    }
+
+    fn destination(&self) -> Option<&Path> {
+        None
+    }
+}
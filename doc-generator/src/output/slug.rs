@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Convert heading text into an anchor slug using the same rules GitHub applies
+/// when rendering markdown, so generated links resolve in rendered docs
+pub fn slugify(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            '-' | '_' => Some(c),
+            c if c.is_alphanumeric() => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Hands out unique slugs for the headings of a single document.
+/// Repeated headings get numeric suffixes (`overview`, `overview-1`, ...),
+/// so the same sequence of headings always yields the same slugs.
+#[derive(Debug, Default, Clone)]
+pub struct Slugger {
+    seen: HashMap<String, usize>,
+}
+
+impl Slugger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Slug for the next heading with this text
+    pub fn slug(&mut self, text: &str) -> String {
+        let base = slugify(text);
+        let mut slug = base.clone();
+        while self.seen.contains_key(&slug) {
+            let count = self.seen.entry(base.clone()).or_insert(0);
+            *count += 1;
+            slug = format!("{}-{}", base, count);
+        }
+        self.seen.insert(slug.clone(), 0);
+        slug
+    }
+}
+
+/// Markdown link to an anchor, optionally in another document
+pub fn anchor_link(text: &str, document: Option<&str>, slug: &str) -> String {
+    format!("[{}]({}#{})", text, document.unwrap_or(""), slug)
+}
+
+/// Put an explicit anchor ahead of every heading in `markdown`, slugged by one `Slugger`
+/// for the whole page, so links to them resolve whichever renderer shows the page
+///
+/// Lines inside fenced code blocks are left alone.
+pub fn anchor_headings(markdown: &str) -> String {
+    let mut slugger = Slugger::new();
+    let mut in_fence = false;
+    let mut anchored = String::with_capacity(markdown.len());
+    for line in markdown.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        } else if !in_fence && let Some(text) = heading_text(line) {
+            let _ = writeln!(anchored, "<a id=\"{}\"></a>", slugger.slug(text));
+        }
+        anchored.push_str(line);
+    }
+    anchored
+}
+
+/// Text of an ATX heading line such as `## Summary`, if `line` is one
+fn heading_text(line: &str) -> Option<&str> {
+    let text = line.trim_start_matches('#');
+    let level = line.len() - text.len();
+    ((1..=6).contains(&level) && text.starts_with(' ')).then(|| text.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Public Interfaces"), "public-interfaces");
+        assert_eq!(slugify("`parse_config` (fn)"), "parse_config-fn");
+        assert_eq!(slugify("🚀 Project Analysis"), "-project-analysis");
+    }
+
+    #[test]
+    fn test_duplicate_headings_get_distinct_stable_slugs() {
+        let headings = ["Summary", "Summary", "Summary 1", "Summary"];
+
+        let mut slugger = Slugger::new();
+        let slugs: Vec<String> = headings.iter().map(|h| slugger.slug(h)).collect();
+        assert_eq!(
+            slugs,
+            vec!["summary", "summary-1", "summary-1-1", "summary-2"]
+        );
+
+        // A fresh slugger over the same headings produces the same anchors
+        let mut again = Slugger::new();
+        let repeat: Vec<String> = headings.iter().map(|h| again.slug(h)).collect();
+        assert_eq!(slugs, repeat);
+
+        // Links built elsewhere point at the same anchors
+        let mut linker = Slugger::new();
+        linker.slug("Summary");
+        let link = anchor_link("Summary", Some("README.md"), &linker.slug("Summary"));
+        assert_eq!(link, format!("[Summary](README.md#{})", slugs[1]));
+    }
+
+    #[test]
+    fn test_anchor_headings_skips_code_blocks() {
+        let page = "# Title\n\n## Summary\nText\n\n```py\n# comment\n```\n## Summary\n#hashtag\n";
+
+        assert_eq!(
+            anchor_headings(page),
+            "<a id=\"title\"></a>\n# Title\n\n<a id=\"summary\"></a>\n## Summary\nText\n\n\
+             ```py\n# comment\n```\n<a id=\"summary-1\"></a>\n## Summary\n#hashtag\n"
+        );
+    }
+}
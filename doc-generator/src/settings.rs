@@ -2,22 +2,122 @@ use std::{
     fs::File,
     io::{self, Write},
     path::PathBuf,
+    time::Duration,
 };
 
-use crate::llm_interface::{models::ModelId, pool::Behaviour};
+use crate::analysis::display::RenderStyle;
+use crate::analysis::prompt::PromptTemplates;
+use crate::analysis::summary::AnalysisAudience;
+use crate::llm_interface::{client::RetryConfig, models::ModelId, pool::Behaviour};
+use crate::output::file_system::CollisionStrategy;
 use clap::ValueEnum;
 use config::{Config, ConfigError, Environment};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
 pub struct CrawlOptions {
     pub max_depth: Option<usize>,
     pub include_hidden: bool,
     #[serde(default)]
     pub include_patterns: Vec<String>,
+    /// Patterns skipped on top of the default build, dependency and VCS directories
     #[serde(default)]
     pub exclude_patterns: Vec<String>,
     pub git_mode: bool,
+    pub include_license: bool,
+    /// Largest file that will be analyzed, e.g. "512KB", "2MB" or a plain byte count
+    pub max_file_size: Option<String>,
+    /// Smallest file that will be analyzed, in bytes, to skip stub files
+    #[serde(default)]
+    pub min_file_size: Option<u64>,
+    /// Analyze at most this many files in a run
+    #[serde(default)]
+    pub max_files: Option<usize>,
+    /// Analyze non-UTF-8 files by decoding them lossily instead of skipping them
+    #[serde(default)]
+    pub lossy_decode: bool,
+    /// Analyze files over max_file_size in chunks instead of skipping them
+    #[serde(default)]
+    pub chunk_large_files: bool,
+    /// Lines each chunk repeats from the end of the previous one (default 20)
+    #[serde(default)]
+    pub chunk_overlap: Option<usize>,
+    /// Most chunks a file is split into, later parts of larger files are left out (default 16)
+    #[serde(default)]
+    pub max_chunks: Option<usize>,
+    /// List top-level Rust, Python and TypeScript signatures in each file's docs
+    #[serde(default)]
+    pub include_signatures: bool,
+    /// Who the docs are written for: llm_consumption, human_developer or technical_documentation
+    #[serde(default)]
+    pub audience: Option<AnalysisAudience>,
+    /// Extra file extensions to analyze (e.g. ["vue", "sql"]), added to the built-in list
+    #[serde(default)]
+    pub analyzable_extensions: Option<Vec<String>>,
+    /// Analyze only analyzable_extensions instead of adding them to the built-in list
+    #[serde(default)]
+    pub replace_default_extensions: bool,
+}
+
+impl Default for CrawlOptions {
+    fn default() -> Self {
+        CrawlOptions {
+            max_depth: None,
+            include_hidden: false,
+            include_patterns: vec![],
+            exclude_patterns: vec![],
+            git_mode: false,
+            include_license: true,
+            max_file_size: None,
+            min_file_size: None,
+            max_files: None,
+            lossy_decode: false,
+            chunk_large_files: false,
+            chunk_overlap: None,
+            max_chunks: None,
+            include_signatures: false,
+            audience: None,
+            analyzable_extensions: None,
+            replace_default_extensions: false,
+        }
+    }
+}
+
+impl CrawlOptions {
+    /// The configured max_file_size in bytes, if one is set
+    pub fn max_file_size_bytes(&self) -> Result<Option<u64>, ConfigError> {
+        self.max_file_size
+            .as_deref()
+            .map(parse_file_size)
+            .transpose()
+    }
+}
+
+/// Parse a human readable size ("512KB", "1.5MB", "1GB", "1024") into bytes
+pub fn parse_file_size(value: &str) -> Result<u64, ConfigError> {
+    let trimmed = value.trim();
+    let split = trimmed
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(split);
+
+    let multiplier: u64 = match unit.trim().to_uppercase().as_str() {
+        "" | "B" => 1,
+        "KB" => 1024,
+        "MB" => 1024 * 1024,
+        "GB" => 1024 * 1024 * 1024,
+        _ => return Err(invalid_file_size(value)),
+    };
+
+    let number: f64 = number.parse().map_err(|_| invalid_file_size(value))?;
+    Ok((number * multiplier as f64) as u64)
+}
+
+fn invalid_file_size(value: &str) -> ConfigError {
+    ConfigError::Message(format!(
+        "Invalid files.max_file_size {:?}: expected a byte count or a number with a KB/MB/GB suffix",
+        value
+    ))
 }
 
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
@@ -28,7 +128,42 @@ pub struct LlmSettings {
     pub max_interval_s: u32,
     pub multiplier: f64,
     pub max_elapsed_time_s: u32,
+    pub randomization_factor: f64,
+    /// Send one follow-up request asking the model to fix a response that fails to parse
+    #[serde(default)]
+    pub repair_on_parse_failure: bool,
+    /// Seconds a single request may take before it is abandoned and retried
+    #[serde(default)]
+    pub request_timeout_s: Option<u32>,
+    /// Most requests each configured model may have in flight at once
+    #[serde(default)]
+    pub max_concurrent_per_client: Option<usize>,
+    /// Most requests a run may have in flight at once across concurrently analyzed subtrees
+    #[serde(default)]
+    pub max_concurrent_requests: Option<usize>,
+    /// Seconds an errored model is skipped before the pool tries it again
+    #[serde(default = "default_error_cooldown_s")]
+    pub error_cooldown_s: u32,
+    /// House-style instructions placed before every analysis prompt
+    #[serde(default)]
+    pub prompt_prefix: Option<String>,
+    /// House-style instructions placed after every analysis prompt
+    #[serde(default)]
+    pub prompt_suffix: Option<String>,
     pub models: Vec<LlmModel>,
+    /// Model used for per-file analysis instead of `models`
+    #[serde(default)]
+    pub file_model: Option<LlmModel>,
+    /// Model used for directory synthesis instead of `models`
+    #[serde(default)]
+    pub directory_model: Option<LlmModel>,
+    /// Model used for project synthesis instead of `models`
+    #[serde(default)]
+    pub project_model: Option<LlmModel>,
+}
+
+fn default_error_cooldown_s() -> u32 {
+    60
 }
 
 impl Default for LlmSettings {
@@ -39,20 +174,90 @@ impl Default for LlmSettings {
             max_interval_s: 60,
             multiplier: 2.0,
             max_elapsed_time_s: 300,
+            randomization_factor: 0.5,
+            repair_on_parse_failure: false,
+            request_timeout_s: None,
+            max_concurrent_per_client: None,
+            max_concurrent_requests: None,
+            error_cooldown_s: default_error_cooldown_s(),
+            prompt_prefix: None,
+            prompt_suffix: None,
             behaviour: Behaviour::Failover,
             models: vec![LlmModel::default()],
+            file_model: None,
+            directory_model: None,
+            project_model: None,
         }
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+impl LlmSettings {
+    /// Prompt templates for `model`, with the house style and its file-analysis override applied
+    pub fn prompt_templates(&self, model: &LlmModel) -> PromptTemplates {
+        let mut templates = PromptTemplates {
+            prompt_prefix: self.prompt_prefix.clone(),
+            prompt_suffix: self.prompt_suffix.clone(),
+            ..Default::default()
+        };
+        if let Some(prompt_override) = &model.prompt_override {
+            templates.file_analysis_template = prompt_override.clone();
+        }
+        templates
+    }
+
+    /// Replace the configured models and stage overrides with `model` alone, as `--model` does.
+    /// The key comes from the first configured model of the same provider, otherwise from
+    /// the provider's environment variable.
+    pub fn use_only_model(&mut self, model: ModelId) {
+        let api_key = self
+            .models
+            .iter()
+            .find(|configured| configured.model.provider() == model.provider())
+            .and_then(|configured| configured.api_key.clone());
+        self.models = vec![LlmModel {
+            model,
+            api_key,
+            ..Default::default()
+        }];
+        self.file_model = None;
+        self.directory_model = None;
+        self.project_model = None;
+    }
+
+    /// Retry behaviour shared by every configured client
+    pub fn retry_config(&self) -> RetryConfig {
+        RetryConfig {
+            max_retries: self.max_retries,
+            initial_interval: Duration::from_millis(self.initial_interval_ms.into()),
+            max_interval: Duration::from_secs(self.max_interval_s.into()),
+            multiplier: self.multiplier,
+            max_elapsed_time: Duration::from_secs(self.max_elapsed_time_s.into()),
+            randomization_factor: self.randomization_factor,
+            repair_on_parse_failure: self.repair_on_parse_failure,
+            request_timeout: self
+                .request_timeout_s
+                .map(|secs| Duration::from_secs(secs.into())),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, PartialEq)]
 pub struct LlmModel {
     pub model: ModelId,
     pub priority: usize,
+    /// The key itself, `env:NAME` to read it from an environment variable or
+    /// `file:/path` to read it from a file, e.g. a mounted secret
     pub api_key: Option<String>,
     pub max_tokens: Option<u32>,
     pub temperature: Option<f32>,
+    /// Template that replaces the built-in file analysis prompt for this model
     pub prompt_override: Option<String>,
+    /// Custom endpoint for self-hosted or proxied providers
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// Most requests sent to this model per minute, spaced evenly
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
 }
 
 impl Default for LlmModel {
@@ -64,16 +269,113 @@ impl Default for LlmModel {
             max_tokens: Some(1500),
             temperature: Some(0.5),
             prompt_override: None,
+            base_url: None,
+            requests_per_minute: None,
         }
     }
 }
 
+impl std::fmt::Debug for LlmModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LlmModel")
+            .field("model", &self.model)
+            .field("priority", &self.priority)
+            .field("api_key", &self.api_key.as_ref().map(|_| "***"))
+            .field("max_tokens", &self.max_tokens)
+            .field("temperature", &self.temperature)
+            .field("prompt_override", &self.prompt_override)
+            .field("base_url", &self.base_url)
+            .field("requests_per_minute", &self.requests_per_minute)
+            .finish()
+    }
+}
+
+/// Resolve an api_key reference: `env:NAME` reads the environment variable,
+/// `file:/path` reads the file's trimmed contents, anything else is the key itself
+pub fn resolve_secret(value: &str) -> Result<String, ConfigError> {
+    if let Some(name) = value.strip_prefix("env:") {
+        std::env::var(name).map_err(|_| {
+            ConfigError::Message(format!(
+                "api_key refers to the environment variable {} but it is not set",
+                name
+            ))
+        })
+    } else if let Some(path) = value.strip_prefix("file:") {
+        std::fs::read_to_string(path)
+            .map(|contents| contents.trim().to_string())
+            .map_err(|e| {
+                ConfigError::Message(format!("Could not read api_key from {}: {}", path, e))
+            })
+    } else {
+        Ok(value.to_string())
+    }
+}
+
+impl LlmModel {
+    /// The configured key with any `env:`/`file:` reference resolved
+    ///
+    /// Resolved only when a client is built, so commands that never call a model don't
+    /// need the secret, and variables from `.env` are already loaded.
+    pub fn resolved_api_key(&self) -> Result<Option<String>, ConfigError> {
+        self.api_key.as_deref().map(resolve_secret).transpose()
+    }
+
+    /// Everything wrong with this model's configuration, prefixed with `label`
+    fn problems(&self, label: &str) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if let Some(temperature) = self.temperature
+            && !(0.0..=2.0).contains(&temperature)
+        {
+            problems.push(format!(
+                "{}: temperature {} is outside the range 0.0 to 2.0",
+                label, temperature
+            ));
+        }
+        if self.max_tokens == Some(0) {
+            problems.push(format!("{}: max_tokens must be greater than 0", label));
+        }
+        if let Err(e) = self.resolved_api_key() {
+            problems.push(format!("{}: {}", label, e));
+        } else if self.api_key.is_none() && std::env::var(self.model.api_key_env_var()).is_err() {
+            problems.push(format!(
+                "{}: no api_key configured and {} is not set for the model {}",
+                label,
+                self.model.api_key_env_var(),
+                self.model
+            ));
+        }
+
+        problems
+    }
+}
+
+/// How markdown pages are written
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Default)]
+pub struct MarkdownSettings {
+    /// Prepend YAML front-matter (title, path, type) for static site generators
+    #[serde(default)]
+    pub front_matter: bool,
+    /// Decorate headers and interface labels with emoji, or keep them plain ASCII
+    #[serde(default)]
+    pub render_style: RenderStyle,
+    /// How to name the pages of files differing only by extension, like `foo.rs` and `foo.py`
+    #[serde(default)]
+    pub collision_strategy: CollisionStrategy,
+    /// Keep hand-written `auto-doc:keep` regions of pages already on disk, replacing only
+    /// the generated region
+    #[serde(default)]
+    pub merge_existing: bool,
+}
+
 #[derive(Debug, Deserialize, Serialize, PartialEq, Default)]
 #[allow(unused)]
 pub struct Settings {
     pub files: CrawlOptions,
     #[serde(default)]
     pub llm_settings: LlmSettings,
+    #[serde(default)]
+    pub markdown: MarkdownSettings,
 }
 
 #[derive(Debug, Clone, ValueEnum)]
@@ -100,20 +402,61 @@ impl Settings {
                     .list_separator(",")
                     .with_list_parse_key("llm_settings.models")
                     .with_list_parse_key("files.include_patterns")
-                    .with_list_parse_key("files.exclude_patterns"),
+                    .with_list_parse_key("files.exclude_patterns")
+                    .with_list_parse_key("files.analyzable_extensions"),
             )
             .build()?;
 
-        config.try_deserialize()
+        let settings: Self = config.try_deserialize()?;
+        settings.files.max_file_size_bytes()?;
+        Ok(settings)
+    }
+
+    /// Check the loaded settings for values that would fail later, reporting every problem at once
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let llm = &self.llm_settings;
+        let mut problems = Vec::new();
+
+        if llm.models.is_empty() {
+            problems.push("llm_settings.models: at least one model must be configured".to_string());
+        }
+        for (i, model) in llm.models.iter().enumerate() {
+            problems.extend(model.problems(&format!("llm_settings.models[{}]", i)));
+        }
+
+        let stage_models = [
+            ("llm_settings.file_model", &llm.file_model),
+            ("llm_settings.directory_model", &llm.directory_model),
+            ("llm_settings.project_model", &llm.project_model),
+        ];
+        for (label, model) in stage_models {
+            if let Some(model) = model {
+                problems.extend(model.problems(label));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
     }
 
     pub fn from_env() -> Result<Self, ConfigError> {
         Self::with_config_builder(|builder| builder)
     }
 
+    #[allow(dead_code)]
     pub fn from_file(file_name: &str) -> Result<Self, ConfigError> {
+        Self::from_files(&[file_name])
+    }
+
+    /// Layer several config files in order, later files overriding earlier ones
+    pub fn from_files(file_names: &[&str]) -> Result<Self, ConfigError> {
         Self::with_config_builder(|builder| {
-            builder.add_source(config::File::with_name(file_name).required(false))
+            file_names.iter().fold(builder, |builder, file_name| {
+                builder.add_source(config::File::with_name(file_name).required(false))
+            })
         })
     }
 
@@ -167,6 +510,7 @@ mod tests {
         assert!(options.include_patterns.is_empty());
         assert!(options.exclude_patterns.is_empty());
         assert!(!options.git_mode);
+        assert!(options.include_license);
     }
 
     #[test]
@@ -178,6 +522,18 @@ mod tests {
             include_patterns: vec!["*.rs".to_string(), "*.toml".to_string()],
             exclude_patterns: vec!["target/".to_string()],
             git_mode: true,
+            include_license: true,
+            max_file_size: None,
+            min_file_size: None,
+            max_files: None,
+            lossy_decode: false,
+            chunk_large_files: false,
+            chunk_overlap: None,
+            max_chunks: None,
+            include_signatures: false,
+            audience: None,
+            analyzable_extensions: None,
+            replace_default_extensions: false,
         };
 
         let options2 = CrawlOptions {
@@ -186,11 +542,49 @@ mod tests {
             include_patterns: vec!["*.rs".to_string(), "*.toml".to_string()],
             exclude_patterns: vec!["target/".to_string()],
             git_mode: true,
+            include_license: true,
+            max_file_size: None,
+            min_file_size: None,
+            max_files: None,
+            lossy_decode: false,
+            chunk_large_files: false,
+            chunk_overlap: None,
+            max_chunks: None,
+            include_signatures: false,
+            audience: None,
+            analyzable_extensions: None,
+            replace_default_extensions: false,
         };
 
         assert_eq!(options1, options2);
     }
 
+    #[test]
+    #[serial]
+    fn test_prompt_templates_for_model() {
+        let llm_settings = LlmSettings {
+            prompt_prefix: Some("Use British spelling.".to_string()),
+            ..Default::default()
+        };
+        let model = LlmModel {
+            prompt_override: Some("Describe {FILE_PATH}".to_string()),
+            ..Default::default()
+        };
+
+        let templates = llm_settings.prompt_templates(&model);
+        assert_eq!(templates.file_analysis_template, "Describe {FILE_PATH}");
+        assert_eq!(
+            templates.prompt_prefix.as_deref(),
+            Some("Use British spelling.")
+        );
+
+        let templates = llm_settings.prompt_templates(&LlmModel::default());
+        assert_eq!(
+            templates.file_analysis_template,
+            PromptTemplates::default().file_analysis_template
+        );
+    }
+
     #[test]
     #[serial]
     fn test_crawl_options_serialization() {
@@ -200,6 +594,18 @@ mod tests {
             include_patterns: vec!["*.md".to_string()],
             exclude_patterns: vec!["*.tmp".to_string()],
             git_mode: true,
+            include_license: true,
+            max_file_size: None,
+            min_file_size: None,
+            max_files: None,
+            lossy_decode: false,
+            chunk_large_files: false,
+            chunk_overlap: None,
+            max_chunks: None,
+            include_signatures: false,
+            audience: None,
+            analyzable_extensions: None,
+            replace_default_extensions: false,
         };
 
         let serialized = serde_json::to_string(&options).unwrap();
@@ -239,6 +645,18 @@ mod tests {
                 include_patterns: vec!["*.rs".to_string()],
                 exclude_patterns: vec!["target/".to_string()],
                 git_mode: false,
+                include_license: false,
+                max_file_size: Some("2MB".to_string()),
+                min_file_size: None,
+                max_files: None,
+                lossy_decode: true,
+                chunk_large_files: true,
+                chunk_overlap: None,
+                max_chunks: None,
+                include_signatures: false,
+                audience: None,
+                analyzable_extensions: None,
+                replace_default_extensions: false,
             },
             llm_settings: LlmSettings {
                 behaviour: Behaviour::Failover,
@@ -250,6 +668,8 @@ mod tests {
                         max_tokens: Some(10),
                         temperature: Some(0.1),
                         prompt_override: None,
+                        base_url: None,
+                        requests_per_minute: None,
                     },
                     LlmModel {
                         model: ModelId::Claude35Haiku,
@@ -258,10 +678,18 @@ mod tests {
                         max_tokens: Some(10),
                         temperature: Some(0.1),
                         prompt_override: None,
+                        base_url: None,
+                        requests_per_minute: None,
                     },
                 ],
                 ..LlmSettings::default()
             },
+            markdown: MarkdownSettings {
+                front_matter: true,
+                render_style: RenderStyle::Plain,
+                collision_strategy: CollisionStrategy::Counter,
+                merge_existing: true,
+            },
         };
 
         let serialized = serde_json::to_string(&settings).unwrap();
@@ -337,6 +765,7 @@ git_mode = false
 
 [llm_settings]
 behaviour = "failover"
+max_concurrent_requests = 2
 
 [[llm_settings.models]]
 priority = 1
@@ -371,6 +800,44 @@ api_key = "test"
         assert_eq!(settings.llm_settings.models.len(), 2);
         assert!(settings.llm_settings.models[0].model == ModelId::Claude4Sonnet);
         assert!(settings.llm_settings.models[1].model == ModelId::Gpt41);
+        assert_eq!(settings.llm_settings.max_concurrent_requests, Some(2));
+        assert!(settings.llm_settings.file_model.is_none());
+    }
+
+    #[test]
+    #[serial]
+    fn test_from_file_with_stage_models() {
+        clear_autodoc_env_vars();
+
+        let toml_content = r#"
+[llm_settings.file_model]
+priority = 1
+model = "claude-3-5-haiku-latest"
+api_key = "test"
+
+[llm_settings.project_model]
+priority = 1
+model = "claude-opus-4-20250514"
+api_key = "test"
+"#;
+
+        let temp_file = NamedTempFile::with_suffix(".toml").unwrap();
+        fs::write(temp_file.path(), toml_content).unwrap();
+
+        let file_path = temp_file.path().to_str().unwrap();
+        let file_name = file_path.strip_suffix(".toml").unwrap();
+
+        let settings = Settings::from_file(file_name).unwrap();
+        let llm_settings = settings.llm_settings;
+        assert_eq!(
+            llm_settings.file_model.map(|m| m.model),
+            Some(ModelId::Claude35Haiku)
+        );
+        assert!(llm_settings.directory_model.is_none());
+        assert_eq!(
+            llm_settings.project_model.map(|m| m.model),
+            Some(ModelId::Claude4Opus)
+        );
     }
 
     #[test]
@@ -391,6 +858,7 @@ api_key = "test"
     "max_interval_s": 60,
     "multiplier": 2.0,
     "max_elapsed_time_s": 300,
+    "randomization_factor": 0.5,
     "models": [
       {
         "model": "claude-sonnet-4-20250514",
@@ -499,6 +967,90 @@ max_depth = 1
         assert!(!settings.llm_settings.models.is_empty());
     }
 
+    #[test]
+    #[serial]
+    fn test_from_files_later_file_overrides() {
+        clear_autodoc_env_vars();
+
+        let team_file = NamedTempFile::with_suffix(".toml").unwrap();
+        fs::write(
+            team_file.path(),
+            "[files]\nmax_depth = 2\ninclude_hidden = true\n",
+        )
+        .unwrap();
+        let personal_file = NamedTempFile::with_suffix(".toml").unwrap();
+        fs::write(personal_file.path(), "[files]\nmax_depth = 7\n").unwrap();
+
+        let team = team_file.path().to_str().unwrap();
+        let personal = personal_file.path().to_str().unwrap();
+
+        let settings = Settings::from_files(&[team, personal]).unwrap();
+        assert_eq!(settings.files.max_depth, Some(7)); // from the later file
+        assert!(settings.files.include_hidden); // kept from the earlier file
+
+        let settings = Settings::from_files(&[personal, team]).unwrap();
+        assert_eq!(settings.files.max_depth, Some(2));
+    }
+
+    #[test]
+    fn test_validate_accepts_keyed_models() {
+        let mut settings = Settings::default();
+        settings.llm_settings.models[0].api_key = Some("key".to_string());
+
+        assert_eq!(settings.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_use_only_model_replaces_configured_models() {
+        let mut settings = Settings::default();
+        settings.llm_settings.models = vec![
+            LlmModel {
+                model: ModelId::Gpt4o,
+                api_key: Some("openai-key".to_string()),
+                ..Default::default()
+            },
+            LlmModel {
+                model: ModelId::Claude4Sonnet,
+                api_key: Some("anthropic-key".to_string()),
+                ..Default::default()
+            },
+        ];
+        settings.llm_settings.file_model = Some(LlmModel::default());
+
+        settings.llm_settings.use_only_model(ModelId::Claude35Haiku);
+        let models = &settings.llm_settings.models;
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].model, ModelId::Claude35Haiku);
+        assert_eq!(models[0].api_key.as_deref(), Some("anthropic-key"));
+        assert!(settings.llm_settings.file_model.is_none());
+
+        // No configured model shares the provider, so the env var is used
+        settings.llm_settings.use_only_model(ModelId::Gpt4oMini);
+        assert!(settings.llm_settings.models[0].api_key.is_none());
+    }
+
+    #[test]
+    #[serial]
+    fn test_validate_reports_every_problem() {
+        unsafe { env::remove_var(ModelId::Llama33.api_key_env_var()) };
+
+        let mut settings = Settings::default();
+        settings.llm_settings.models = vec![];
+        settings.llm_settings.file_model = Some(LlmModel {
+            model: ModelId::Llama33,
+            temperature: Some(2.5),
+            max_tokens: Some(0),
+            ..Default::default()
+        });
+
+        let problems = settings.validate().unwrap_err();
+        assert_eq!(problems.len(), 4);
+        assert!(problems[0].contains("at least one model"));
+        assert!(problems[1].starts_with("llm_settings.file_model: temperature 2.5"));
+        assert!(problems[2].contains("max_tokens must be greater than 0"));
+        assert!(problems[3].contains("OLLAMA_API_KEY"));
+    }
+
     #[test]
     #[serial]
     fn test_invalid_env_var_types() {
@@ -516,6 +1068,37 @@ max_depth = 1
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_parse_file_size() {
+        assert_eq!(parse_file_size("512KB").unwrap(), 512 * 1024);
+        assert_eq!(parse_file_size("1.5MB").unwrap(), 1024 * 1024 * 3 / 2);
+        assert_eq!(parse_file_size("1024").unwrap(), 1024);
+        assert_eq!(parse_file_size("2 gb").unwrap(), 2 * 1024 * 1024 * 1024);
+        assert!(parse_file_size("2 banana").is_err());
+        assert!(parse_file_size("MB").is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_from_file_with_invalid_max_file_size() {
+        clear_autodoc_env_vars();
+
+        let toml_content = r#"
+[files]
+max_file_size = "2 banana"
+"#;
+
+        let temp_file = NamedTempFile::with_suffix(".toml").unwrap();
+        fs::write(temp_file.path(), toml_content).unwrap();
+
+        let file_path = temp_file.path().to_str().unwrap();
+        let file_name = file_path.strip_suffix(".toml").unwrap();
+
+        let result = Settings::from_file(file_name);
+        let error = result.unwrap_err().to_string();
+        assert!(error.contains("max_file_size"));
+    }
+
     // #[test]
     // #[serial]
     // fn test_llm_settings_from_env_json_string() {
@@ -558,6 +1141,71 @@ max_depth = 1
         // For now, this will likely fail since it expects JSON
         println!("Comma-separated result: {:?}", result);
     }
+    #[test]
+    #[serial]
+    fn test_resolve_secret() {
+        unsafe { env::set_var("AUTODOC_TEST_SECRET", "from-env") };
+        let secret_file = NamedTempFile::new().unwrap();
+        fs::write(secret_file.path(), "from-file\n").unwrap();
+
+        assert_eq!(
+            resolve_secret("env:AUTODOC_TEST_SECRET").unwrap(),
+            "from-env"
+        );
+        assert_eq!(
+            resolve_secret(&format!("file:{}", secret_file.path().display())).unwrap(),
+            "from-file"
+        );
+        assert_eq!(resolve_secret("sk-literal").unwrap(), "sk-literal");
+
+        unsafe { env::remove_var("AUTODOC_TEST_SECRET") };
+        assert!(resolve_secret("env:AUTODOC_TEST_SECRET").is_err());
+        assert!(resolve_secret("file:/does/not/exist").is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_api_key_references_resolve_lazily() {
+        clear_autodoc_env_vars();
+        unsafe { env::set_var("AUTODOC_TEST_SECRET", "sk-resolved") };
+
+        let toml_content = r#"
+[[llm_settings.models]]
+priority = 1
+model = "claude-sonnet-4-20250514"
+api_key = "env:AUTODOC_TEST_SECRET"
+"#;
+        let temp_file = NamedTempFile::with_suffix(".toml").unwrap();
+        fs::write(temp_file.path(), toml_content).unwrap();
+        let file_name = temp_file
+            .path()
+            .to_str()
+            .unwrap()
+            .strip_suffix(".toml")
+            .unwrap();
+
+        let settings = Settings::from_file(file_name).unwrap();
+        let model = &settings.llm_settings.models[0];
+        assert_eq!(model.api_key.as_deref(), Some("env:AUTODOC_TEST_SECRET"));
+        assert_eq!(
+            model.resolved_api_key().unwrap().as_deref(),
+            Some("sk-resolved")
+        );
+        assert!(format!("{:?}", model).contains("***"));
+        assert!(settings.validate().is_ok());
+
+        // An unset variable only matters once a key is needed
+        unsafe { env::remove_var("AUTODOC_TEST_SECRET") };
+        let settings = Settings::from_file(file_name).unwrap();
+        assert!(settings.llm_settings.models[0].resolved_api_key().is_err());
+        let problems = settings.validate().unwrap_err();
+        assert!(
+            problems[0].contains("AUTODOC_TEST_SECRET"),
+            "{:?}",
+            problems
+        );
+    }
+
     // Helper function to clear all AUTODOC environment variables
     fn clear_autodoc_env_vars() {
         let vars_to_clear = [
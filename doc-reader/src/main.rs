@@ -1,14 +1,20 @@
 pub mod settings;
 pub mod tools;
 use std::io;
+use std::net::IpAddr;
 use std::path::PathBuf;
-use std::sync::OnceLock;
+use std::sync::{Arc, OnceLock};
 
-use crate::settings::{FileType, Settings};
+use crate::settings::{FileType, Settings, SettingsStore};
+use crate::tools::get_doc_tree::GetDocTreeTool;
 use crate::tools::get_docs::GetDocumentationTool;
+use crate::tools::list_components::ListComponentsTool;
+use crate::tools::reload_config::ReloadConfigTool;
+use crate::tools::search_docs::SearchDocumentationTool;
 use clap::CommandFactory;
 use clap::{Command, Parser, Subcommand, ValueEnum};
 use clap_complete::{Generator, Shell, generate};
+use config::ConfigError;
 use mcp_core::server::Server;
 use mcp_core::tool_text_content;
 use mcp_core::transport::{ServerSseTransport, ServerStdioTransport};
@@ -18,26 +24,34 @@ use tracing::{Level, debug, error};
 use tracing_subscriber::{EnvFilter, FmtSubscriber};
 
 // Global settings that will be initialized once during CLI parsing
-static SETTINGS: OnceLock<Settings> = OnceLock::new();
+static SETTINGS: OnceLock<SettingsStore> = OnceLock::new();
 
 fn init_settings(config_path: Option<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
-    let settings = match config_path {
-        Some(config_path) => Settings::from_file(&config_path.to_string_lossy())?,
-        None => Settings::from_env()?,
-    };
-    debug!("Found settings with: {:#?}", settings);
+    let store = SettingsStore::load(config_path)?;
+    debug!("Found settings with: {:#?}", store.current());
     SETTINGS
-        .set(settings)
+        .set(store)
         .map_err(|_| "Settings already initialized")?;
     Ok(())
 }
 
-pub fn get_global_settings() -> &'static Settings {
+fn settings_store() -> &'static SettingsStore {
     SETTINGS
         .get()
         .expect("Settings not initialized - call init_settings() first")
 }
 
+pub fn get_global_settings() -> Arc<Settings> {
+    settings_store().current()
+}
+
+/// Re-read the config the server was started with, so later tool calls see the changes
+pub fn reload_global_settings() -> Result<Arc<Settings>, ConfigError> {
+    let settings = settings_store().reload()?;
+    debug!("Reloaded settings with: {:#?}", settings);
+    Ok(settings)
+}
+
 #[derive(Parser, Debug)]
 #[command(name = "doc-reader")]
 #[command(bin_name = "doc-reader")]
@@ -118,8 +132,18 @@ enum TransportType {
 enum Commands {
     /// Serve the mcp server
     Serve {
-        #[arg(value_enum, default_value_t = TransportType::Stdio)]
-        transport: TransportType,
+        /// Transport to serve on, repeat to serve several at once
+        #[arg(short, long = "transport", value_enum, default_values_t = [TransportType::Stdio])]
+        transports: Vec<TransportType>,
+        /// Single transport to serve on, the same as one `--transport`
+        #[arg(value_enum, conflicts_with = "transports")]
+        transport: Option<TransportType>,
+        /// Address the SSE transport binds to, an IP address or host name
+        #[arg(long, default_value = "127.0.0.1", value_parser = parse_host)]
+        host: String,
+        /// Port the SSE transport listens on
+        #[arg(long, default_value_t = 3000, value_parser = clap::value_parser!(u16).range(1..))]
+        port: u16,
     },
     /// Command for creating the config
     Config {
@@ -132,6 +156,28 @@ enum Commands {
     Completions { shell: Shell },
 }
 
+/// Accept an IP address or a host name made of dot separated alphanumeric and `-` labels
+fn parse_host(host: &str) -> Result<String, String> {
+    if host.parse::<IpAddr>().is_ok() {
+        return Ok(host.to_string());
+    }
+    let valid_label = |label: &str| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    };
+    if host.len() <= 253 && host.split('.').all(valid_label) {
+        Ok(host.to_string())
+    } else {
+        Err(format!(
+            "'{}' is not an IP address or valid host name",
+            host
+        ))
+    }
+}
+
 fn print_completions<G: Generator>(generator: G, cmd: &mut Command) {
     generate(
         generator,
@@ -163,7 +209,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             print_completions(shell, &mut cmd);
             Ok(())
         }
-        Some(Commands::Serve { transport }) => {
+        Some(Commands::Serve {
+            transports,
+            transport,
+            host,
+            port,
+        }) => {
+            let transports = transport.map_or(transports, |transport| vec![transport]);
             let server_protocol = Server::builder(
                 "Doc Reader".to_string(),
                 "1.0".to_string(),
@@ -174,18 +226,33 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 ..Default::default()
             })
             .register_tool(GetDocumentationTool::tool(), GetDocumentationTool::call())
+            .register_tool(GetDocTreeTool::tool(), GetDocTreeTool::call())
+            .register_tool(ListComponentsTool::tool(), ListComponentsTool::call())
+            .register_tool(ReloadConfigTool::tool(), ReloadConfigTool::call())
+            .register_tool(
+                SearchDocumentationTool::tool(),
+                SearchDocumentationTool::call(),
+            )
             .build();
-            match transport {
-                TransportType::Stdio => {
-                    let transport = ServerStdioTransport::new(server_protocol);
+
+            // Each transport gets its own copy of the protocol and runs until it stops, or
+            // until the other fails
+            let stdio = async {
+                if transports.contains(&TransportType::Stdio) {
+                    let transport = ServerStdioTransport::new(server_protocol.clone());
                     Server::start(transport).await?
                 }
-                TransportType::Sse => {
+                Ok::<(), anyhow::Error>(())
+            };
+            let sse = async {
+                if transports.contains(&TransportType::Sse) {
                     let transport =
-                        ServerSseTransport::new("127.0.0.1".to_string(), 3000, server_protocol);
+                        ServerSseTransport::new(host.clone(), port, server_protocol.clone());
                     Server::start(transport).await?
                 }
-            }
+                Ok::<(), anyhow::Error>(())
+            };
+            tokio::try_join!(stdio, sse)?;
             Ok(())
         }
         Some(Commands::Config { output, format }) => {
@@ -198,3 +265,86 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         None => Ok(()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serve_binds_host_and_port() {
+        let cli = Cli::try_parse_from([
+            "doc-reader",
+            "serve",
+            "--transport",
+            "sse",
+            "--host",
+            "0.0.0.0",
+            "--port",
+            "8080",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Serve { host, port: 8080, .. }) if host == "0.0.0.0"
+        ));
+
+        let named = Cli::try_parse_from(["doc-reader", "serve", "--host", "docs.local"]);
+        assert!(named.is_ok());
+
+        for bad in [
+            ["--host", "not a host"],
+            ["--host", "-leading.dash"],
+            ["--port", "0"],
+            ["--port", "70000"],
+        ] {
+            let parsed = Cli::try_parse_from(["doc-reader", "serve", bad[0], bad[1]]);
+            assert!(parsed.is_err(), "{:?} should be rejected", bad);
+        }
+    }
+
+    #[test]
+    fn test_serve_accepts_repeated_transports() {
+        let cli = Cli::try_parse_from([
+            "doc-reader",
+            "serve",
+            "--transport",
+            "stdio",
+            "--transport",
+            "sse",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Commands::Serve {
+                transports,
+                host,
+                port,
+                ..
+            }) => {
+                assert_eq!(transports, vec![TransportType::Stdio, TransportType::Sse]);
+                assert_eq!((host.as_str(), port), ("127.0.0.1", 3000));
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+
+        let cli = Cli::try_parse_from(["doc-reader", "serve"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Serve { transports, .. }) if transports == vec![TransportType::Stdio]
+        ));
+    }
+
+    #[test]
+    fn test_serve_accepts_a_positional_transport() {
+        let cli = Cli::try_parse_from(["doc-reader", "serve", "sse"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Serve {
+                transport: Some(TransportType::Sse),
+                ..
+            })
+        ));
+
+        let both = Cli::try_parse_from(["doc-reader", "serve", "sse", "--transport", "stdio"]);
+        assert!(both.is_err());
+    }
+}
@@ -2,7 +2,8 @@ use std::{
     collections::HashMap,
     fs::File,
     io::{self, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
 };
 
 use clap::ValueEnum;
@@ -13,12 +14,41 @@ use tracing::debug;
 #[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
 pub struct Component {
     pub relative_path: PathBuf,
+    /// File name of the directory index pages, matching the generator's `directory_index_name`
+    #[serde(default = "default_index_name")]
+    pub index_name: String,
+    /// File name of the page at the top of the docs, when the generator's `root_index_name`
+    /// gives it a different one from `index_name`
+    #[serde(default)]
+    pub root_index_name: Option<String>,
+}
+
+fn default_index_name() -> String {
+    "README.md".to_string()
 }
 
 impl Default for Component {
     fn default() -> Self {
         Self {
             relative_path: PathBuf::from("doc-reader/_docs"),
+            index_name: default_index_name(),
+            root_index_name: None,
+        }
+    }
+}
+
+impl Component {
+    /// Whether `file`, in the docs at `root`, is a directory index page: named
+    /// `root_index_name` (if set) at the top of the docs and `index_name` everywhere else
+    pub fn is_index(&self, root: &Path, file: &Path) -> bool {
+        let Some(name) = file.file_name() else {
+            return false;
+        };
+        match &self.root_index_name {
+            Some(root_index_name) if file.parent() == Some(root) => {
+                name == root_index_name.as_str()
+            }
+            _ => name == self.index_name.as_str(),
         }
     }
 }
@@ -45,13 +75,70 @@ impl Default for Project {
 #[allow(unused)]
 pub struct Settings {
     pub projects: HashMap<String, Project>,
+    /// Keep file contents in memory between tool calls, re-reading when modified
+    #[serde(default = "default_cache_files")]
+    pub cache_files: bool,
+    /// Most documentation files read at once when gathering a component's docs
+    #[serde(default = "default_max_concurrent_reads")]
+    pub max_concurrent_reads: usize,
+}
+
+fn default_cache_files() -> bool {
+    true
+}
+
+fn default_max_concurrent_reads() -> usize {
+    8
 }
 
 impl Default for Settings {
     fn default() -> Self {
         let mut map = HashMap::new();
         map.insert("auto-doc".to_string(), Project::default());
-        Self { projects: map }
+        Self {
+            projects: map,
+            cache_files: true,
+            max_concurrent_reads: default_max_concurrent_reads(),
+        }
+    }
+}
+
+/// The settings the tools read, swapped out whole when the config is reloaded
+///
+/// Tools take an `Arc` snapshot per call, so a reload never changes settings part way
+/// through a request.
+#[derive(Debug)]
+pub struct SettingsStore {
+    /// Config file given on the command line, `None` to read the default location
+    config_path: Option<PathBuf>,
+    current: RwLock<Arc<Settings>>,
+}
+
+impl SettingsStore {
+    pub fn load(config_path: Option<PathBuf>) -> Result<Self, ConfigError> {
+        let settings = Settings::read(config_path.as_deref(), false)?;
+        Ok(Self {
+            config_path,
+            current: RwLock::new(Arc::new(settings)),
+        })
+    }
+
+    pub fn current(&self) -> Arc<Settings> {
+        self.current
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Re-read the config from where it was first loaded, keeping the current settings
+    /// if it is gone or no longer loads
+    pub fn reload(&self) -> Result<Arc<Settings>, ConfigError> {
+        let settings = Arc::new(Settings::read(self.config_path.as_deref(), true)?);
+        *self
+            .current
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = settings.clone();
+        Ok(settings)
     }
 }
 
@@ -84,8 +171,9 @@ impl Settings {
         config.try_deserialize()
     }
 
-    pub fn from_env() -> Result<Self, ConfigError> {
-        let config_location: String = match std::env::var("DOC_READER.CONFIG") {
+    /// Config file named by `DOC_READER.CONFIG`, or the default one in the user's config dir
+    fn env_config_location() -> String {
+        match std::env::var("DOC_READER.CONFIG") {
             Ok(location) => location,
             Err(_) => {
                 // Create a sensible default config path
@@ -95,17 +183,29 @@ impl Settings {
 
                 config_dir.join("config").to_string_lossy().to_string()
             }
+        }
+    }
+
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::read(None, false)
+    }
+
+    /// Settings from `config_path`, or from the environment's config location if unset
+    ///
+    /// A missing config file falls back to the defaults, unless it is `required`.
+    pub fn read(config_path: Option<&Path>, required: bool) -> Result<Self, ConfigError> {
+        let config_location = match config_path {
+            Some(config_path) => config_path.to_string_lossy().to_string(),
+            None => Self::env_config_location(),
         };
         debug!("Looking for config in {}", config_location);
         Self::with_config_builder(|builder| {
-            builder.add_source(config::File::with_name(&config_location).required(false))
+            builder.add_source(config::File::with_name(&config_location).required(required))
         })
     }
 
     pub fn from_file(file_name: &str) -> Result<Self, ConfigError> {
-        Self::with_config_builder(|builder| {
-            builder.add_source(config::File::with_name(file_name).required(false))
-        })
+        Self::read(Some(Path::new(file_name)), false)
     }
 
     pub fn write_default_config(
@@ -140,11 +240,15 @@ impl Settings {
         Ok(())
     }
 
+    /// Every component name across all projects, sorted
     pub fn get_component_names(&self) -> Vec<String> {
-        self.projects
+        let mut names: Vec<String> = self
+            .projects
             .values()
             .flat_map(|project| project.components.keys().cloned())
-            .collect()
+            .collect();
+        names.sort();
+        names
     }
 
     pub fn get_project_names(&self) -> Vec<String> {
@@ -162,11 +266,16 @@ impl Settings {
     }
 
     pub fn get_component_path(&self, component_name: &str) -> Option<PathBuf> {
+        self.find_component(component_name).map(|(path, _)| path)
+    }
+
+    /// A component in any project by name, along with the full path of its docs
+    pub fn find_component(&self, component_name: &str) -> Option<(PathBuf, &Component)> {
         self.projects.values().find_map(|project| {
             project
                 .components
                 .get(component_name)
-                .map(|component| project.location.join(&component.relative_path))
+                .map(|component| (project.location.join(&component.relative_path), component))
         })
     }
 
@@ -178,3 +287,51 @@ impl Settings {
             .unwrap_or_default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_reload_picks_up_new_projects() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let config_path = temp_dir.path().join("config.yaml");
+        fs::write(
+            &config_path,
+            "projects:\n  first:\n    location: /repos/first\n    components:\n      api:\n        relative_path: _docs\n",
+        )?;
+
+        let store = SettingsStore::load(Some(config_path.clone()))?;
+        let before = store.current();
+        assert!(before.get_project("first").is_some());
+        assert!(before.get_project("second").is_none());
+
+        fs::write(
+            &config_path,
+            "projects:\n  first:\n    location: /repos/first\n    components:\n      api:\n        relative_path: _docs\n  second:\n    location: /repos/second\n    components:\n      web:\n        relative_path: docs\n",
+        )?;
+        store.reload()?;
+
+        let after = store.current();
+        assert_eq!(
+            after.get_component_path("web"),
+            Some(PathBuf::from("/repos/second/docs"))
+        );
+        // Snapshots taken before the reload are left as they were
+        assert!(before.get_project("second").is_none());
+
+        // A config that no longer parses keeps the last good settings
+        fs::write(&config_path, "projects: [not, a, map")?;
+        assert!(store.reload().is_err());
+        assert!(store.current().get_project("second").is_some());
+
+        // So does a config that has been deleted, rather than falling back to the defaults
+        fs::remove_file(&config_path)?;
+        assert!(store.reload().is_err());
+        assert!(store.current().get_project("second").is_some());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+use std::path::{Path, PathBuf};
+
+use crate::get_global_settings;
+use crate::tools::get_docs::get_files_to_depth;
+use mcp_core::{
+    tool_text_response,
+    tools::ToolHandlerFn,
+    types::{CallToolRequest, Tool},
+};
+use serde_json::json;
+
+/// Spaces of indent per directory level
+const INDENT: &str = "  ";
+
+pub struct GetDocTreeTool;
+
+impl GetDocTreeTool {
+    pub fn tool() -> Tool {
+        let settings = get_global_settings();
+        let component_names = settings.get_component_names().join(", ");
+        Tool {
+            name: "get_doc_tree".to_string(),
+            description: Some(
+                "Lists the documentation files of a component as an indented tree with relative paths, without their contents. Use it to pick files before calling get_docs".to_string(),
+            ),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "component_name": {
+                        "type": "string",
+                        "description": format!("The name of the component to list documentation for, one of: {}.", component_names)
+                    },
+                    "depth": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "description": "How many directories down to list. 0 lists only the top level files."
+                    }
+                },
+                "required": ["component_name", "depth"]
+            }),
+            annotations: None,
+        }
+    }
+
+    pub fn call() -> ToolHandlerFn {
+        move |request: CallToolRequest| {
+            Box::pin(async move {
+                let args = match request.arguments.as_ref() {
+                    Some(args) => args,
+                    None => return tool_text_response!("No arguments provided"),
+                };
+
+                let component_name = match args.get("component_name").and_then(|v| v.as_str()) {
+                    Some(name) => name,
+                    None => return tool_text_response!("component_name parameter is required"),
+                };
+
+                let depth = match args.get("depth").and_then(|v| v.as_u64()) {
+                    Some(depth) => depth as usize,
+                    None => {
+                        return tool_text_response!(
+                            "depth parameter is required and must be a non-negative integer"
+                        );
+                    }
+                };
+
+                let settings = get_global_settings();
+                let (component_path, _) = match settings.find_component(component_name) {
+                    Some(found) => found,
+                    None => {
+                        return tool_text_response!(format!(
+                            "Component '{}' not found",
+                            component_name
+                        ));
+                    }
+                };
+
+                if !component_path.exists() {
+                    return tool_text_response!(format!(
+                        "Documentation path does not exist: {} Ensure your configuration is correct.",
+                        component_path.display()
+                    ));
+                }
+
+                match get_files_to_depth(&component_path, depth).await {
+                    Ok(files) => tool_text_response!(render_tree(&component_path, files)),
+                    Err(e) => tool_text_response!(format!("Error retrieving files: {}", e)),
+                }
+            })
+        }
+    }
+}
+
+/// Render `files` as an indented tree relative to `root` in path order, directories
+/// suffixed with `/` and each file followed by its relative path
+fn render_tree(root: &Path, files: Vec<PathBuf>) -> String {
+    let mut relative: Vec<PathBuf> = files
+        .iter()
+        .filter_map(|file| file.strip_prefix(root).ok())
+        .map(Path::to_path_buf)
+        .collect();
+    relative.sort();
+
+    let root_name = root
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| root.display().to_string());
+    let mut result = format!("{}/\n", root_name);
+    if relative.is_empty() {
+        result.push_str(&format!("{}*No files*\n", INDENT));
+        return result;
+    }
+
+    // Directories of the previous file, so shared parents are only printed once
+    let mut open_dirs: Vec<String> = Vec::new();
+    for path in relative {
+        let mut parts: Vec<String> = path
+            .iter()
+            .map(|part| part.to_string_lossy().into_owned())
+            .collect();
+        let Some(name) = parts.pop() else {
+            continue;
+        };
+
+        let shared = open_dirs
+            .iter()
+            .zip(&parts)
+            .take_while(|(open, part)| open == part)
+            .count();
+        open_dirs.truncate(shared);
+        for dir in &parts[shared..] {
+            result.push_str(&format!("{}{}/\n", INDENT.repeat(open_dirs.len() + 1), dir));
+            open_dirs.push(dir.clone());
+        }
+
+        result.push_str(&format!(
+            "{}{} ({})\n",
+            INDENT.repeat(open_dirs.len() + 1),
+            name,
+            path.display()
+        ));
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_render_tree_of_doc_dir() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path().join("docs");
+        fs::create_dir_all(root.join("api").join("v1"))?;
+        fs::create_dir_all(root.join("guides"))?;
+        fs::write(root.join("README.md"), "root")?;
+        fs::write(root.join("api").join("README.md"), "api")?;
+        fs::write(root.join("api").join("v1").join("users.md"), "users")?;
+        fs::write(root.join("guides").join("setup.md"), "setup")?;
+
+        let files = get_files_to_depth(&root, 2).await?;
+        assert_eq!(
+            render_tree(&root, files),
+            "docs/
+  README.md (README.md)
+  api/
+    README.md (api/README.md)
+    v1/
+      users.md (api/v1/users.md)
+  guides/
+    setup.md (guides/setup.md)
+"
+        );
+
+        let files = get_files_to_depth(&root, 0).await?;
+        assert_eq!(
+            render_tree(&root, files),
+            "docs/\n  README.md (README.md)\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_tree_without_files() {
+        assert_eq!(
+            render_tree(Path::new("/tmp/docs"), Vec::new()),
+            "docs/\n  *No files*\n"
+        );
+    }
+}
@@ -1,32 +1,114 @@
 use std::{
-    fs,
+    collections::{HashMap, VecDeque},
+    fs, io,
     path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock},
+    time::SystemTime,
 };
 
 use crate::get_global_settings;
+use crate::settings::Component;
 use mcp_core::{
     tool_text_response,
     tools::ToolHandlerFn,
     types::{CallToolRequest, Tool},
 };
 use serde_json::json;
+use tokio::{sync::Semaphore, task::JoinSet};
 
 pub struct GetDocumentationTool;
 
+/// Response size cap used when a request doesn't set `max_bytes`
+const DEFAULT_MAX_BYTES: usize = 100 * 1024;
+
+/// Arguments of the `get_docs` input schema
+#[derive(Debug, PartialEq)]
+struct GetDocsArgs {
+    component_name: String,
+    depth: usize,
+    summary_only: bool,
+    max_bytes: usize,
+    /// Files to skip, in path order
+    offset: usize,
+    /// Most files to return, all remaining files if unset
+    limit: Option<usize>,
+}
+
+impl GetDocsArgs {
+    fn from_request(request: &CallToolRequest) -> Result<Self, String> {
+        let args = request
+            .arguments
+            .as_ref()
+            .ok_or_else(|| "No arguments provided".to_string())?;
+
+        let component_name = args
+            .get("component_name")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "component_name parameter is required".to_string())?;
+
+        let depth = args.get("depth").and_then(|v| v.as_u64()).ok_or_else(|| {
+            "depth parameter is required and must be a non-negative integer".to_string()
+        })?;
+
+        let summary_only = args
+            .get("summary_only")
+            .and_then(|v| v.as_bool())
+            .ok_or_else(|| {
+                "summary_only parameter is required and must be a boolean".to_string()
+            })?;
+
+        let max_bytes = match args.get("max_bytes") {
+            None => DEFAULT_MAX_BYTES,
+            Some(value) => value
+                .as_u64()
+                .filter(|&max_bytes| max_bytes > 0)
+                .ok_or_else(|| "max_bytes must be a positive integer".to_string())?
+                as usize,
+        };
+
+        let offset = match args.get("offset") {
+            None => 0,
+            Some(value) => value
+                .as_u64()
+                .ok_or_else(|| "offset must be a non-negative integer".to_string())?
+                as usize,
+        };
+
+        let limit = match args.get("limit") {
+            None => None,
+            Some(value) => Some(
+                value
+                    .as_u64()
+                    .filter(|&limit| limit > 0)
+                    .ok_or_else(|| "limit must be a positive integer".to_string())?
+                    as usize,
+            ),
+        };
+
+        Ok(Self {
+            component_name: component_name.to_string(),
+            depth: depth as usize,
+            summary_only,
+            max_bytes,
+            offset,
+            limit,
+        })
+    }
+}
+
 impl GetDocumentationTool {
     pub fn tool() -> Tool {
         let settings = get_global_settings();
-        let service_names: Vec<String> = settings.get_component_names();
+        let component_names = settings.get_component_names().join(", ");
         Tool {
             name: "get_docs".to_string(),
-            description: Some("Retrieves Documentation for given projectss".to_string()),
+            description: Some("Retrieves Documentation for given components".to_string()),
             input_schema: json!({
                 "type": "object",
                 "properties": {
                     "component_name": {
                         "type": "string",
-                        "enum": service_names,
-                        "description": "The name of the project."
+                        "description": format!("The name of the component to retrieve documentation for, one of: {}.", component_names)
                     },
                     "depth": {
                         "type": "integer",
@@ -36,10 +118,27 @@ impl GetDocumentationTool {
                     "summary_only": {
                         "type": "boolean",
                         "description": "If true returns only summaries for the directories false will return all file summaries."
+                    },
+                    "max_bytes": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "default": DEFAULT_MAX_BYTES,
+                        "description": "Most bytes of documentation to return, the rest is truncated."
+                    },
+                    "offset": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "default": 0,
+                        "description": "Number of files to skip, for paging through large components. Use the next offset from the previous response."
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "description": "Most files to return. Omit to return every remaining file."
                     }
                 },
 
-                "required": ["project_name", "depth", "summary_only"]
+                "required": ["component_name", "depth", "summary_only"]
             }),
             annotations: None,
         }
@@ -48,31 +147,22 @@ impl GetDocumentationTool {
     pub fn call() -> ToolHandlerFn {
         move |request: CallToolRequest| {
             Box::pin(async move {
-                let settings = get_global_settings();
-                // Extract parameters from the request
-                let args = match request.arguments.as_ref() {
-                    Some(args) => args,
-                    None => return tool_text_response!("No arguments provided"),
-                };
-
-                let component_name = match args.get("component_name").and_then(|v| v.as_str()) {
-                    Some(name) => name,
-                    None => return tool_text_response!("component_name parameter is required"),
+                let GetDocsArgs {
+                    component_name,
+                    depth,
+                    summary_only,
+                    max_bytes,
+                    offset,
+                    limit,
+                } = match GetDocsArgs::from_request(&request) {
+                    Ok(args) => args,
+                    Err(message) => return tool_text_response!(message),
                 };
-
-                let depth = match args.get("depth").and_then(|v| v.as_i64()) {
-                    Some(d) => d as usize,
-                    None => 0,
-                };
-
-                let summary_only = args
-                    .get("summary_only")
-                    .and_then(|v| v.as_bool())
-                    .unwrap_or(false);
+                let settings = get_global_settings();
 
                 // Find the component path
-                let component_path = match settings.get_component_path(component_name) {
-                    Some(path) => path,
+                let (component_path, component) = match settings.find_component(&component_name) {
+                    Some(found) => found,
                     None => {
                         return tool_text_response!(format!(
                             "Component '{}' not found",
@@ -89,7 +179,7 @@ impl GetDocumentationTool {
                     ));
                 }
 
-                let mut files = match get_files_to_depth(component_path, depth) {
+                let mut files = match get_files_to_depth(&component_path, depth).await {
                     Ok(value) => value,
                     Err(e) => {
                         return tool_text_response!(format!(
@@ -100,15 +190,21 @@ impl GetDocumentationTool {
                 };
 
                 if summary_only {
-                    files.retain(|file| {
-                        file.file_name()
-                            .and_then(|name| name.to_str())
-                            .map(|name| name.to_lowercase().contains("readme.md"))
-                            .unwrap_or(false)
-                    });
+                    retain_indices(&mut files, &component_path, component);
                 }
 
-                let content = format_file_contents(files, None);
+                let total = files.len();
+                let (files, next_offset) = paginate(files, offset, limit);
+                let listed = files.len();
+                let (mut content, emitted) = format_file_contents(
+                    files,
+                    Some(max_bytes),
+                    settings.cache_files,
+                    settings.max_concurrent_reads,
+                )
+                .await;
+                let (shown, next_offset) = shown_on_page(offset, listed, emitted, next_offset);
+                content.push_str(&page_footer(offset, shown, total, next_offset));
 
                 tool_text_response!(content)
             })
@@ -116,34 +212,32 @@ impl GetDocumentationTool {
     }
 }
 
-// Alternative implementation using iterative approach with a queue
-fn get_files_to_depth<P: AsRef<Path>>(
+/// Every file under `root_dir` at most `max_depth` directories down, breadth first
+///
+/// Uses async directory reads so large doc trees don't block the runtime.
+pub(crate) async fn get_files_to_depth<P: AsRef<Path>>(
     root_dir: P,
     max_depth: usize,
-) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
-    use std::collections::VecDeque;
-
-    let root_path = root_dir.as_ref();
+) -> io::Result<Vec<PathBuf>> {
     let mut files = Vec::new();
     let mut queue = VecDeque::new();
 
     // Queue entries: (path, depth)
-    queue.push_back((root_path.to_path_buf(), 0));
+    queue.push_back((root_dir.as_ref().to_path_buf(), 0));
 
     while let Some((current_path, depth)) = queue.pop_front() {
-        if depth > max_depth {
-            continue;
-        }
-
-        let entries = fs::read_dir(&current_path)?;
+        let mut entries = tokio::fs::read_dir(&current_path).await?;
 
-        for entry in entries {
-            let entry = entry?;
+        while let Some(entry) = entries.next_entry().await? {
             let path = entry.path();
+            // Follows symlinks, like `Path::is_file` and `Path::is_dir`
+            let Ok(metadata) = tokio::fs::metadata(&path).await else {
+                continue;
+            };
 
-            if path.is_file() {
+            if metadata.is_file() {
                 files.push(path);
-            } else if path.is_dir() && depth < max_depth {
+            } else if metadata.is_dir() && depth < max_depth {
                 queue.push_back((path, depth + 1));
             }
         }
@@ -152,47 +246,250 @@ fn get_files_to_depth<P: AsRef<Path>>(
     Ok(files)
 }
 
-fn format_file_contents(file_paths: Vec<PathBuf>, max_size: Option<usize>) -> String {
-    let mut result = String::new();
+/// The `limit` files after the first `offset`, in path order, and the offset of the
+/// next page if any files are left
+fn paginate(
+    mut files: Vec<PathBuf>,
+    offset: usize,
+    limit: Option<usize>,
+) -> (Vec<PathBuf>, Option<usize>) {
+    files.sort();
+    let total = files.len();
+    let page: Vec<PathBuf> = files
+        .into_iter()
+        .skip(offset)
+        .take(limit.unwrap_or(usize::MAX))
+        .collect();
+    let end = offset + page.len();
+    let next_offset = (end < total).then_some(end);
+    (page, next_offset)
+}
 
-    for path in file_paths {
-        result.push_str(&format!("\n## 📄 {}\n\n", path.display()));
-
-        match fs::read_to_string(&path) {
-            Ok(content) => {
-                let display_content = if let Some(max) = max_size {
-                    if content.len() > max {
-                        format!(
-                            "{}...\n\n*[File truncated at {} bytes]*",
-                            &content[..max],
-                            max
-                        )
-                    } else {
-                        content
-                    }
-                } else {
-                    content
-                };
+/// How many of the `listed` files a page really covers once `max_bytes` has cut it down to
+/// `emitted` whole files, and the offset of the next page
+///
+/// The next page starts at the first file that was cut off, except that a file too large
+/// for `max_bytes` on its own counts as shown, so paging still moves past it.
+fn shown_on_page(
+    offset: usize,
+    listed: usize,
+    emitted: usize,
+    next_offset: Option<usize>,
+) -> (usize, Option<usize>) {
+    let shown = match emitted {
+        0 => listed.min(1),
+        emitted => emitted,
+    };
+    match shown < listed {
+        true => (shown, Some(offset + shown)),
+        false => (shown, next_offset),
+    }
+}
+
+/// Which files this page covers and, if there are more, the offset to ask for next
+fn page_footer(offset: usize, shown: usize, total: usize, next_offset: Option<usize>) -> String {
+    let range = match shown {
+        0 => format!("no files (offset {} of {})", offset, total),
+        _ => format!("files {}-{} of {}", offset + 1, offset + shown, total),
+    };
+    match next_offset {
+        Some(next) => format!(
+            "\n---\n*Showing {}. has_more: true, next offset: {}*\n",
+            range, next
+        ),
+        None => format!("\n---\n*Showing {}. has_more: false*\n", range),
+    }
+}
+
+/// Keep only the component's directory index pages, the files named exactly as its index names
+fn retain_indices(files: &mut Vec<PathBuf>, root: &Path, component: &Component) {
+    files.retain(|file| component.is_index(root, file));
+}
+
+/// File contents keyed by path, alongside the modified time they were read at
+static FILE_CACHE: OnceLock<Mutex<HashMap<PathBuf, (SystemTime, String)>>> = OnceLock::new();
+
+/// Read a file, reusing the cached contents while its modified time is unchanged
+fn read_cached(path: &Path) -> io::Result<String> {
+    let modified = fs::metadata(path)?.modified()?;
+    let cache = FILE_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+
+    if let Some((cached_at, content)) = cache
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .get(path)
+        && *cached_at == modified
+    {
+        return Ok(content.clone());
+    }
+
+    let content = fs::read_to_string(path)?;
+    cache
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .insert(path.to_path_buf(), (modified, content.clone()));
+    Ok(content)
+}
+
+/// Render each file under a heading, reading at most `max_concurrent_reads` files at once
+/// on the blocking pool while keeping the order of `file_paths`
+///
+/// The whole output is cut off at `max_bytes`, ending with a truncation notice. Returns the
+/// output and how many files it includes in full.
+async fn format_file_contents(
+    file_paths: Vec<PathBuf>,
+    max_bytes: Option<usize>,
+    use_cache: bool,
+    max_concurrent_reads: usize,
+) -> (String, usize) {
+    let permits = Arc::new(Semaphore::new(max_concurrent_reads.max(1)));
+    let mut reads = JoinSet::new();
+    for (index, path) in file_paths.iter().cloned().enumerate() {
+        let permit = permits
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("read semaphore is never closed");
+        reads.spawn_blocking(move || {
+            let _permit = permit;
+            let read = if use_cache {
+                read_cached(&path)
+            } else {
+                fs::read_to_string(&path)
+            };
+            (index, read)
+        });
+    }
 
-                result.push_str(&format!("```\n{}\n```\n\n", display_content));
+    let mut contents: Vec<Option<io::Result<String>>> = std::iter::repeat_with(|| None)
+        .take(file_paths.len())
+        .collect();
+    while let Some(joined) = reads.join_next().await {
+        let (index, read) = joined.expect("file read task panicked");
+        contents[index] = Some(read);
+    }
+
+    let mut result = String::new();
+    let mut emitted = 0;
+    for (path, read) in file_paths.iter().zip(contents) {
+        let section = match read.expect("every spawned read is joined") {
+            Ok(content) => format!("\n## 📄 {}\n\n```\n{}\n```\n\n", path.display(), content),
+            Err(e) => format!(
+                "\n## 📄 {}\n\n*Error reading file: {}*\n\n",
+                path.display(),
+                e
+            ),
+        };
+
+        if let Some(max) = max_bytes
+            && result.len() + section.len() > max
+        {
+            let mut cut = max - result.len();
+            while !section.is_char_boundary(cut) {
+                cut -= 1;
             }
-            Err(e) => {
-                result.push_str(&format!("*Error reading file: {}*\n\n", e));
+            let kept = &section[..cut];
+            result.push_str(kept);
+            // Close a code block cut off part way so the notice renders as text
+            if kept.matches("```").count() % 2 == 1 {
+                result.push_str("\n```\n");
             }
+            result.push_str(&format!(
+                "\n*[truncated: output limited to {} bytes, request a smaller depth or summary_only]*\n",
+                max
+            ));
+            break;
         }
+        result.push_str(&section);
+        emitted += 1;
     }
 
-    result
+    (result, emitted)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use mcp_core::types::ToolResponseContent;
+    use std::collections::HashMap;
     use std::fs;
     use tempfile::TempDir;
 
+    fn request(arguments: serde_json::Value) -> CallToolRequest {
+        CallToolRequest {
+            name: "get_docs".to_string(),
+            arguments: Some(serde_json::from_value::<HashMap<_, _>>(arguments).unwrap()),
+            meta: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_call_without_component_name() {
+        let response =
+            GetDocumentationTool::call()(request(json!({"depth": 1, "summary_only": true}))).await;
+
+        match response.content.as_slice() {
+            [ToolResponseContent::Text(text)] => {
+                assert_eq!(text.text, "component_name parameter is required")
+            }
+            other => panic!("unexpected response: {:?}", other),
+        }
+    }
+
     #[test]
-    fn test_get_files_to_depth() -> Result<(), Box<dyn std::error::Error>> {
+    fn test_args_enforce_required_params() {
+        let args = GetDocsArgs::from_request(&request(
+            json!({"component_name": "doc-reader", "depth": 2, "summary_only": false}),
+        ));
+        assert_eq!(
+            args,
+            Ok(GetDocsArgs {
+                component_name: "doc-reader".to_string(),
+                depth: 2,
+                summary_only: false,
+                max_bytes: DEFAULT_MAX_BYTES,
+                offset: 0,
+                limit: None,
+            })
+        );
+
+        let capped = GetDocsArgs::from_request(&request(
+            json!({"component_name": "doc-reader", "depth": 2, "summary_only": false, "max_bytes": 512}),
+        ));
+        assert_eq!(capped.unwrap().max_bytes, 512);
+
+        let zero = GetDocsArgs::from_request(&request(
+            json!({"component_name": "doc-reader", "depth": 2, "summary_only": false, "max_bytes": 0}),
+        ));
+        assert!(zero.unwrap_err().starts_with("max_bytes must be"));
+
+        let paged = GetDocsArgs::from_request(&request(
+            json!({"component_name": "doc-reader", "depth": 0, "summary_only": true, "offset": 4, "limit": 2}),
+        ))
+        .unwrap();
+        assert_eq!((paged.offset, paged.limit), (4, Some(2)));
+
+        let missing_depth = GetDocsArgs::from_request(&request(
+            json!({"component_name": "doc-reader", "summary_only": false}),
+        ));
+        assert!(
+            missing_depth
+                .unwrap_err()
+                .starts_with("depth parameter is required")
+        );
+
+        let missing_summary = GetDocsArgs::from_request(&request(
+            json!({"component_name": "doc-reader", "depth": 0}),
+        ));
+        assert!(
+            missing_summary
+                .unwrap_err()
+                .starts_with("summary_only parameter is required")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_files_to_depth() -> Result<(), Box<dyn std::error::Error>> {
         // Create a temporary directory structure for testing
         let temp_dir = TempDir::new()?;
         let root = temp_dir.path();
@@ -208,23 +505,136 @@ mod tests {
         )?;
 
         // Test depth 0 (only root level)
-        let files = get_files_to_depth(root, 0)?;
+        let files = get_files_to_depth(root, 0).await?;
         assert_eq!(files.len(), 1);
         assert!(files[0].file_name().unwrap() == "file1.txt");
 
         // Test depth 1 (root + one level down)
-        let files = get_files_to_depth(root, 1)?;
-        assert_eq!(files.len(), 2);
+        let mut files = get_files_to_depth(root, 1).await?;
+        files.sort();
+        assert_eq!(
+            files,
+            vec![root.join("dir1").join("file2.txt"), root.join("file1.txt")]
+        );
 
         // Test depth 2 (all files)
-        let files = get_files_to_depth(root, 2)?;
+        let files = get_files_to_depth(root, 2).await?;
         assert_eq!(files.len(), 3);
+        assert!(files.contains(&root.join("dir1").join("subdir").join("file3.txt")));
 
         Ok(())
     }
 
     #[test]
-    fn test_get_file_contents() -> Result<(), Box<dyn std::error::Error>> {
+    fn test_paginate_slices_in_path_order() {
+        let files: Vec<PathBuf> = ["c.md", "a.md", "e.md", "b.md", "d.md"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+
+        let (page, next) = paginate(files.clone(), 0, Some(2));
+        assert_eq!(page, vec![PathBuf::from("a.md"), PathBuf::from("b.md")]);
+        assert_eq!(next, Some(2));
+        assert!(
+            page_footer(0, page.len(), files.len(), next)
+                .contains("has_more: true, next offset: 2")
+        );
+
+        let (page, next) = paginate(files.clone(), 2, Some(2));
+        assert_eq!(page, vec![PathBuf::from("c.md"), PathBuf::from("d.md")]);
+        assert_eq!(next, Some(4));
+
+        let (page, next) = paginate(files.clone(), 4, Some(2));
+        assert_eq!(page, vec![PathBuf::from("e.md")]);
+        assert_eq!(next, None);
+        assert!(
+            page_footer(4, page.len(), files.len(), next)
+                .contains("files 5-5 of 5. has_more: false")
+        );
+
+        let (page, next) = paginate(files.clone(), 0, None);
+        assert_eq!(page.len(), 5);
+        assert_eq!(next, None);
+
+        let (page, next) = paginate(files, 10, Some(2));
+        assert!(page.is_empty());
+        assert_eq!(next, None);
+    }
+
+    #[test]
+    fn test_truncated_files_are_left_for_the_next_page() {
+        // All of a page that fits is shown
+        assert_eq!(shown_on_page(2, 2, 2, Some(4)), (2, Some(4)));
+        assert_eq!(shown_on_page(4, 1, 1, None), (1, None));
+        // Files cut off by max_bytes start the next page, even on the last one
+        assert_eq!(shown_on_page(2, 2, 1, Some(4)), (1, Some(3)));
+        assert_eq!(shown_on_page(0, 5, 3, None), (3, Some(3)));
+        // A file too large on its own is passed over rather than returned forever
+        assert_eq!(shown_on_page(3, 2, 0, None), (1, Some(4)));
+        assert_eq!(shown_on_page(4, 1, 0, None), (1, None));
+        assert_eq!(shown_on_page(9, 0, 0, None), (0, None));
+    }
+
+    #[test]
+    fn test_summary_only_keeps_exact_index_names() {
+        let files: Vec<PathBuf> = [
+            "README.md",
+            "src/README.md",
+            "src/main.md",
+            "src/readme.md",
+            "notes/my-readme.md-notes",
+            "site/index.md",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+
+        let root = Path::new("");
+        let mut readmes = files.clone();
+        retain_indices(&mut readmes, root, &Component::default());
+        assert_eq!(
+            readmes,
+            vec![PathBuf::from("README.md"), PathBuf::from("src/README.md")]
+        );
+
+        let index_md = Component {
+            index_name: "index.md".to_string(),
+            ..Default::default()
+        };
+        let mut indices = files;
+        retain_indices(&mut indices, root, &index_md);
+        assert_eq!(indices, vec![PathBuf::from("site/index.md")]);
+    }
+
+    #[test]
+    fn test_summary_only_keeps_a_differently_named_root_page() {
+        let files: Vec<PathBuf> = [
+            "docs/index.md",
+            "docs/README.md",
+            "docs/src/README.md",
+            "docs/src/index.md",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+        let component = Component {
+            root_index_name: Some("index.md".to_string()),
+            ..Default::default()
+        };
+
+        let mut indices = files;
+        retain_indices(&mut indices, Path::new("docs"), &component);
+        assert_eq!(
+            indices,
+            vec![
+                PathBuf::from("docs/index.md"),
+                PathBuf::from("docs/src/README.md")
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_file_contents() -> Result<(), Box<dyn std::error::Error>> {
         let temp_dir = TempDir::new()?;
         let root = temp_dir.path();
 
@@ -234,10 +644,79 @@ mod tests {
         fs::write(&file1, "Hello, world!")?;
         fs::write(&file2, "This is a longer content that might be truncated")?;
 
-        let paths = vec![file1, file2];
-        let contents = format_file_contents(paths, None);
+        let paths = vec![file1.clone(), file2.clone()];
+        let (contents, emitted) = format_file_contents(paths, None, false, 1).await;
 
+        assert_eq!(emitted, 2);
         assert!(contents.contains("Hello, world!"));
+        // Files appear in the order given, however the reads complete
+        let first = contents.find(&file1.display().to_string()).unwrap();
+        let second = contents.find(&file2.display().to_string()).unwrap();
+        assert!(first < second);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_max_bytes_caps_total_output() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let paths: Vec<PathBuf> = (0..5)
+            .map(|i| temp_dir.path().join(format!("file{}.md", i)))
+            .collect();
+        for path in &paths {
+            fs::write(path, "x".repeat(100))?;
+        }
+
+        let (uncapped, _) = format_file_contents(paths.clone(), None, false, 2).await;
+        let (contents, emitted) = format_file_contents(paths.clone(), Some(300), false, 2).await;
+
+        assert!(uncapped.len() > 300);
+        let (kept, notice) = contents.split_once("\n*[truncated").unwrap();
+        assert!(kept.trim_end_matches("\n```\n").len() <= 300);
+        assert!(notice.contains("300 bytes"));
+        assert!(contents.contains("file1.md"));
+        assert!(!contents.contains("file4.md"));
+        // The cut-off code block is closed before the notice
+        assert_eq!(contents.matches("```").count() % 2, 0);
+
+        // Only whole files count as emitted, so the next page starts at the one cut off
+        let section =
+            |path: &PathBuf| format!("{}\n\n```\n{}\n```", path.display(), "x".repeat(100));
+        assert!((1..5).contains(&emitted));
+        assert!(contents.contains(&section(&paths[emitted - 1])));
+        assert!(!contents.contains(&section(&paths[emitted])));
+        assert_eq!(shown_on_page(0, 5, emitted, None), (emitted, Some(emitted)));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cache_refreshes_on_modification() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("README.md");
+        fs::write(&path, "first version")?;
+        let first_modified = fs::metadata(&path)?.modified()?;
+
+        assert_eq!(read_cached(&path)?, "first version");
+
+        // Overwrite without touching the mtime: the cached copy is served
+        fs::write(&path, "unseen edit")?;
+        fs::File::options()
+            .write(true)
+            .open(&path)?
+            .set_modified(first_modified)?;
+        assert_eq!(read_cached(&path)?, "first version");
+
+        // A new mtime invalidates the entry
+        fs::write(&path, "second version")?;
+        fs::File::options()
+            .write(true)
+            .open(&path)?
+            .set_modified(first_modified + std::time::Duration::from_secs(60))?;
+        assert_eq!(read_cached(&path)?, "second version");
+
+        let (contents, _) = format_file_contents(vec![path], None, true, 4).await;
+        assert!(contents.contains("second version"));
 
         Ok(())
     }
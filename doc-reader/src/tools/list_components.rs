@@ -0,0 +1,139 @@
+use crate::{get_global_settings, settings::Settings};
+use mcp_core::{
+    tool_text_response,
+    tools::ToolHandlerFn,
+    types::{CallToolRequest, Tool},
+};
+use serde_json::json;
+
+pub struct ListComponentsTool;
+
+impl ListComponentsTool {
+    pub fn tool() -> Tool {
+        Tool {
+            name: "list_components".to_string(),
+            description: Some(
+                "Lists configured projects and their documented components, with doc paths and whether they exist".to_string(),
+            ),
+            input_schema: json!({
+                "type": "object",
+                "properties": {}
+            }),
+            annotations: None,
+        }
+    }
+
+    pub fn call() -> ToolHandlerFn {
+        move |_request: CallToolRequest| {
+            Box::pin(async move {
+                let settings = get_global_settings();
+                tool_text_response!(format_components(&settings))
+            })
+        }
+    }
+}
+
+fn format_components(settings: &Settings) -> String {
+    let mut project_names = settings.get_project_names();
+    project_names.sort();
+
+    if project_names.is_empty() {
+        return "No projects configured".to_string();
+    }
+
+    let mut result = String::new();
+    for project_name in project_names {
+        let Some(project) = settings.get_project(&project_name) else {
+            continue;
+        };
+        result.push_str(&format!("\n## 📦 {}\n\n", project_name));
+        result.push_str(&format!(
+            "**Location:** `{}`\n\n",
+            project.location.display()
+        ));
+
+        let mut component_names = settings.get_project_component_names(&project_name);
+        component_names.sort();
+        if component_names.is_empty() {
+            result.push_str("*No components configured*\n");
+            continue;
+        }
+
+        for component_name in component_names {
+            match settings.get_component_path(&component_name) {
+                Some(path) => {
+                    let status = if path.exists() { "exists" } else { "missing" };
+                    result.push_str(&format!(
+                        "- `{}`: `{}` ({})\n",
+                        component_name,
+                        path.display(),
+                        status
+                    ));
+                }
+                None => result.push_str(&format!("- `{}`: unresolved\n", component_name)),
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::{Component, Project};
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_format_components() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        fs::create_dir(temp_dir.path().join("docs"))?;
+
+        let mut components = HashMap::new();
+        components.insert(
+            "present".to_string(),
+            Component {
+                relative_path: PathBuf::from("docs"),
+                ..Default::default()
+            },
+        );
+        components.insert(
+            "absent".to_string(),
+            Component {
+                relative_path: PathBuf::from("missing"),
+                ..Default::default()
+            },
+        );
+        let mut projects = HashMap::new();
+        projects.insert(
+            "demo".to_string(),
+            Project {
+                location: temp_dir.path().to_path_buf(),
+                components,
+            },
+        );
+
+        let result = format_components(&Settings {
+            projects,
+            cache_files: true,
+            max_concurrent_reads: 1,
+        });
+
+        assert!(result.contains("## 📦 demo"));
+        assert!(result.contains(&format!("**Location:** `{}`", temp_dir.path().display())));
+        assert!(result.contains(&format!(
+            "- `present`: `{}` (exists)",
+            temp_dir.path().join("docs").display()
+        )));
+        assert!(result.contains(&format!(
+            "- `absent`: `{}` (missing)",
+            temp_dir.path().join("missing").display()
+        )));
+        assert!(result.find("absent") < result.find("present"));
+
+        Ok(())
+    }
+}
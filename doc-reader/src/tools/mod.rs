@@ -1 +1,5 @@
+pub mod get_doc_tree;
 pub mod get_docs;
+pub mod list_components;
+pub mod reload_config;
+pub mod search_docs;
@@ -0,0 +1,45 @@
+use crate::reload_global_settings;
+use mcp_core::{
+    tool_text_response,
+    tools::ToolHandlerFn,
+    types::{CallToolRequest, Tool},
+};
+use serde_json::json;
+
+pub struct ReloadConfigTool;
+
+impl ReloadConfigTool {
+    pub fn tool() -> Tool {
+        Tool {
+            name: "reload_config".to_string(),
+            description: Some(
+                "Re-reads the doc-reader config so newly added projects and components can be used without a restart".to_string(),
+            ),
+            input_schema: json!({
+                "type": "object",
+                "properties": {}
+            }),
+            annotations: None,
+        }
+    }
+
+    pub fn call() -> ToolHandlerFn {
+        move |_request: CallToolRequest| {
+            Box::pin(async move {
+                match reload_global_settings() {
+                    Ok(settings) => {
+                        tool_text_response!(format!(
+                            "Reloaded config: {} projects, components: {}. Components added since startup are missing from other tools' descriptions but can still be passed by name.",
+                            settings.projects.len(),
+                            settings.get_component_names().join(", ")
+                        ))
+                    }
+                    Err(e) => tool_text_response!(format!(
+                        "Error reloading config, keeping the previous settings: {}",
+                        e
+                    )),
+                }
+            })
+        }
+    }
+}
@@ -0,0 +1,314 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::get_global_settings;
+use grep::{
+    regex::{RegexMatcher, RegexMatcherBuilder},
+    searcher::{Searcher, SearcherBuilder, Sink, SinkContext, SinkMatch},
+};
+use mcp_core::{
+    tool_text_response,
+    tools::ToolHandlerFn,
+    types::{CallToolRequest, Tool},
+};
+use serde_json::json;
+
+/// Lines of context shown either side of a match
+const CONTEXT_LINES: usize = 2;
+/// Maximum length of a search response before results are truncated
+const MAX_RESPONSE_CHARS: usize = 20_000;
+
+pub struct SearchDocumentationTool;
+
+impl SearchDocumentationTool {
+    pub fn tool() -> Tool {
+        let settings = get_global_settings();
+        let component_names = settings.get_component_names().join(", ");
+        Tool {
+            name: "search_docs".to_string(),
+            description: Some(
+                "Searches documentation for a case-insensitive regex or text and returns matching sections with their relative paths".to_string(),
+            ),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "query": {
+                        "type": "string",
+                        "description": "Case-insensitive regex or plain text to search for."
+                    },
+                    "component_name": {
+                        "type": "string",
+                        "description": format!("Limit the search to this component, one of: {}. Searches every component if omitted.", component_names)
+                    }
+                },
+                "required": ["query"]
+            }),
+            annotations: None,
+        }
+    }
+
+    pub fn call() -> ToolHandlerFn {
+        move |request: CallToolRequest| {
+            Box::pin(async move {
+                let settings = get_global_settings();
+                let args = match request.arguments.as_ref() {
+                    Some(args) => args,
+                    None => return tool_text_response!("No arguments provided"),
+                };
+
+                let query = match args.get("query").and_then(|v| v.as_str()) {
+                    Some(query) if !query.trim().is_empty() => query,
+                    _ => return tool_text_response!("query parameter is required"),
+                };
+
+                let component_names = match args.get("component_name").and_then(|v| v.as_str()) {
+                    Some(name) => vec![name.to_string()],
+                    None => {
+                        let mut names = settings.get_component_names();
+                        names.sort();
+                        names
+                    }
+                };
+
+                let mut components = Vec::new();
+                for name in component_names {
+                    match settings.get_component_path(&name) {
+                        Some(path) => components.push((name, path)),
+                        None => {
+                            return tool_text_response!(format!("Component '{}' not found", name));
+                        }
+                    }
+                }
+
+                let content = match search_components(&components, query, MAX_RESPONSE_CHARS) {
+                    Ok(content) => content,
+                    Err(e) => {
+                        return tool_text_response!(format!(
+                            "Error searching documentation: {}",
+                            e
+                        ));
+                    }
+                };
+
+                tool_text_response!(content)
+            })
+        }
+    }
+}
+
+/// A contiguous block of matching and context lines within a file
+#[derive(Debug, Default, PartialEq)]
+struct Section {
+    lines: Vec<String>,
+}
+
+/// Collects matches into sections, splitting wherever the searcher skips lines
+#[derive(Default)]
+struct SectionSink {
+    sections: Vec<Section>,
+    current: Section,
+}
+
+impl SectionSink {
+    fn push(&mut self, line_number: Option<u64>, separator: char, bytes: &[u8]) {
+        let text = String::from_utf8_lossy(bytes);
+        self.current.lines.push(format!(
+            "{}{} {}",
+            line_number.unwrap_or_default(),
+            separator,
+            text.trim_end_matches(['\r', '\n'])
+        ));
+    }
+
+    fn into_sections(mut self) -> Vec<Section> {
+        if !self.current.lines.is_empty() {
+            self.sections.push(self.current);
+        }
+        self.sections
+    }
+}
+
+impl Sink for SectionSink {
+    type Error = io::Error;
+
+    fn matched(&mut self, _: &Searcher, mat: &SinkMatch<'_>) -> Result<bool, io::Error> {
+        self.push(mat.line_number(), ':', mat.bytes());
+        Ok(true)
+    }
+
+    fn context(&mut self, _: &Searcher, ctx: &SinkContext<'_>) -> Result<bool, io::Error> {
+        self.push(ctx.line_number(), '-', ctx.bytes());
+        Ok(true)
+    }
+
+    fn context_break(&mut self, _: &Searcher) -> Result<bool, io::Error> {
+        if !self.current.lines.is_empty() {
+            self.sections.push(std::mem::take(&mut self.current));
+        }
+        Ok(true)
+    }
+}
+
+/// Build a case-insensitive matcher, treating invalid regex as literal text
+fn build_matcher(query: &str) -> Result<RegexMatcher, grep::regex::Error> {
+    let mut builder = RegexMatcherBuilder::new();
+    builder.case_insensitive(true);
+    builder
+        .build(query)
+        .or_else(|_| builder.fixed_strings(true).build(query))
+}
+
+fn search_file(
+    searcher: &mut Searcher,
+    matcher: &RegexMatcher,
+    path: &Path,
+) -> Result<Vec<Section>, io::Error> {
+    let mut sink = SectionSink::default();
+    searcher.search_path(matcher, path, &mut sink)?;
+    Ok(sink.into_sections())
+}
+
+/// All files beneath `root`, sorted for stable output
+fn collect_files(root: &Path) -> Result<Vec<PathBuf>, io::Error> {
+    let mut files = Vec::new();
+    let mut stack = vec![root.to_path_buf()];
+
+    while let Some(dir) = stack.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                stack.push(path);
+            } else if path.is_file() {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Search each component's docs and format the hits, capped at `max_chars`
+fn search_components(
+    components: &[(String, PathBuf)],
+    query: &str,
+    max_chars: usize,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let matcher = build_matcher(query)?;
+    let mut searcher = SearcherBuilder::new()
+        .line_number(true)
+        .before_context(CONTEXT_LINES)
+        .after_context(CONTEXT_LINES)
+        .build();
+
+    let mut result = String::new();
+    let mut hits = 0;
+
+    for (component_name, root) in components {
+        if !root.exists() {
+            continue;
+        }
+
+        for path in collect_files(root)? {
+            let sections = search_file(&mut searcher, &matcher, &path)?;
+            if sections.is_empty() {
+                continue;
+            }
+            hits += sections.len();
+
+            let relative_path = path.strip_prefix(root).unwrap_or(&path);
+            let mut entry = format!(
+                "\n## 📄 {} ({})\n\n",
+                relative_path.display(),
+                component_name
+            );
+            for section in sections {
+                entry.push_str(&format!("```\n{}\n```\n\n", section.lines.join("\n")));
+            }
+
+            if result.len() + entry.len() > max_chars {
+                result.push_str(&format!(
+                    "*[Results truncated at {} characters, refine the query to see more]*\n",
+                    max_chars
+                ));
+                return Ok(result);
+            }
+            result.push_str(&entry);
+        }
+    }
+
+    if hits == 0 {
+        return Ok(format!("No matches found for '{}'", query));
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn docs_fixture() -> Result<TempDir, Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::write(
+            root.join("README.md"),
+            "# Overview\nThe LlmPool balances clients.\n",
+        )?;
+        fs::create_dir(root.join("src"))?;
+        fs::write(
+            root.join("src").join("pool.md"),
+            "line one\nline two\nstruct LLMPOOL\nline four\nline five\nline six\n",
+        )?;
+        Ok(temp_dir)
+    }
+
+    #[test]
+    fn test_search_reports_relative_paths_and_context() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = docs_fixture()?;
+        let components = vec![("auto-doc".to_string(), temp_dir.path().to_path_buf())];
+
+        let result = search_components(&components, "llmpool", MAX_RESPONSE_CHARS)?;
+
+        assert!(result.contains("## 📄 README.md (auto-doc)"));
+        assert!(result.contains(&format!(
+            "## 📄 {} (auto-doc)",
+            Path::new("src").join("pool.md").display()
+        )));
+        assert!(result.contains("3: struct LLMPOOL"));
+        assert!(result.contains("1- line one"));
+        assert!(result.contains("5- line five"));
+        assert!(!result.contains("line six"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_invalid_regex_falls_back_to_text() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("api.md"), "call parse_config( first\n")?;
+        let components = vec![("auto-doc".to_string(), temp_dir.path().to_path_buf())];
+
+        let result = search_components(&components, "parse_config(", MAX_RESPONSE_CHARS)?;
+        assert!(result.contains("1: call parse_config( first"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_truncates_and_reports_no_matches() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = docs_fixture()?;
+        let components = vec![("auto-doc".to_string(), temp_dir.path().to_path_buf())];
+
+        let result = search_components(&components, "line", 10)?;
+        assert!(result.contains("*[Results truncated at 10 characters"));
+
+        let result = search_components(&components, "nothing-here", MAX_RESPONSE_CHARS)?;
+        assert_eq!(result, "No matches found for 'nothing-here'");
+
+        Ok(())
+    }
+}